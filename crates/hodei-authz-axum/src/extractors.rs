@@ -1,23 +1,24 @@
 //! Axum extractors for authentication and authorization
 
-use async_trait::async_trait;
+use crate::middleware::AuthenticatedPrincipal;
+use crate::resolvers::SharedPrincipalResolver;
 use axum::{
-    extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
-};
-use axum_extra::{
-    headers::{authorization::Bearer, Authorization},
-    TypedHeader,
+    body::Body,
+    extract::{FromRef, FromRequestParts, State},
+    http::{request::Parts, Request, StatusCode},
+    middleware::Next,
+    response::Response,
 };
 use hodei_authz::RuntimeHodeiEntityMapper;
-use serde::de::DeserializeOwned;
 
 /// Error type for authentication failures
 #[derive(Debug)]
 pub enum AuthError {
     MissingToken,
     InvalidToken,
+    ExpiredToken,
     UserNotFound,
+    Internal(String),
 }
 
 impl axum::response::IntoResponse for AuthError {
@@ -25,18 +26,55 @@ impl axum::response::IntoResponse for AuthError {
         let (status, message) = match self {
             AuthError::MissingToken => (StatusCode::UNAUTHORIZED, "Missing authorization token"),
             AuthError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid authorization token"),
+            AuthError::ExpiredToken => (StatusCode::UNAUTHORIZED, "Expired authorization token"),
             AuthError::UserNotFound => (StatusCode::UNAUTHORIZED, "User not found"),
+            AuthError::Internal(e) => {
+                tracing::error!("Authentication error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Authentication failed")
+            }
         };
-        
+
         (status, message).into_response()
     }
 }
 
+/// Resolve the principal of `parts` and record it in the request extensions
+///
+/// A principal already resolved earlier in the request (e.g. by [`authenticate`])
+/// is reused.
+async fn resolve_principal<T>(
+    parts: &mut Parts,
+    resolver: &SharedPrincipalResolver<T>,
+) -> Result<T, AuthError>
+where
+    T: RuntimeHodeiEntityMapper + Clone + Send + Sync + 'static,
+{
+    if let Some(principal) = parts.extensions.get::<T>() {
+        return Ok(principal.clone());
+    }
+
+    let principal = resolver.resolve(parts).await?;
+    parts.extensions.insert(principal.clone());
+    parts
+        .extensions
+        .insert(AuthenticatedPrincipal::new(principal.clone()));
+
+    Ok(principal)
+}
+
 /// Extractor for authenticated users
-/// 
+///
+/// The principal is resolved with the [`PrincipalResolver`](crate::PrincipalResolver)
+/// stored in the router state.
+///
 /// # Example
-/// 
+///
 /// ```rust,ignore
+/// #[derive(Clone, FromRef)]
+/// struct AppState {
+///     resolver: SharedPrincipalResolver<User>,
+/// }
+///
 /// async fn handler(
 ///     AuthenticatedUser(user): AuthenticatedUser<User>,
 /// ) -> impl IntoResponse {
@@ -45,39 +83,46 @@ impl axum::response::IntoResponse for AuthError {
 /// ```
 pub struct AuthenticatedUser<T: RuntimeHodeiEntityMapper>(pub T);
 
-#[async_trait]
 impl<S, T> FromRequestParts<S> for AuthenticatedUser<T>
 where
-    T: RuntimeHodeiEntityMapper + DeserializeOwned + Send + Sync + 'static,
+    T: RuntimeHodeiEntityMapper + Clone + Send + Sync + 'static,
+    SharedPrincipalResolver<T>: FromRef<S>,
     S: Send + Sync,
 {
     type Rejection = AuthError;
-    
+
     async fn from_request_parts(
         parts: &mut Parts,
         state: &S,
     ) -> Result<Self, Self::Rejection> {
-        // Extract Bearer token from Authorization header
-        let TypedHeader(Authorization(bearer)) = TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
-            .await
-            .map_err(|_| AuthError::MissingToken)?;
-        
-        let token = bearer.token();
-        
-        // TODO: In a real implementation, you would:
-        // 1. Validate the JWT token
-        // 2. Extract user ID from token claims
-        // 3. Fetch user from database
-        // 4. Return AuthenticatedUser(user)
-        
-        // For now, this is a placeholder that demonstrates the API
-        // Users of the library will implement their own authentication logic
-        
-        // Example placeholder:
-        // let user = fetch_user_from_token(token).await
-        //     .map_err(|_| AuthError::UserNotFound)?;
-        // Ok(AuthenticatedUser(user))
-        
-        Err(AuthError::InvalidToken)
+        let resolver = SharedPrincipalResolver::<T>::from_ref(state);
+        resolve_principal(parts, &resolver).await.map(AuthenticatedUser)
     }
 }
+
+/// Authentication middleware
+///
+/// Resolves the principal before routing so that [`HodeiAuthLayer`](crate::HodeiAuthLayer)
+/// finds it in the request extensions. Must be added as an outer layer.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let app = Router::new()
+///     .route("/documents/{id}", get(get_document))
+///     .layer(auth_layer)
+///     .layer(axum::middleware::from_fn_with_state(resolver, authenticate::<User>));
+/// ```
+pub async fn authenticate<T>(
+    State(resolver): State<SharedPrincipalResolver<T>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AuthError>
+where
+    T: RuntimeHodeiEntityMapper + Clone + Send + Sync + 'static,
+{
+    let (mut parts, body) = req.into_parts();
+    resolve_principal(&mut parts, &resolver).await?;
+
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
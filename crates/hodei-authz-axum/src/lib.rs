@@ -5,22 +5,33 @@
 //! # Example
 //!
 //! ```rust,ignore
-//! use hodei_authz_axum::{AuthenticatedUser, authorize_middleware};
-//! use axum::{Router, routing::get};
+//! use hodei_authz_axum::{AuthenticatedUser, HodeiAuthLayer};
+//! use axum::{Router, routing::get, http::Method};
 //!
 //! async fn protected_handler(
 //!     AuthenticatedUser(user): AuthenticatedUser<User>,
 //! ) -> impl IntoResponse {
 //!     Json(user)
 //! }
-//! 
-//! // let app = Router::new()
-//! //     .route("/protected", get(protected_handler))
-//! //     .layer(middleware::from_fn(authorize_middleware));
+//!
+//! let app = Router::new()
+//!     .route("/documents/{id}", get(protected_handler))
+//!     .layer(
+//!         HodeiAuthLayer::new(auth_service)
+//!             .route(Method::GET, "/documents/{id}", DocumentAction::Read, load_document),
+//!     );
 //! ```
 
-// pub mod extractors;  // Temporarily disabled - needs fixing
+pub mod extractors;
 pub mod middleware;
+pub mod resolvers;
 
-// pub use extractors::{AuthenticatedUser, AuthError};
-pub use middleware::authorize_middleware;
+pub use extractors::{authenticate, AuthError, AuthenticatedUser};
+pub use middleware::{
+    AuthenticatedPrincipal, AuthorizationRejection, AuthorizationState, ContextProvider,
+    HodeiAuthLayer, HodeiAuthMiddleware, PathParams, ResourceLoader,
+};
+pub use resolvers::{
+    ApiKeyAuthenticator, HrnAuthenticator, JwtAuthenticator, PrincipalResolver,
+    RepositoryResolver, SharedPrincipalResolver,
+};
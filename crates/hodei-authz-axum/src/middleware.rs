@@ -1,44 +1,684 @@
 //! Axum middleware for authorization
+//!
+//! [`HodeiAuthLayer`] maps each protected route + HTTP method to a Hodei action
+//! and a resource loader, evaluates the request with an
+//! [`AuthorizationEvaluator`] (e.g. `HodeiAuthService`) and rejects denied
+//! requests with `403 Forbidden`. Requests to routes that are neither mapped
+//! nor declared public are denied as well.
 
+use async_trait::async_trait;
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
-    middleware::Next,
-    response::Response,
+    http::{request::Parts, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
 };
+use cedar_policy::Context;
+use hodei_authz::{
+    AuthorizationEvaluator, HodeiContext, RuntimeHodeiActionMapper, RuntimeHodeiEntityMapper,
+};
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use tower::{Layer, Service};
+
+/// Parameters captured from the request path, e.g. `{"id": "doc-1"}`
+pub type PathParams = HashMap<String, String>;
+
+/// Principal of the current request
+///
+/// Authentication inserts it into the request extensions; the authorization
+/// middleware reads it from there.
+#[derive(Clone)]
+pub struct AuthenticatedPrincipal(pub Arc<dyn RuntimeHodeiEntityMapper + Send + Sync>);
+
+impl AuthenticatedPrincipal {
+    /// Wrap any entity as the principal of the request
+    pub fn new(principal: impl RuntimeHodeiEntityMapper + Send + Sync + 'static) -> Self {
+        Self(Arc::new(principal))
+    }
+}
+
+/// Loads the resource targeted by a request from its path parameters
+///
+/// Implemented for async closures `Fn(PathParams) -> Future<Output = Result<Option<R>, E>>`.
+/// Returning `Ok(None)` rejects the request as if it had been denied, so that
+/// responses do not reveal which resources exist.
+#[async_trait]
+pub trait ResourceLoader: Send + Sync {
+    async fn load(
+        &self,
+        params: PathParams,
+    ) -> Result<Option<Box<dyn RuntimeHodeiEntityMapper + Send + Sync>>, String>;
+}
+
+#[async_trait]
+impl<F, Fut, R, E> ResourceLoader for F
+where
+    F: Fn(PathParams) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<R>, E>> + Send,
+    R: RuntimeHodeiEntityMapper + Send + Sync + 'static,
+    E: Display,
+{
+    async fn load(
+        &self,
+        params: PathParams,
+    ) -> Result<Option<Box<dyn RuntimeHodeiEntityMapper + Send + Sync>>, String> {
+        match (self)(params).await {
+            Ok(resource) => Ok(resource
+                .map(|r| Box::new(r) as Box<dyn RuntimeHodeiEntityMapper + Send + Sync>)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Builds the Cedar context of a request
+///
+/// Implemented for closures `Fn(&Parts) -> Result<C, E>` where `C` is a
+/// [`HodeiContext`], e.g. a `#[derive(HodeiContext)]` struct read from the
+/// request headers. An error rejects the request with `400 Bad Request`.
+pub trait ContextProvider: Send + Sync {
+    fn context(&self, parts: &Parts) -> Result<Context, String>;
+}
+
+impl<F, C, E> ContextProvider for F
+where
+    F: Fn(&Parts) -> Result<C, E> + Send + Sync,
+    C: HodeiContext,
+    E: Display,
+{
+    fn context(&self, parts: &Parts) -> Result<Context, String> {
+        (self)(parts)
+            .map_err(|e| e.to_string())?
+            .to_cedar_context()
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Wildcard(String),
+}
+
+/// Route template such as `/documents/{id}` or `/files/{*path}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RoutePattern {
+    segments: Vec<Segment>,
+}
+
+impl RoutePattern {
+    fn parse(pattern: &str) -> Self {
+        let segments = split_path(pattern)
+            .map(|segment| {
+                match segment
+                    .strip_prefix('{')
+                    .and_then(|s| s.strip_suffix('}'))
+                {
+                    Some(name) => match name.strip_prefix('*') {
+                        Some(rest) => Segment::Wildcard(rest.to_string()),
+                        None => Segment::Param(name.to_string()),
+                    },
+                    None => Segment::Literal(segment.to_string()),
+                }
+            })
+            .collect();
+
+        Self { segments }
+    }
+
+    /// Sort key putting literal segments before parameters and wildcards
+    fn specificity(&self) -> Vec<u8> {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(_) => 0,
+                Segment::Param(_) => 1,
+                Segment::Wildcard(_) => 2,
+            })
+            .collect()
+    }
+
+    /// Matches the percent-decoded segments of `path`
+    fn matches(&self, path: &str) -> Option<PathParams> {
+        let parts: Vec<Cow<'_, str>> = split_path(path)
+            .map(|segment| percent_decode_str(segment).decode_utf8().ok())
+            .collect::<Option<_>>()?;
+        let mut params = PathParams::new();
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Wildcard(name) => {
+                    if index >= parts.len() {
+                        return None;
+                    }
+                    params.insert(name.clone(), parts[index..].join("/"));
+                    return Some(params);
+                }
+                Segment::Literal(literal) => {
+                    if parts.get(index).map(|part| part.as_ref()) != Some(literal.as_str()) {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    params.insert(name.clone(), parts.get(index)?.to_string());
+                }
+            }
+        }
+
+        (parts.len() == self.segments.len()).then_some(params)
+    }
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty())
+}
+
+#[derive(Clone)]
+enum RouteAccess {
+    Public,
+    Protected {
+        action: Arc<dyn RuntimeHodeiActionMapper + Send + Sync>,
+        loader: Arc<dyn ResourceLoader>,
+        context: Option<Arc<dyn ContextProvider>>,
+    },
+}
+
+#[derive(Clone)]
+struct RouteRule {
+    method: Method,
+    pattern: RoutePattern,
+    access: RouteAccess,
+}
 
 /// Authorization middleware state
-/// 
-/// This can be extended to include the AuthorizationService
-/// and other dependencies needed for authorization
-pub struct AuthorizationState {
-    // Placeholder for authorization service
-    // pub auth_service: Arc<AuthorizationService<P, C>>,
+///
+/// Holds the authorization service and the route-to-action mapping of a router.
+pub struct AuthorizationState<E> {
+    service: Arc<E>,
+    /// Sorted from most to least specific pattern
+    routes: Vec<RouteRule>,
+    allow_unmapped: bool,
 }
 
-/// Authorization middleware function
-/// 
+impl<E: AuthorizationEvaluator> AuthorizationState<E> {
+    fn match_route(&self, method: &Method, path: &str) -> Option<(&RouteRule, PathParams)> {
+        self.routes
+            .iter()
+            .filter(|rule| rule.method == *method)
+            .find_map(|rule| rule.pattern.matches(path).map(|params| (rule, params)))
+    }
+
+    async fn authorize(&self, parts: &Parts) -> Result<(), AuthorizationRejection> {
+        let Some((rule, params)) = self.match_route(&parts.method, parts.uri.path()) else {
+            return if self.allow_unmapped {
+                Ok(())
+            } else {
+                Err(AuthorizationRejection::Unmapped)
+            };
+        };
+        let RouteAccess::Protected {
+            action,
+            loader,
+            context,
+        } = &rule.access
+        else {
+            return Ok(());
+        };
+
+        let principal = parts
+            .extensions
+            .get::<AuthenticatedPrincipal>()
+            .ok_or(AuthorizationRejection::Unauthenticated)?;
+
+        let context = match context {
+            Some(provider) => provider
+                .context(parts)
+                .map_err(AuthorizationRejection::InvalidContext)?,
+            None => Context::empty(),
+        };
+
+        let resource = loader
+            .load(params)
+            .await
+            .map_err(AuthorizationRejection::Internal)?
+            .ok_or(AuthorizationRejection::ResourceNotFound)?;
+
+        let decision = self
+            .service
+            .evaluate(principal.0.as_ref(), action.as_ref(), resource.as_ref(), context)
+            .await
+            .map_err(|e| AuthorizationRejection::Internal(e.to_string()))?;
+
+        if decision.is_allowed() {
+            Ok(())
+        } else {
+            Err(AuthorizationRejection::Forbidden {
+                action: action.to_cedar_action_euid().to_string(),
+                resource: resource.to_cedar_euid().to_string(),
+                determining_policies: decision.determining_policies,
+            })
+        }
+    }
+}
+
+/// Reasons why the middleware rejected a request
+///
+/// Missing resources, unmapped routes and denials all get the same `403`
+/// response; the details are only logged.
+#[derive(Debug)]
+pub enum AuthorizationRejection {
+    /// No authenticated principal in the request extensions
+    Unauthenticated,
+    /// The context provider of the route rejected the request
+    InvalidContext(String),
+    /// The resource loader did not find the target resource
+    ResourceNotFound,
+    /// The route is neither mapped nor public
+    Unmapped,
+    /// The policies denied the request
+    Forbidden {
+        action: String,
+        resource: String,
+        determining_policies: Vec<String>,
+    },
+    /// Loading the resource or evaluating the request failed
+    Internal(String),
+}
+
+impl IntoResponse for AuthorizationRejection {
+    fn into_response(self) -> Response {
+        let forbidden = (
+            StatusCode::FORBIDDEN,
+            serde_json::json!({ "error": "forbidden" }),
+        );
+
+        let (status, body) = match self {
+            AuthorizationRejection::Unauthenticated => (
+                StatusCode::UNAUTHORIZED,
+                serde_json::json!({ "error": "unauthenticated" }),
+            ),
+            AuthorizationRejection::InvalidContext(message) => {
+                tracing::debug!("Invalid authorization context: {}", message);
+                (
+                    StatusCode::BAD_REQUEST,
+                    serde_json::json!({ "error": "invalid_request" }),
+                )
+            }
+            AuthorizationRejection::ResourceNotFound => {
+                tracing::debug!("Authorization denied: resource not found");
+                forbidden
+            }
+            AuthorizationRejection::Unmapped => {
+                tracing::warn!("Authorization denied: route is not mapped to an action");
+                forbidden
+            }
+            AuthorizationRejection::Forbidden {
+                action,
+                resource,
+                determining_policies,
+            } => {
+                tracing::info!(
+                    action = %action,
+                    resource = %resource,
+                    policies = ?determining_policies,
+                    "Authorization denied"
+                );
+                forbidden
+            }
+            AuthorizationRejection::Internal(message) => {
+                tracing::error!("Authorization error: {}", message);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    serde_json::json!({ "error": "authorization_failed" }),
+                )
+            }
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Tower layer that authorizes requests with Cedar policies
+///
 /// # Example
-/// 
+///
 /// ```rust,ignore
+/// let auth_layer = HodeiAuthLayer::new(auth_service)
+///     .route(Method::GET, "/documents/{id}", DocumentAction::Read, load_document)
+///     .route(Method::DELETE, "/documents/{id}", DocumentAction::Delete, load_document)
+///     .public(Method::GET, "/health");
+///
 /// let app = Router::new()
-///     .route("/protected", get(handler))
-///     .layer(middleware::from_fn(authorize_middleware));
+///     .route("/documents/{id}", get(get_document).delete(delete_document))
+///     .route("/health", get(health))
+///     .layer(auth_layer);
 /// ```
-pub async fn authorize_middleware(
-    req: Request<Body>,
-    next: Next,
-) -> Result<Response, StatusCode> {
-    // TODO: Implement authorization logic
-    // 1. Extract user from request
-    // 2. Extract action and resource from request
-    // 3. Check authorization with AuthorizationService
-    // 4. If authorized, call next
-    // 5. If not, return 403 Forbidden
-    
-    // For now, pass through all requests
-    Ok(next.run(req).await)
-}
-
-// Note: create_authorization_layer removed due to type complexity
-// Users should use: axum::middleware::from_fn(authorize_middleware) directly
+pub struct HodeiAuthLayer<E> {
+    service: Arc<E>,
+    routes: Vec<RouteRule>,
+    allow_unmapped: bool,
+}
+
+impl<E> Clone for HodeiAuthLayer<E> {
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            routes: self.routes.clone(),
+            allow_unmapped: self.allow_unmapped,
+        }
+    }
+}
+
+impl<E: AuthorizationEvaluator> HodeiAuthLayer<E> {
+    /// Create a layer backed by the given authorization service
+    pub fn new(service: Arc<E>) -> Self {
+        Self {
+            service,
+            routes: Vec::new(),
+            allow_unmapped: false,
+        }
+    }
+
+    /// Protect `path` for `method` with `action` on the resource returned by `loader`
+    ///
+    /// `path` uses the Axum syntax: `{param}` captures a segment and `{*rest}`
+    /// the remainder of the path. When several routes match a request the most
+    /// specific one applies: `/documents/shared` before `/documents/{id}`.
+    /// The request is evaluated with an empty context.
+    pub fn route<A, L>(self, method: Method, path: &str, action: A, loader: L) -> Self
+    where
+        A: RuntimeHodeiActionMapper + Send + Sync + 'static,
+        L: ResourceLoader + 'static,
+    {
+        self.add_route(
+            method,
+            path,
+            RouteAccess::Protected {
+                action: Arc::new(action),
+                loader: Arc::new(loader),
+                context: None,
+            },
+        )
+    }
+
+    /// Like [`route`](Self::route), evaluating the request with the context
+    /// built by `context`
+    ///
+    /// Required for actions that declare a context in the schema.
+    ///
+    /// ```rust,ignore
+    /// layer.route_with_context(
+    ///     Method::POST,
+    ///     "/documents/{id}/share",
+    ///     DocumentAction::Share,
+    ///     load_document,
+    ///     |parts: &Parts| RequestContext::from_headers(&parts.headers),
+    /// )
+    /// ```
+    pub fn route_with_context<A, L, C>(
+        self,
+        method: Method,
+        path: &str,
+        action: A,
+        loader: L,
+        context: C,
+    ) -> Self
+    where
+        A: RuntimeHodeiActionMapper + Send + Sync + 'static,
+        L: ResourceLoader + 'static,
+        C: ContextProvider + 'static,
+    {
+        self.add_route(
+            method,
+            path,
+            RouteAccess::Protected {
+                action: Arc::new(action),
+                loader: Arc::new(loader),
+                context: Some(Arc::new(context)),
+            },
+        )
+    }
+
+    /// Let requests to `path` for `method` through without authorization
+    pub fn public(self, method: Method, path: &str) -> Self {
+        self.add_route(method, path, RouteAccess::Public)
+    }
+
+    /// Let requests to routes that are neither mapped nor public through
+    ///
+    /// By default they are denied, so that a missing or mistyped route does
+    /// not skip authorization.
+    pub fn allow_unmapped(mut self) -> Self {
+        self.allow_unmapped = true;
+        self
+    }
+
+    fn add_route(mut self, method: Method, path: &str, access: RouteAccess) -> Self {
+        self.routes.push(RouteRule {
+            method,
+            pattern: RoutePattern::parse(path),
+            access,
+        });
+        // Stable, so equally specific routes keep their order
+        self.routes.sort_by_key(|rule| rule.pattern.specificity());
+        self
+    }
+}
+
+impl<S, E> Layer<S> for HodeiAuthLayer<E> {
+    type Service = HodeiAuthMiddleware<S, E>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HodeiAuthMiddleware {
+            inner,
+            state: Arc::new(AuthorizationState {
+                service: self.service.clone(),
+                routes: self.routes.clone(),
+                allow_unmapped: self.allow_unmapped,
+            }),
+        }
+    }
+}
+
+/// Service produced by [`HodeiAuthLayer`]
+pub struct HodeiAuthMiddleware<S, E> {
+    inner: S,
+    state: Arc<AuthorizationState<E>>,
+}
+
+impl<S: Clone, E> Clone for HodeiAuthMiddleware<S, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S, E> Service<Request<Body>> for HodeiAuthMiddleware<S, E>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    E: AuthorizationEvaluator + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let state = self.state.clone();
+        // The ready service is taken and replaced by a fresh clone
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let (parts, body) = req.into_parts();
+
+        Box::pin(async move {
+            match state.authorize(&parts).await {
+                Ok(()) => inner.call(Request::from_parts(parts, body)).await,
+                Err(rejection) => Ok(rejection.into_response()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedar_policy::{Decision, Entity, EntityUid};
+    use hodei_authz::{AuthorizationDecision, AuthorizationError, VirtualEntityError};
+
+    struct DenyAll;
+
+    #[async_trait]
+    impl AuthorizationEvaluator for DenyAll {
+        async fn evaluate(
+            &self,
+            _principal: &(dyn RuntimeHodeiEntityMapper + Sync),
+            _action: &(dyn RuntimeHodeiActionMapper + Sync),
+            _resource: &(dyn RuntimeHodeiEntityMapper + Sync),
+            _context: Context,
+        ) -> Result<AuthorizationDecision, AuthorizationError> {
+            Ok(AuthorizationDecision {
+                decision: Decision::Deny,
+                determining_policies: vec!["internal-policy".to_string()],
+                errors: Vec::new(),
+            })
+        }
+    }
+
+    struct Read;
+
+    impl RuntimeHodeiActionMapper for Read {
+        fn to_cedar_action_euid(&self) -> EntityUid {
+            r#"Action::"Read""#.parse().unwrap()
+        }
+
+        fn creates_resource_from_payload(&self) -> bool {
+            false
+        }
+
+        fn get_payload_as_virtual_entity(
+            &self,
+            _context: &dyn std::any::Any,
+        ) -> Result<Option<Entity>, VirtualEntityError> {
+            Ok(None)
+        }
+    }
+
+    struct NoResource;
+
+    #[async_trait]
+    impl ResourceLoader for NoResource {
+        async fn load(
+            &self,
+            _params: PathParams,
+        ) -> Result<Option<Box<dyn RuntimeHodeiEntityMapper + Send + Sync>>, String> {
+            Ok(None)
+        }
+    }
+
+    fn state(layer: HodeiAuthLayer<DenyAll>) -> Arc<AuthorizationState<DenyAll>> {
+        layer.layer(()).state
+    }
+
+    fn parts(method: Method, path: &str) -> Parts {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[test]
+    fn test_literal_routes_take_priority() {
+        let state = state(
+            HodeiAuthLayer::new(Arc::new(DenyAll))
+                .route(Method::GET, "/docs/{id}", Read, NoResource)
+                .public(Method::GET, "/docs/shared"),
+        );
+
+        let (rule, _) = state.match_route(&Method::GET, "/docs/shared").unwrap();
+        assert!(matches!(rule.access, RouteAccess::Public));
+        let (rule, params) = state.match_route(&Method::GET, "/docs/doc-1").unwrap();
+        assert!(matches!(rule.access, RouteAccess::Protected { .. }));
+        assert_eq!(params.get("id"), Some(&"doc-1".to_string()));
+    }
+
+    #[test]
+    fn test_route_pattern_percent_decoding() {
+        let pattern = RoutePattern::parse("/documents/{id}");
+        let params = pattern.matches("/documents/annual%20report").unwrap();
+        assert_eq!(params.get("id"), Some(&"annual report".to_string()));
+
+        assert!(RoutePattern::parse("/docs/shared").matches("/docs/%73hared").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unmapped_routes_are_denied_by_default() {
+        let layer = HodeiAuthLayer::new(Arc::new(DenyAll)).public(Method::GET, "/health");
+
+        let strict = state(layer.clone());
+        assert!(strict.authorize(&parts(Method::GET, "/health")).await.is_ok());
+        assert!(matches!(
+            strict.authorize(&parts(Method::GET, "/admin")).await,
+            Err(AuthorizationRejection::Unmapped)
+        ));
+
+        let lenient = state(layer.allow_unmapped());
+        assert!(lenient.authorize(&parts(Method::GET, "/admin")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejections_do_not_leak_details() {
+        let denied = AuthorizationRejection::Forbidden {
+            action: r#"Action::"Read""#.to_string(),
+            resource: r#"Doc::"doc-1""#.to_string(),
+            determining_policies: vec!["internal-policy".to_string()],
+        }
+        .into_response();
+        let missing = AuthorizationRejection::ResourceNotFound.into_response();
+
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+        assert_eq!(missing.status(), StatusCode::FORBIDDEN);
+        let denied = axum::body::to_bytes(denied.into_body(), usize::MAX).await.unwrap();
+        let missing = axum::body::to_bytes(missing.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(denied, missing);
+    }
+
+    #[test]
+    fn test_route_pattern_params() {
+        let pattern = RoutePattern::parse("/documents/{id}");
+        let params = pattern.matches("/documents/doc-1").unwrap();
+        assert_eq!(params.get("id"), Some(&"doc-1".to_string()));
+    }
+
+    #[test]
+    fn test_route_pattern_literal_mismatch() {
+        let pattern = RoutePattern::parse("/documents/{id}");
+        assert!(pattern.matches("/users/u-1").is_none());
+        assert!(pattern.matches("/documents").is_none());
+        assert!(pattern.matches("/documents/doc-1/check").is_none());
+    }
+
+    #[test]
+    fn test_route_pattern_wildcard() {
+        let pattern = RoutePattern::parse("/files/{*path}");
+        let params = pattern.matches("/files/reports/2025/q1.pdf").unwrap();
+        assert_eq!(params.get("path"), Some(&"reports/2025/q1.pdf".to_string()));
+        assert!(pattern.matches("/files").is_none());
+    }
+}
@@ -0,0 +1,292 @@
+//! Principal resolution strategies for the `AuthenticatedUser` extractor
+//!
+//! Authentication is split in two steps:
+//!
+//! 1. An [`HrnAuthenticator`] validates the request credentials (JWT, API key)
+//!    and returns the [`Hrn`] of the caller.
+//! 2. A [`PrincipalResolver`] turns the request into the principal entity, e.g.
+//!    by looking up that `Hrn` in a repository with [`RepositoryResolver`].
+//!
+//! Applications with their own login system can implement [`PrincipalResolver`]
+//! directly.
+
+use crate::extractors::AuthError;
+use async_trait::async_trait;
+use axum::http::request::Parts;
+use axum_extra::headers::{authorization::Bearer, Authorization, HeaderMapExt};
+use hodei_hrn::Hrn;
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Resolves the principal entity of a request
+#[async_trait]
+pub trait PrincipalResolver<T>: Send + Sync {
+    async fn resolve(&self, parts: &Parts) -> Result<T, AuthError>;
+}
+
+/// Shared resolver stored in the router state
+///
+/// The `AuthenticatedUser<T>` extractor requires `SharedPrincipalResolver<T>: FromRef<S>`,
+/// which `#[derive(FromRef)]` provides for a state field of this type.
+pub type SharedPrincipalResolver<T> = Arc<dyn PrincipalResolver<T>>;
+
+/// Validates request credentials and returns the caller's HRN
+#[async_trait]
+pub trait HrnAuthenticator: Send + Sync {
+    async fn authenticate(&self, parts: &Parts) -> Result<Hrn, AuthError>;
+}
+
+/// Bearer JWT authenticator (HS256 or RS256)
+///
+/// The caller's HRN is read from the `sub` claim unless another claim is
+/// configured with [`with_hrn_claim`](Self::with_hrn_claim).
+pub struct JwtAuthenticator {
+    key: DecodingKey,
+    validation: Validation,
+    hrn_claim: String,
+}
+
+impl JwtAuthenticator {
+    /// Validate tokens signed with a shared HMAC secret
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self::new(DecodingKey::from_secret(secret), Algorithm::HS256)
+    }
+
+    /// Validate tokens signed with an RSA key, given its public key in PEM format
+    pub fn rs256_pem(public_key_pem: &[u8]) -> Result<Self, AuthError> {
+        let key = DecodingKey::from_rsa_pem(public_key_pem)
+            .map_err(|e| AuthError::Internal(format!("Invalid RSA public key: {}", e)))?;
+        Ok(Self::new(key, Algorithm::RS256))
+    }
+
+    fn new(key: DecodingKey, algorithm: Algorithm) -> Self {
+        Self {
+            key,
+            validation: Validation::new(algorithm),
+            hrn_claim: "sub".to_string(),
+        }
+    }
+
+    /// Read the HRN from a claim other than `sub`
+    pub fn with_hrn_claim(mut self, claim: impl Into<String>) -> Self {
+        self.hrn_claim = claim.into();
+        self
+    }
+
+    /// Require the `iss` claim to match
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.validation.set_issuer(&[issuer]);
+        self
+    }
+
+    /// Require the `aud` claim to match
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.validation.set_audience(&[audience]);
+        self
+    }
+
+    /// Validate a raw token and return the HRN it carries
+    pub fn validate(&self, token: &str) -> Result<Hrn, AuthError> {
+        let data = jsonwebtoken::decode::<HashMap<String, serde_json::Value>>(
+            token,
+            &self.key,
+            &self.validation,
+        )
+        .map_err(|e| match e.kind() {
+            ErrorKind::ExpiredSignature => AuthError::ExpiredToken,
+            _ => AuthError::InvalidToken,
+        })?;
+
+        data.claims
+            .get(&self.hrn_claim)
+            .and_then(|value| value.as_str())
+            .and_then(|value| value.parse::<Hrn>().ok())
+            .ok_or(AuthError::InvalidToken)
+    }
+}
+
+#[async_trait]
+impl HrnAuthenticator for JwtAuthenticator {
+    async fn authenticate(&self, parts: &Parts) -> Result<Hrn, AuthError> {
+        let Authorization(bearer) = parts
+            .headers
+            .typed_get::<Authorization<Bearer>>()
+            .ok_or(AuthError::MissingToken)?;
+
+        self.validate(bearer.token())
+    }
+}
+
+/// Static API key authenticator
+///
+/// Keys are read from the `x-api-key` header by default.
+pub struct ApiKeyAuthenticator {
+    header: String,
+    keys: HashMap<String, Hrn>,
+}
+
+impl Default for ApiKeyAuthenticator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiKeyAuthenticator {
+    /// Create an authenticator without keys
+    pub fn new() -> Self {
+        Self {
+            header: "x-api-key".to_string(),
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Register an API key for the given principal
+    pub fn with_key(mut self, key: impl Into<String>, hrn: Hrn) -> Self {
+        self.keys.insert(key.into(), hrn);
+        self
+    }
+
+    /// Read the key from a different header
+    pub fn with_header(mut self, header: impl Into<String>) -> Self {
+        self.header = header.into();
+        self
+    }
+}
+
+#[async_trait]
+impl HrnAuthenticator for ApiKeyAuthenticator {
+    async fn authenticate(&self, parts: &Parts) -> Result<Hrn, AuthError> {
+        let key = parts
+            .headers
+            .get(self.header.as_str())
+            .ok_or(AuthError::MissingToken)?
+            .to_str()
+            .map_err(|_| AuthError::InvalidToken)?;
+
+        self.keys.get(key).cloned().ok_or(AuthError::InvalidToken)
+    }
+}
+
+/// Resolver that authenticates the caller and loads the entity with a user-supplied lookup
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let resolver = RepositoryResolver::new(JwtAuthenticator::hs256(secret), move |hrn: Hrn| {
+///     let repo = repo.clone();
+///     async move { repo.find_user(&hrn).await }
+/// });
+/// let state = AppState { resolver: Arc::new(resolver) as SharedPrincipalResolver<User> };
+/// ```
+pub struct RepositoryResolver<A, F, T> {
+    authenticator: A,
+    lookup: F,
+    _principal: PhantomData<fn() -> T>,
+}
+
+impl<A, F, T> RepositoryResolver<A, F, T> {
+    pub fn new(authenticator: A, lookup: F) -> Self {
+        Self {
+            authenticator,
+            lookup,
+            _principal: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<A, F, Fut, T, E> PrincipalResolver<T> for RepositoryResolver<A, F, T>
+where
+    A: HrnAuthenticator,
+    F: Fn(Hrn) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<T>, E>> + Send,
+    T: Send + 'static,
+    E: Display,
+{
+    async fn resolve(&self, parts: &Parts) -> Result<T, AuthError> {
+        let hrn = self.authenticator.authenticate(parts).await?;
+
+        (self.lookup)(hrn)
+            .await
+            .map_err(|e| AuthError::Internal(e.to_string()))?
+            .ok_or(AuthError::UserNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    fn parts_with_header(name: &str, value: &str) -> Parts {
+        Request::builder()
+            .header(name, value)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    fn test_hrn() -> Hrn {
+        "hrn:hodei:docapp:global:tenant-1:user/alice".parse().unwrap()
+    }
+
+    fn token(secret: &[u8], exp: u64) -> String {
+        let claims = serde_json::json!({ "sub": test_hrn().to_string(), "exp": exp });
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_jwt_hs256_valid_token() {
+        let authenticator = JwtAuthenticator::hs256(b"secret");
+        let parts = parts_with_header("authorization", &format!("Bearer {}", token(b"secret", u64::MAX / 2)));
+
+        let hrn = authenticator.authenticate(&parts).await.unwrap();
+        assert_eq!(hrn, test_hrn());
+    }
+
+    #[tokio::test]
+    async fn test_jwt_wrong_secret() {
+        let authenticator = JwtAuthenticator::hs256(b"secret");
+        let parts = parts_with_header("authorization", &format!("Bearer {}", token(b"other", u64::MAX / 2)));
+
+        let result = authenticator.authenticate(&parts).await;
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+    }
+
+    #[tokio::test]
+    async fn test_jwt_expired_token() {
+        let authenticator = JwtAuthenticator::hs256(b"secret");
+        let parts = parts_with_header("authorization", &format!("Bearer {}", token(b"secret", 1)));
+
+        let result = authenticator.authenticate(&parts).await;
+        assert!(matches!(result, Err(AuthError::ExpiredToken)));
+    }
+
+    #[tokio::test]
+    async fn test_api_key() {
+        let authenticator = ApiKeyAuthenticator::new().with_key("key-1", test_hrn());
+
+        let parts = parts_with_header("x-api-key", "key-1");
+        assert_eq!(authenticator.authenticate(&parts).await.unwrap(), test_hrn());
+
+        let parts = parts_with_header("x-api-key", "unknown");
+        assert!(matches!(authenticator.authenticate(&parts).await, Err(AuthError::InvalidToken)));
+    }
+
+    #[tokio::test]
+    async fn test_repository_resolver_user_not_found() {
+        let resolver = RepositoryResolver::new(
+            ApiKeyAuthenticator::new().with_key("key-1", test_hrn()),
+            |_hrn: Hrn| async { Ok::<Option<String>, String>(None) },
+        );
+
+        let parts = parts_with_header("x-api-key", "key-1");
+        assert!(matches!(resolver.resolve(&parts).await, Err(AuthError::UserNotFound)));
+    }
+}
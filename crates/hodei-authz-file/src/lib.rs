@@ -0,0 +1,443 @@
+//! File-system policy store for Hodei authorization framework
+//!
+//! This crate maps a directory of `.cedar` files onto the `PolicyStore` trait
+//! and watches it for changes, so edits made on disk reach running services
+//! through the `CacheInvalidation` trait.
+
+use async_trait::async_trait;
+use cedar_policy::{Policy, PolicyId, PolicySet, Schema, Validator};
+use hodei_authz::memory::policies_from_source;
+use hodei_authz::{
+    validate_policy, CacheError, CacheInvalidation, PolicyLoadReport, PolicyStore,
+    PolicyStoreError, RejectedPolicy,
+};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Default quiet period before a burst of file events is reported
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(250);
+
+type Callback = Arc<dyn Fn() + Send + Sync>;
+
+/// Handle to a running directory watch
+///
+/// Watching stops when the handle is dropped.
+pub struct PolicyDirWatcher {
+    callback: Callback,
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+/// Policy store backed by a directory of `.cedar` files
+///
+/// Every read goes to disk, so the store always reflects the current state of
+/// the directory. Policy ids follow [`policies_from_source`]: an `@id("...")`
+/// annotation wins, otherwise the file stem is used (`<stem>-<n>` for files
+/// holding several policies).
+///
+/// Unannotated policies in multi-policy files are identified by position, so
+/// deleting one renumbers the ones after it. Annotate them with `@id` if they
+/// are managed through the API.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let store = FilePolicyStore::new("./policies")?;
+/// store.subscribe_to_invalidations(|| println!("policies changed")).await?;
+/// ```
+pub struct FilePolicyStore {
+    dir: PathBuf,
+    debounce: Duration,
+    validator: Option<Validator>,
+    /// Serializes read-modify-write cycles on the directory
+    write_lock: tokio::sync::Mutex<()>,
+    subscription: Mutex<Option<PolicyDirWatcher>>,
+}
+
+impl FilePolicyStore {
+    /// Create a store over `dir`, creating the directory if it does not exist
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, PolicyStoreError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            PolicyStoreError::Internal(format!("Cannot create {}: {}", dir.display(), e))
+        })?;
+
+        Ok(Self {
+            dir,
+            debounce: DEFAULT_DEBOUNCE,
+            validator: None,
+            write_lock: tokio::sync::Mutex::new(()),
+            subscription: Mutex::new(None),
+        })
+    }
+
+    /// Override the quiet period used to coalesce file events
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Validate every write made through the store against `schema`
+    ///
+    /// Files edited directly on disk are not validated.
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.validator = Some(Validator::new(schema));
+        self
+    }
+
+    /// Directory backing this store
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Watch the directory and return a handle to the watch
+    ///
+    /// The callback is invoked once per burst of changes to `.cedar` files,
+    /// after no further events arrive for the configured debounce period.
+    /// It runs on the watcher thread, outside any async runtime.
+    pub fn watch<F>(&self, callback: F) -> Result<PolicyDirWatcher, CacheError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let callback: Callback = Arc::new(callback);
+        let handler = callback.clone();
+
+        let mut debouncer = new_debouncer(self.debounce, move |result: DebounceEventResult| {
+            match result {
+                Ok(events) if events.iter().any(|event| is_policy_file(&event.path)) => {
+                    tracing::debug!("Policy directory changed, notifying subscriber");
+                    handler();
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Policy directory watch error: {}", e),
+            }
+        })
+        .map_err(|e| CacheError::Subscribe(e.to_string()))?;
+
+        debouncer
+            .watcher()
+            .watch(&self.dir, RecursiveMode::NonRecursive)
+            .map_err(|e| CacheError::Subscribe(format!("{}: {}", self.dir.display(), e)))?;
+
+        Ok(PolicyDirWatcher {
+            callback,
+            _debouncer: debouncer,
+        })
+    }
+
+    /// Stop the watch started through `subscribe_to_invalidations`, if any
+    pub fn unwatch(&self) {
+        self.subscription.lock().unwrap().take();
+    }
+
+    /// Run `f` with the directory on the blocking thread pool
+    ///
+    /// Every file-system access of the async API goes through here so that it
+    /// never blocks the runtime threads.
+    async fn with_dir<T, F>(&self, f: F) -> Result<T, PolicyStoreError>
+    where
+        F: FnOnce(&Path) -> Result<T, PolicyStoreError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || f(&dir))
+            .await
+            .map_err(|e| PolicyStoreError::Internal(e.to_string()))?
+    }
+
+    /// Validate the policy against the schema, if one is configured
+    fn check_policy(&self, id: &str, content: &str) -> Result<(), PolicyStoreError> {
+        if let Some(validator) = &self.validator {
+            let policy = Policy::parse(Some(PolicyId::new(id)), content)
+                .map_err(|e| PolicyStoreError::Parse(e.to_string()))?;
+            validate_policy(validator, &policy)?;
+        }
+        Ok(())
+    }
+}
+
+/// A policy file and its policies as `(id, content)` pairs
+type PolicyFile = (PathBuf, Vec<(String, String)>);
+
+/// Paths of every policy file in `dir`, in name order
+fn policy_paths(dir: &Path) -> Result<Vec<PathBuf>, PolicyStoreError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        PolicyStoreError::Internal(format!("Cannot read {}: {}", dir.display(), e))
+    })?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_policy_file(path))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Read every policy file in `dir` as `(path, policies)`, in name order
+fn read_files(dir: &Path) -> Result<Vec<PolicyFile>, PolicyStoreError> {
+    policy_paths(dir)?
+        .into_iter()
+        .map(|path| {
+            let policies = read_policy_file(&path)?;
+            Ok((path, policies))
+        })
+        .collect()
+}
+
+/// Find the file in `dir` holding policy `id`
+fn find_policy(dir: &Path, id: &str) -> Result<PolicyFile, PolicyStoreError> {
+    read_files(dir)?
+        .into_iter()
+        .find(|(_, policies)| policies.iter().any(|(existing, _)| existing == id))
+        .ok_or_else(|| PolicyStoreError::NotFound(id.to_string()))
+}
+
+/// Read the policies of a single file as `(id, content)` pairs
+fn read_policy_file(path: &Path) -> Result<Vec<(String, String)>, PolicyStoreError> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        PolicyStoreError::Internal(format!("Cannot read {}: {}", path.display(), e))
+    })?;
+    policies_from_source(&name, &source)
+}
+
+/// Returns true for `.cedar` files; temporary files written by the store are ignored
+fn is_policy_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "cedar")
+}
+
+/// Ids that can be used as file names as-is
+fn is_safe_file_name(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Parse `content` as exactly one policy and return its id
+///
+/// `default_id` is used when the policy has no `@id` annotation.
+fn single_policy_id(default_id: &str, content: &str) -> Result<String, PolicyStoreError> {
+    let set = PolicySet::from_str(content).map_err(|e| PolicyStoreError::Parse(e.to_string()))?;
+    if set.policies().count() != 1 || set.templates().count() != 0 {
+        return Err(PolicyStoreError::Parse(format!(
+            "Policy {} must contain exactly one static policy",
+            default_id
+        )));
+    }
+
+    let mut policies = policies_from_source(default_id, content)?;
+    Ok(policies.remove(0).0)
+}
+
+/// Write `content` to `path` through a temporary file and a rename
+///
+/// Watchers never observe a half-written policy file.
+fn write_atomic(path: &Path, content: &str) -> Result<(), PolicyStoreError> {
+    let tmp = path.with_extension("cedar.tmp");
+    std::fs::write(&tmp, content)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| PolicyStoreError::Internal(format!("Cannot write {}: {}", path.display(), e)))
+}
+
+/// Write the remaining policies of a file back, or remove it when empty
+fn rewrite_file(path: &Path, policies: &[(String, String)]) -> Result<(), PolicyStoreError> {
+    if policies.is_empty() {
+        return std::fs::remove_file(path).map_err(|e| {
+            PolicyStoreError::Internal(format!("Cannot remove {}: {}", path.display(), e))
+        });
+    }
+
+    let content: Vec<&str> = policies.iter().map(|(_, content)| content.as_str()).collect();
+    write_atomic(path, &format!("{}\n", content.join("\n\n")))
+}
+
+#[async_trait]
+impl PolicyStore for FilePolicyStore {
+    /// Writes the policy to `<id>.cedar`
+    ///
+    /// The id comes from the `@id` annotation when present, otherwise a UUID
+    /// is generated. Ids that are not valid file names are stored under a
+    /// UUID file name and keep their id through the annotation.
+    async fn create_policy(&self, content: String) -> Result<String, PolicyStoreError> {
+        let _guard = self.write_lock.lock().await;
+
+        let generated = uuid::Uuid::new_v4().to_string();
+        let id = single_policy_id(&generated, &content)?;
+        self.check_policy(&id, &content)?;
+
+        self.with_dir(move |dir| {
+            let files = read_files(dir)?;
+            if files
+                .iter()
+                .any(|(_, policies)| policies.iter().any(|(existing, _)| existing == &id))
+            {
+                return Err(PolicyStoreError::AlreadyExists(id));
+            }
+
+            let mut path = dir.join(format!("{}.cedar", id));
+            if !is_safe_file_name(&id) || path.exists() {
+                path = dir.join(format!("{}.cedar", generated));
+            }
+            write_atomic(&path, &content)?;
+
+            tracing::info!("Created policy {} in {}", id, path.display());
+            Ok(id)
+        })
+        .await
+    }
+
+    async fn get_policy(&self, id: &str) -> Result<Option<String>, PolicyStoreError> {
+        let id = id.to_string();
+        self.with_dir(move |dir| {
+            Ok(read_files(dir)?
+                .into_iter()
+                .flat_map(|(_, policies)| policies)
+                .find(|(existing, _)| *existing == id)
+                .map(|(_, content)| content))
+        })
+        .await
+    }
+
+    async fn list_policies(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        self.with_dir(|dir| {
+            Ok(read_files(dir)?
+                .into_iter()
+                .flat_map(|(_, policies)| policies)
+                .collect())
+        })
+        .await
+    }
+
+    /// Rewrites the file holding the policy
+    ///
+    /// The new content must resolve to the same id, so an `@id` annotation
+    /// cannot be changed through an update.
+    async fn update_policy(&self, id: &str, content: String) -> Result<(), PolicyStoreError> {
+        let _guard = self.write_lock.lock().await;
+
+        let new_id = single_policy_id(id, &content)?;
+        if new_id != id {
+            return Err(PolicyStoreError::Parse(format!(
+                "Policy {} cannot change its id to {}",
+                id, new_id
+            )));
+        }
+        self.check_policy(id, &content)?;
+
+        let id = id.to_string();
+        self.with_dir(move |dir| {
+            let (path, mut policies) = find_policy(dir, &id)?;
+            for entry in policies.iter_mut().filter(|(existing, _)| *existing == id) {
+                entry.1 = content.clone();
+            }
+            rewrite_file(&path, &policies)
+        })
+        .await
+    }
+
+    async fn delete_policy(&self, id: &str) -> Result<(), PolicyStoreError> {
+        let _guard = self.write_lock.lock().await;
+
+        let id = id.to_string();
+        self.with_dir(move |dir| {
+            let (path, mut policies) = find_policy(dir, &id)?;
+            policies.retain(|(existing, _)| *existing != id);
+            rewrite_file(&path, &policies)
+        })
+        .await
+    }
+
+    async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
+        let parsed = self
+            .list_policies()
+            .await?
+            .into_iter()
+            .map(|(id, content)| {
+                Policy::parse(Some(PolicyId::new(&id)), &content)
+                    .map_err(|e| PolicyStoreError::Parse(format!("{}: {}", id, e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        PolicySet::from_policies(parsed).map_err(|e| PolicyStoreError::Internal(e.to_string()))
+    }
+
+    /// Skips unreadable files as a whole, reported under their file name
+    async fn load_policies_lenient(&self) -> Result<PolicyLoadReport, PolicyStoreError> {
+        let files = self
+            .with_dir(|dir| {
+                Ok(policy_paths(dir)?
+                    .into_iter()
+                    .map(|path| {
+                        let policies = read_policy_file(&path);
+                        (path, policies)
+                    })
+                    .collect::<Vec<_>>())
+            })
+            .await?;
+
+        let mut report = PolicyLoadReport::default();
+        for (path, policies) in files {
+            let policies = match policies {
+                Ok(policies) => policies,
+                Err(e) => {
+                    report.rejected.push(RejectedPolicy {
+                        policy_id: path.display().to_string(),
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            for (id, content) in policies {
+                let added = Policy::parse(Some(PolicyId::new(&id)), &content)
+                    .map_err(|e| e.to_string())
+                    .and_then(|policy| report.policy_set.add(policy).map_err(|e| e.to_string()));
+                if let Err(reason) = added {
+                    report.rejected.push(RejectedPolicy { policy_id: id, reason });
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[async_trait]
+impl CacheInvalidation for FilePolicyStore {
+    /// Invokes the current subscriber directly
+    ///
+    /// Changes written through the store are also picked up by the watcher;
+    /// this is useful to force a reload without touching the directory.
+    async fn invalidate_policies(&self) -> Result<(), CacheError> {
+        let callback = self
+            .subscription
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|subscription| subscription.callback.clone());
+        if let Some(callback) = callback {
+            callback();
+        }
+        Ok(())
+    }
+
+    /// Starts a directory watch owned by this store
+    ///
+    /// Any previous watch is stopped. Use [`FilePolicyStore::watch`] to manage
+    /// the watch handle directly.
+    async fn subscribe_to_invalidations<F>(&self, callback: F) -> Result<(), CacheError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let watcher = self.watch(callback)?;
+        self.subscription.lock().unwrap().replace(watcher);
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Integration tests for the file-system policy store
+
+use hodei_authz::{CacheInvalidation, PolicyStore, PolicyStoreError};
+use hodei_authz_file::FilePolicyStore;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const PERMIT_ALL: &str = "permit(principal, action, resource);";
+
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("hodei-file-store-{}", uuid::Uuid::new_v4()))
+}
+
+#[tokio::test]
+async fn test_ids_from_files_and_annotations() {
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("public.cedar"), PERMIT_ALL).unwrap();
+    std::fs::write(
+        dir.join("documents.cedar"),
+        r#"
+        @id("owners")
+        permit(principal, action, resource) when { principal == resource.owner };
+        forbid(principal, action, resource) when { resource.locked };
+        "#,
+    )
+    .unwrap();
+
+    let store = FilePolicyStore::new(&dir).unwrap();
+    let ids: Vec<String> = store
+        .list_policies()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(ids, vec!["owners", "documents-1", "public"]);
+
+    let set = store.load_all_policies().await.unwrap();
+    assert_eq!(set.policies().count(), 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_crud_round_trip() {
+    let dir = temp_dir();
+    let store = FilePolicyStore::new(&dir).unwrap();
+
+    let id = store.create_policy(PERMIT_ALL.to_string()).await.unwrap();
+    assert!(dir.join(format!("{}.cedar", id)).exists());
+    assert_eq!(store.get_policy(&id).await.unwrap(), Some(PERMIT_ALL.to_string()));
+
+    let annotated = r#"@id("admins") permit(principal, action, resource);"#;
+    let admins = store.create_policy(annotated.to_string()).await.unwrap();
+    assert_eq!(admins, "admins");
+    assert!(dir.join("admins.cedar").exists());
+
+    let duplicate = store.create_policy(annotated.to_string()).await;
+    assert!(matches!(duplicate, Err(PolicyStoreError::AlreadyExists(_))));
+
+    let forbid = "forbid(principal, action, resource);";
+    store.update_policy(&id, forbid.to_string()).await.unwrap();
+    assert_eq!(store.get_policy(&id).await.unwrap(), Some(forbid.to_string()));
+
+    let renamed = store
+        .update_policy("admins", r#"@id("root") permit(principal, action, resource);"#.to_string())
+        .await;
+    assert!(matches!(renamed, Err(PolicyStoreError::Parse(_))));
+
+    store.delete_policy(&id).await.unwrap();
+    assert!(!dir.join(format!("{}.cedar", id)).exists());
+    let missing = store.delete_policy(&id).await;
+    assert!(matches!(missing, Err(PolicyStoreError::NotFound(_))));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_list_policies_contract() {
+    let dir = temp_dir();
+    let store = FilePolicyStore::new(&dir).unwrap();
+
+    hodei_authz::testing::check_list_policies(&store).await;
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_delete_from_multi_policy_file() {
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("shared.cedar"),
+        r#"
+        @id("a") permit(principal, action, resource);
+        @id("b") forbid(principal, action, resource);
+        "#,
+    )
+    .unwrap();
+
+    let store = FilePolicyStore::new(&dir).unwrap();
+    store.delete_policy("a").await.unwrap();
+
+    let ids: Vec<String> = store
+        .list_policies()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(ids, vec!["b"]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_lenient_load_skips_broken_files() {
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("good.cedar"), PERMIT_ALL).unwrap();
+    std::fs::write(dir.join("broken.cedar"), "permit(principal, action").unwrap();
+
+    let store = FilePolicyStore::new(&dir).unwrap();
+    assert!(store.load_all_policies().await.is_err());
+
+    let report = store.load_policies_lenient().await.unwrap();
+    assert_eq!(report.policy_set.policies().count(), 1);
+    assert_eq!(report.rejected.len(), 1);
+    assert!(report.rejected[0].policy_id.ends_with("broken.cedar"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_content() {
+    let dir = temp_dir();
+    let store = FilePolicyStore::new(&dir).unwrap();
+
+    let result = store.create_policy("permit(principal, action".to_string()).await;
+    assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+
+    let two = format!("{}\n{}", PERMIT_ALL, PERMIT_ALL);
+    let result = store.create_policy(two).await;
+    assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+
+    assert!(store.list_policies().await.unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_with_schema_rejects_invalid_policy() {
+    let dir = temp_dir();
+    let (schema, _) = cedar_policy::Schema::from_cedarschema_str(
+        "entity User; action Read appliesTo { principal: User, resource: User };",
+    )
+    .unwrap();
+    let store = FilePolicyStore::new(&dir).unwrap().with_schema(schema);
+
+    let result = store
+        .create_policy("permit(principal is Group, action, resource);".to_string())
+        .await;
+    assert!(matches!(result, Err(PolicyStoreError::Validation(_))));
+    assert!(store.list_policies().await.unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_watch_debounces_changes() {
+    let dir = temp_dir();
+    let store = FilePolicyStore::new(&dir)
+        .unwrap()
+        .with_debounce(Duration::from_millis(100));
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    store
+        .subscribe_to_invalidations(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .await
+        .unwrap();
+
+    // Non-policy files do not trigger notifications
+    std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    // A burst of writes is coalesced instead of firing once per file
+    for n in 0..5 {
+        std::fs::write(dir.join(format!("p{}.cedar", n)), PERMIT_ALL).unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    let after_burst = calls.load(Ordering::SeqCst);
+    assert!((1..5).contains(&after_burst));
+
+    store.invalidate_policies().await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), after_burst + 1);
+
+    store.unwatch();
+    std::fs::write(dir.join("p9.cedar"), PERMIT_ALL).unwrap();
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), after_burst + 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
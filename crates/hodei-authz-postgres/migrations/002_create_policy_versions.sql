@@ -0,0 +1,24 @@
+-- Policy versioning for Hodei authorization framework
+ALTER TABLE policies ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+CREATE TABLE IF NOT EXISTS policy_versions (
+    policy_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    content TEXT NOT NULL,
+    author TEXT,
+    change_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (policy_id, version)
+);
+
+-- Existing policies start their history at version 1
+INSERT INTO policy_versions (policy_id, version, content, created_at)
+SELECT id, 1, content, COALESCE(updated_at, created_at)
+FROM policies
+ON CONFLICT DO NOTHING;
+
+-- Comments
+COMMENT ON TABLE policy_versions IS 'Every recorded version of each policy; kept after the policy is deleted';
+COMMENT ON COLUMN policies.version IS 'Current version, used for optimistic concurrency';
+COMMENT ON COLUMN policy_versions.author IS 'User or system that made the change';
+COMMENT ON COLUMN policy_versions.change_reason IS 'Free-form explanation of the change';
@@ -0,0 +1,29 @@
+-- Policy templates and template-linked policies
+CREATE TABLE IF NOT EXISTS policy_templates (
+    id TEXT PRIMARY KEY,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS policy_template_links (
+    id TEXT PRIMARY KEY,
+    template_id TEXT NOT NULL REFERENCES policy_templates(id) ON DELETE CASCADE,
+    principal_type TEXT,
+    principal_hrn TEXT,
+    resource_type TEXT,
+    resource_hrn TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK ((principal_type IS NULL) = (principal_hrn IS NULL)),
+    CHECK ((resource_type IS NULL) = (resource_hrn IS NULL))
+);
+
+-- Indexes for listing the links of a template or of a shared entity
+CREATE INDEX IF NOT EXISTS idx_policy_template_links_template ON policy_template_links(template_id);
+CREATE INDEX IF NOT EXISTS idx_policy_template_links_principal ON policy_template_links(principal_hrn);
+CREATE INDEX IF NOT EXISTS idx_policy_template_links_resource ON policy_template_links(resource_hrn);
+
+-- Comments
+COMMENT ON TABLE policy_templates IS 'Cedar policy templates with ?principal/?resource slots';
+COMMENT ON TABLE policy_template_links IS 'Templates linked to concrete entities; each row becomes a policy';
+COMMENT ON COLUMN policy_template_links.principal_type IS 'Cedar entity type bound to ?principal';
+COMMENT ON COLUMN policy_template_links.principal_hrn IS 'HRN bound to ?principal';
@@ -0,0 +1,20 @@
+-- Policy metadata for Hodei authorization framework
+ALTER TABLE policies
+    ADD COLUMN IF NOT EXISTS name TEXT,
+    ADD COLUMN IF NOT EXISTS description TEXT,
+    ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS owner TEXT,
+    ADD COLUMN IF NOT EXISTS enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS effect TEXT CHECK (effect IN ('permit', 'forbid'));
+
+-- Indexes for admin filtering
+CREATE INDEX IF NOT EXISTS idx_policies_tags ON policies USING GIN (tags);
+CREATE INDEX IF NOT EXISTS idx_policies_owner ON policies(owner);
+
+-- Comments
+COMMENT ON COLUMN policies.name IS 'Short human readable name';
+COMMENT ON COLUMN policies.description IS 'What the policy is for';
+COMMENT ON COLUMN policies.tags IS 'Free-form labels used to group policies';
+COMMENT ON COLUMN policies.owner IS 'Team or user responsible for the policy';
+COMMENT ON COLUMN policies.enabled IS 'Disabled policies are kept but not loaded for evaluation';
+COMMENT ON COLUMN policies.effect IS 'permit or forbid, derived from the content on write (filled in by PostgresPolicyStore::migrate for existing rows)';
@@ -0,0 +1,8 @@
+-- Tenant-scoped policies for Hodei authorization framework
+ALTER TABLE policies ADD COLUMN IF NOT EXISTS tenant_id TEXT;
+
+-- Index for per-tenant loads
+CREATE INDEX IF NOT EXISTS idx_policies_tenant_id ON policies(tenant_id);
+
+-- Comments
+COMMENT ON COLUMN policies.tenant_id IS 'Tenant (Hrn::tenant_id) the policy applies to; NULL for global policies';
@@ -0,0 +1,26 @@
+-- Authorization decision audit log for Hodei authorization framework
+CREATE TABLE IF NOT EXISTS authz_decisions (
+    id BIGSERIAL PRIMARY KEY,
+    decided_at TIMESTAMPTZ NOT NULL,
+    principal TEXT NOT NULL,
+    action TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    allowed BOOLEAN NOT NULL,
+    determining_policies TEXT[] NOT NULL DEFAULT '{}',
+    errors JSONB NOT NULL DEFAULT '[]',
+    latency_us BIGINT NOT NULL,
+    context JSONB NOT NULL DEFAULT '{}'
+);
+
+-- Indexes for the usual audit queries
+CREATE INDEX IF NOT EXISTS idx_authz_decisions_decided_at ON authz_decisions(decided_at);
+CREATE INDEX IF NOT EXISTS idx_authz_decisions_principal ON authz_decisions(principal, decided_at);
+CREATE INDEX IF NOT EXISTS idx_authz_decisions_resource ON authz_decisions(resource, decided_at);
+
+-- Comments
+COMMENT ON TABLE authz_decisions IS 'Audit log of authorization decisions written by PostgresAuditSink';
+COMMENT ON COLUMN authz_decisions.principal IS 'HRN of the principal';
+COMMENT ON COLUMN authz_decisions.resource IS 'HRN of the resource';
+COMMENT ON COLUMN authz_decisions.determining_policies IS 'Policies that determined the decision';
+COMMENT ON COLUMN authz_decisions.errors IS 'Policies that failed to evaluate, as [{policy_id, message}]';
+COMMENT ON COLUMN authz_decisions.context IS 'Request context, attribute name to Cedar expression';
@@ -0,0 +1,25 @@
+-- Cedar entities (principals, groups, resources) for Hodei authorization framework
+CREATE TABLE IF NOT EXISTS entities (
+    hrn TEXT PRIMARY KEY,
+    entity_type TEXT NOT NULL,
+    attributes JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS entity_parents (
+    child_hrn TEXT NOT NULL REFERENCES entities(hrn) ON DELETE CASCADE,
+    parent_hrn TEXT NOT NULL,
+    parent_type TEXT NOT NULL,
+    PRIMARY KEY (child_hrn, parent_hrn)
+);
+
+-- Indexes for listing by type and walking the hierarchy downwards
+CREATE INDEX IF NOT EXISTS idx_entities_type ON entities(entity_type);
+CREATE INDEX IF NOT EXISTS idx_entity_parents_parent ON entity_parents(parent_hrn);
+
+-- Comments
+COMMENT ON TABLE entities IS 'Cedar entities keyed by HRN, written by PostgresEntityStore';
+COMMENT ON COLUMN entities.entity_type IS 'Cedar entity type, e.g. MyApp::User';
+COMMENT ON COLUMN entities.attributes IS 'Entity attributes in Cedar JSON format';
+COMMENT ON TABLE entity_parents IS 'Direct parent edges (e.g. group memberships); the parent need not be stored';
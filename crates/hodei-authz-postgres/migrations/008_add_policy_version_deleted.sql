@@ -0,0 +1,5 @@
+-- Deletions are recorded in the policy history as tombstone versions
+ALTER TABLE policy_versions ADD COLUMN IF NOT EXISTS deleted BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Comments
+COMMENT ON COLUMN policy_versions.deleted IS 'The policy was deleted in this version; content is its last content';
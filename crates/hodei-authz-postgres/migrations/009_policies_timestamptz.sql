@@ -0,0 +1,5 @@
+-- Policy timestamps with time zone, like policy_versions
+-- Existing values were written with NOW() in the server's time zone
+ALTER TABLE policies
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING created_at AT TIME ZONE current_setting('TimeZone'),
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING updated_at AT TIME ZONE current_setting('TimeZone');
@@ -0,0 +1,69 @@
+//! PostgreSQL audit sink
+
+use async_trait::async_trait;
+use hodei_authz::{AuditError, AuditEvent, AuditSink};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+/// Rows per INSERT, well below PostgreSQL's limit of 65535 bind parameters
+const ROWS_PER_INSERT: usize = 1000;
+
+/// Audit sink writing to the `authz_decisions` table
+///
+/// The table is created by `PostgresPolicyStore::migrate`. Each batch is
+/// written with multi-row INSERTs.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let auth = HodeiAuthService::builder()
+///     .with_postgres(pool.clone())
+///     .with_audit_sink(PostgresAuditSink::new(pool))
+///     .auto_discover_schema()?
+///     .build()
+///     .await?;
+/// ```
+pub struct PostgresAuditSink {
+    pool: PgPool,
+}
+
+impl PostgresAuditSink {
+    /// Create a sink using `pool`
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditSink for PostgresAuditSink {
+    async fn record(&self, events: &[AuditEvent]) -> Result<(), AuditError> {
+        for chunk in events.chunks(ROWS_PER_INSERT) {
+            let mut query = QueryBuilder::<Postgres>::new(
+                "INSERT INTO authz_decisions \
+                 (decided_at, principal, action, resource, allowed, determining_policies, errors, latency_us, context) ",
+            );
+            query.push_values(chunk, |mut row, event| {
+                let json = event.to_json();
+                row.push_bind(DateTime::<Utc>::from(event.timestamp))
+                    .push_bind(event.principal.to_string())
+                    .push_bind(event.action.clone())
+                    .push_bind(event.resource.to_string())
+                    .push_bind(event.is_allowed())
+                    .push_bind(event.determining_policies.clone())
+                    .push_bind(json["errors"].to_string())
+                    .push_unseparated("::JSONB")
+                    .push_bind(event.latency.as_micros() as i64)
+                    .push_bind(json["context"].to_string())
+                    .push_unseparated("::JSONB");
+            });
+            
+            query
+                .build()
+                .execute(&self.pool)
+                .await
+                .map_err(|e| AuditError::Database(e.to_string()))?;
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+//! PostgreSQL entity store
+
+use async_trait::async_trait;
+use cedar_policy::{Entity, EntityUid};
+use hodei_authz::{EntityProvider, EntityProviderError, LinkedEntity};
+use hodei_hrn::Hrn;
+use serde_json::{json, Value};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+
+/// Entities with their direct parents, to be completed with a `WHERE`
+const SELECT_ENTITIES: &str = "\
+    SELECT e.hrn, e.entity_type, e.attributes::TEXT AS attributes, \
+        COALESCE(ARRAY_AGG(p.parent_type ORDER BY p.parent_hrn) \
+            FILTER (WHERE p.parent_hrn IS NOT NULL), '{}') AS parent_types, \
+        COALESCE(ARRAY_AGG(p.parent_hrn ORDER BY p.parent_hrn) \
+            FILTER (WHERE p.parent_hrn IS NOT NULL), '{}') AS parent_hrns \
+    FROM entities e LEFT JOIN entity_parents p ON p.child_hrn = e.hrn";
+
+/// Entities `$1` with every ancestor as a parent
+///
+/// Cedar only needs an entity's ancestors to answer `in`, so listing them all
+/// spares loading the groups in between. Cycles are tolerated.
+const SELECT_WITH_ANCESTORS: &str = "\
+    WITH RECURSIVE ancestors(child_hrn, hrn, entity_type) AS ( \
+        SELECT child_hrn, parent_hrn, parent_type FROM entity_parents WHERE child_hrn = ANY($1) \
+        UNION \
+        SELECT a.child_hrn, p.parent_hrn, p.parent_type \
+        FROM entity_parents p JOIN ancestors a ON p.child_hrn = a.hrn \
+    ) \
+    SELECT e.hrn, e.entity_type, e.attributes::TEXT AS attributes, \
+        COALESCE(ARRAY_AGG(a.entity_type ORDER BY a.hrn) \
+            FILTER (WHERE a.hrn IS NOT NULL AND a.hrn <> e.hrn), '{}') AS parent_types, \
+        COALESCE(ARRAY_AGG(a.hrn ORDER BY a.hrn) \
+            FILTER (WHERE a.hrn IS NOT NULL AND a.hrn <> e.hrn), '{}') AS parent_hrns \
+    FROM entities e LEFT JOIN ancestors a ON a.child_hrn = e.hrn \
+    WHERE e.hrn = ANY($1) GROUP BY e.hrn";
+
+/// Cedar entities stored in the `entities` and `entity_parents` tables
+///
+/// Entities are keyed by HRN (their Cedar id). Attributes are kept in Cedar
+/// JSON format and parents (e.g. group memberships) as edges, so a parent
+/// does not have to be stored itself.
+///
+/// It implements [`EntityProvider`]: with
+/// `HodeiAuthServiceBuilder::with_entity_provider` requests include the
+/// entities the principal and resource reach, and every entity loaded carries
+/// all its stored ancestors as parents, so `principal in Group::"x"` holds
+/// through any number of nested groups regardless of the entity depth.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let entities = PostgresEntityStore::new(pool.clone());
+/// entities.migrate().await?;
+///
+/// entities.upsert_entity(&Entity::with_uid(admins.to_euid()?)).await?;
+/// entities.add_parent(&alice.id, &admins).await?;
+///
+/// let auth = HodeiAuthService::builder()
+///     .with_postgres(pool)
+///     .with_entity_provider(entities)
+///     .auto_discover_schema()?
+///     .build()
+///     .await?;
+/// ```
+pub struct PostgresEntityStore {
+    pool: PgPool,
+}
+
+impl PostgresEntityStore {
+    /// Create a new PostgreSQL entity store
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Run database migrations (shared with `PostgresPolicyStore`)
+    pub async fn migrate(&self) -> Result<(), sqlx::migrate::MigrateError> {
+        sqlx::migrate!("./migrations").run(&self.pool).await
+    }
+
+    /// Insert or replace an entity and its parents
+    pub async fn upsert_entity(&self, entity: &Entity) -> Result<(), EntityProviderError> {
+        self.upsert_entities(std::slice::from_ref(entity)).await
+    }
+
+    /// Insert or replace several entities in one transaction
+    ///
+    /// The parents of each entity are replaced by the ones it carries. If an
+    /// HRN is repeated, the last entity wins.
+    pub async fn upsert_entities(&self, entities: &[Entity]) -> Result<(), EntityProviderError> {
+        // A single INSERT cannot update the same row twice
+        let hrn_of = |entity: &Entity| entity.uid().id().unescaped().to_string();
+        let last: HashMap<String, usize> = entities
+            .iter()
+            .enumerate()
+            .map(|(i, entity)| (hrn_of(entity), i))
+            .collect();
+
+        let mut hrns = Vec::with_capacity(entities.len());
+        let mut types = Vec::with_capacity(entities.len());
+        let mut attributes = Vec::with_capacity(entities.len());
+        let mut children = Vec::new();
+        let mut parent_hrns = Vec::new();
+        let mut parent_types = Vec::new();
+
+        for (i, entity) in entities.iter().enumerate() {
+            let hrn = hrn_of(entity);
+            if last[&hrn] != i {
+                continue;
+            }
+            let uid = entity.uid();
+            let json = entity
+                .to_json_value()
+                .map_err(|e| EntityProviderError::Invalid(format!("{}: {}", uid, e)))?;
+
+            for parent in json["parents"].as_array().into_iter().flatten() {
+                let (Some(parent_type), Some(parent_hrn)) =
+                    (parent["type"].as_str(), parent["id"].as_str())
+                else {
+                    return Err(EntityProviderError::Invalid(format!("{}: invalid parent", uid)));
+                };
+                children.push(hrn.clone());
+                parent_types.push(parent_type.to_string());
+                parent_hrns.push(parent_hrn.to_string());
+            }
+            hrns.push(hrn);
+            types.push(uid.type_name().to_string());
+            attributes.push(json["attrs"].to_string());
+        }
+
+        let mut tx = self.pool.begin().await.map_err(backend_error)?;
+
+        sqlx::query(
+            "INSERT INTO entities (hrn, entity_type, attributes) \
+             SELECT hrn, entity_type, attributes::JSONB \
+             FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[]) AS t(hrn, entity_type, attributes) \
+             ON CONFLICT (hrn) DO UPDATE \
+             SET entity_type = EXCLUDED.entity_type, attributes = EXCLUDED.attributes, \
+                 updated_at = NOW()"
+        )
+        .bind(&hrns)
+        .bind(&types)
+        .bind(&attributes)
+        .execute(&mut *tx)
+        .await
+        .map_err(backend_error)?;
+
+        sqlx::query("DELETE FROM entity_parents WHERE child_hrn = ANY($1)")
+            .bind(&hrns)
+            .execute(&mut *tx)
+            .await
+            .map_err(backend_error)?;
+
+        sqlx::query(
+            "INSERT INTO entity_parents (child_hrn, parent_hrn, parent_type) \
+             SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[]) \
+             ON CONFLICT DO NOTHING"
+        )
+        .bind(&children)
+        .bind(&parent_hrns)
+        .bind(&parent_types)
+        .execute(&mut *tx)
+        .await
+        .map_err(backend_error)?;
+
+        tx.commit().await.map_err(backend_error)
+    }
+
+    /// Get an entity with its direct parents
+    pub async fn get_entity(&self, hrn: &Hrn) -> Result<Option<Entity>, EntityProviderError> {
+        let row = sqlx::query(&format!("{} WHERE e.hrn = $1 GROUP BY e.hrn", SELECT_ENTITIES))
+            .bind(hrn.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(backend_error)?;
+
+        row.as_ref().map(entity_from_row).transpose()
+    }
+
+    /// List the entities of a Cedar type, e.g. `MyApp::Group`
+    pub async fn list_entities(
+        &self,
+        entity_type: &str,
+    ) -> Result<Vec<Entity>, EntityProviderError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE e.entity_type = $1 GROUP BY e.hrn ORDER BY e.hrn",
+            SELECT_ENTITIES
+        ))
+        .bind(entity_type)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(backend_error)?;
+
+        rows.iter().map(entity_from_row).collect()
+    }
+
+    /// Delete an entity and every edge to or from it
+    ///
+    /// Returns false if it did not exist.
+    pub async fn delete_entity(&self, hrn: &Hrn) -> Result<bool, EntityProviderError> {
+        let hrn = hrn.to_string();
+        let mut tx = self.pool.begin().await.map_err(backend_error)?;
+
+        sqlx::query("DELETE FROM entity_parents WHERE parent_hrn = $1")
+            .bind(&hrn)
+            .execute(&mut *tx)
+            .await
+            .map_err(backend_error)?;
+
+        let deleted = sqlx::query("DELETE FROM entities WHERE hrn = $1")
+            .bind(&hrn)
+            .execute(&mut *tx)
+            .await
+            .map_err(backend_error)?;
+
+        tx.commit().await.map_err(backend_error)?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    /// Make `parent` a direct parent of `child`, e.g. add a user to a group
+    pub async fn add_parent(
+        &self,
+        child: &Hrn,
+        parent: &LinkedEntity,
+    ) -> Result<(), EntityProviderError> {
+        let inserted = sqlx::query(
+            "INSERT INTO entity_parents (child_hrn, parent_hrn, parent_type) \
+             SELECT hrn, $2, $3 FROM entities WHERE hrn = $1 \
+             ON CONFLICT (child_hrn, parent_hrn) \
+             DO UPDATE SET parent_type = EXCLUDED.parent_type"
+        )
+        .bind(child.to_string())
+        .bind(parent.hrn.to_string())
+        .bind(&parent.entity_type)
+        .execute(&self.pool)
+        .await
+        .map_err(backend_error)?;
+
+        if inserted.rows_affected() == 0 {
+            return Err(EntityProviderError::Invalid(format!("Entity {} not found", child)));
+        }
+        Ok(())
+    }
+
+    /// Remove a direct parent; returns false if `parent` was not one
+    pub async fn remove_parent(
+        &self,
+        child: &Hrn,
+        parent: &Hrn,
+    ) -> Result<bool, EntityProviderError> {
+        let deleted =
+            sqlx::query("DELETE FROM entity_parents WHERE child_hrn = $1 AND parent_hrn = $2")
+                .bind(child.to_string())
+                .bind(parent.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(backend_error)?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    /// Every ancestor of `hrn` (parents, their parents, ...)
+    ///
+    /// Computed in one recursive query; cycles in the hierarchy are tolerated.
+    pub async fn ancestors(&self, hrn: &Hrn) -> Result<Vec<LinkedEntity>, EntityProviderError> {
+        let rows = sqlx::query(
+            "WITH RECURSIVE ancestors(hrn, entity_type) AS ( \
+                 SELECT parent_hrn, parent_type FROM entity_parents WHERE child_hrn = $1 \
+                 UNION \
+                 SELECT p.parent_hrn, p.parent_type \
+                 FROM entity_parents p JOIN ancestors a ON p.child_hrn = a.hrn \
+             ) \
+             SELECT hrn, entity_type FROM ancestors ORDER BY hrn"
+        )
+        .bind(hrn.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(backend_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let hrn: String = row.try_get("hrn").map_err(backend_error)?;
+                let entity_type: String = row.try_get("entity_type").map_err(backend_error)?;
+                let hrn = hrn
+                    .parse()
+                    .map_err(|_| EntityProviderError::Invalid(format!("Invalid HRN {}", hrn)))?;
+                Ok(LinkedEntity::new(entity_type, hrn))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EntityProvider for PostgresEntityStore {
+    /// Entities with all their ancestors as parents, in one query
+    async fn load_many(&self, uids: &[EntityUid]) -> Result<Vec<Entity>, EntityProviderError> {
+        let hrns: Vec<String> =
+            uids.iter().map(|uid| uid.id().unescaped().to_string()).collect();
+        let rows = sqlx::query(SELECT_WITH_ANCESTORS)
+            .bind(&hrns)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(backend_error)?;
+
+        // The HRN is the key, but a uid also names the type
+        let mut entities = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let entity = entity_from_row(row)?;
+            if uids.contains(&entity.uid()) {
+                entities.push(entity);
+            }
+        }
+        Ok(entities)
+    }
+}
+
+fn entity_from_row(row: &PgRow) -> Result<Entity, EntityProviderError> {
+    let hrn: String = row.try_get("hrn").map_err(backend_error)?;
+    let entity_type: String = row.try_get("entity_type").map_err(backend_error)?;
+    let attributes: String = row.try_get("attributes").map_err(backend_error)?;
+    let parent_types: Vec<String> = row.try_get("parent_types").map_err(backend_error)?;
+    let parent_hrns: Vec<String> = row.try_get("parent_hrns").map_err(backend_error)?;
+
+    let attributes: Value = serde_json::from_str(&attributes)
+        .map_err(|e| EntityProviderError::Invalid(format!("{}: {}", hrn, e)))?;
+    let parents: Vec<Value> = parent_types
+        .iter()
+        .zip(&parent_hrns)
+        .map(|(parent_type, parent_hrn)| json!({ "type": parent_type, "id": parent_hrn }))
+        .collect();
+
+    Entity::from_json_value(
+        json!({
+            "uid": { "type": entity_type, "id": hrn },
+            "attrs": attributes,
+            "parents": parents,
+        }),
+        None,
+    )
+    .map_err(|e| EntityProviderError::Invalid(format!("{}: {}", hrn, e)))
+}
+
+fn backend_error(e: sqlx::Error) -> EntityProviderError {
+    EntityProviderError::Backend(e.to_string())
+}
+
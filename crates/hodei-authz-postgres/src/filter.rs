@@ -0,0 +1,259 @@
+//! SQL filters from residual conditions
+
+use hodei_authz::{CompareOp, ResidualExpr, ResidualFilter, ResidualPolicy, ResidualValue};
+use sqlx::{Postgres, QueryBuilder};
+use std::collections::HashMap;
+
+/// Translates a [`ResidualFilter`] into a parameterised `WHERE` condition
+///
+/// Resource attributes map to columns of the same name unless configured
+/// with [`with_column`](Self::with_column); the resource itself maps to the
+/// id column. Entity references are bound as their id (the HRN), so columns
+/// such as `owner_id` must store HRN strings. A tenant restriction
+/// ([`ResidualExpr::InTenant`]) matches the tenant part of the HRN in the id
+/// column.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let residual = auth.residual_filter(&user, &DocumentCommand::List, "MyApp::Document").await?;
+/// let filter = SqlFilterTranslator::new().translate(&residual);
+///
+/// let mut query = QueryBuilder::new("SELECT * FROM documents WHERE ");
+/// filter.push_to(&mut query);
+/// let mut documents: Vec<Document> = query.build_query_as().fetch_all(&pool).await?;
+///
+/// if !filter.is_exact() {
+///     // Some conditions could not be expressed in SQL: check the rows
+///     documents = post_filter(&auth, &user, documents).await?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqlFilterTranslator {
+    columns: HashMap<String, String>,
+    id_column: String,
+}
+
+impl Default for SqlFilterTranslator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqlFilterTranslator {
+    /// Translator with the id in column `id`
+    pub fn new() -> Self {
+        Self {
+            columns: HashMap::new(),
+            id_column: "id".to_string(),
+        }
+    }
+
+    /// Read attribute `attr` from `column` (written verbatim, e.g. `d.owner`)
+    pub fn with_column(mut self, attr: impl Into<String>, column: impl Into<String>) -> Self {
+        self.columns.insert(attr.into(), column.into());
+        self
+    }
+
+    /// Column holding the resource HRN
+    pub fn with_id_column(mut self, column: impl Into<String>) -> Self {
+        self.id_column = column.into();
+        self
+    }
+
+    /// Build the condition
+    ///
+    /// Expressions that cannot be translated are replaced so the result keeps
+    /// every allowed row (and possibly some denied ones); the filter is then
+    /// marked as not exact.
+    pub fn translate(&self, residual: &ResidualFilter) -> SqlFilter {
+        let mut filter = SqlFilter {
+            parts: Vec::new(),
+            exact: true,
+        };
+
+        match residual {
+            ResidualFilter::AllowAll => filter.sql("TRUE"),
+            ResidualFilter::DenyAll => filter.sql("FALSE"),
+            ResidualFilter::Conditional { permits, forbids } => {
+                filter.sql("(");
+                if permits.is_empty() {
+                    filter.sql("FALSE");
+                }
+                for (i, permit) in permits.iter().enumerate() {
+                    if i > 0 {
+                        filter.sql(" OR ");
+                    }
+                    self.policy(permit, true, &mut filter);
+                }
+                filter.sql(")");
+                for forbid in forbids {
+                    filter.sql(" AND NOT ");
+                    self.policy(forbid, false, &mut filter);
+                }
+            }
+        }
+
+        filter
+    }
+
+    /// Cedar ignores policies that fail to evaluate, e.g. on a missing
+    /// attribute; in SQL that is a NULL, hence the COALESCE
+    fn policy(&self, policy: &ResidualPolicy, positive: bool, filter: &mut SqlFilter) {
+        filter.sql("COALESCE(");
+        self.condition(&policy.condition, positive, filter);
+        filter.sql(", FALSE)");
+    }
+
+    /// Push a boolean expression
+    ///
+    /// `positive` is false under an odd number of negations; untranslatable
+    /// parts become TRUE or FALSE so the row is kept either way.
+    fn condition(&self, expr: &ResidualExpr, positive: bool, filter: &mut SqlFilter) {
+        match expr {
+            ResidualExpr::Value(ResidualValue::Bool(true)) => filter.sql("TRUE"),
+            ResidualExpr::Value(ResidualValue::Bool(false)) => filter.sql("FALSE"),
+            ResidualExpr::Attr(..) if self.operand(expr).is_some() => {
+                self.push_operand(expr, filter);
+            }
+            ResidualExpr::InTenant(tenant_id) => {
+                filter.sql(&format!("({} LIKE ", self.id_column));
+                filter.parts.push(SqlPart::Param(SqlValue::Text(format!(
+                    "hrn:%:%:%:{}:%",
+                    crate::escape_like(tenant_id)
+                ))));
+                filter.sql(" ESCAPE '\\')");
+            }
+            ResidualExpr::Has(target, attr) if matches!(**target, ResidualExpr::Resource) => {
+                match self.column(attr) {
+                    Some(column) => filter.sql(&format!("{} IS NOT NULL", column)),
+                    None => filter.unsupported(positive),
+                }
+            }
+            ResidualExpr::Compare(op, left, right)
+                if self.operand(left).is_some() && self.operand(right).is_some() =>
+            {
+                filter.sql("(");
+                self.push_operand(left, filter);
+                filter.sql(match op {
+                    CompareOp::Eq => " = ",
+                    CompareOp::NotEq => " <> ",
+                    CompareOp::Less => " < ",
+                    CompareOp::LessEq => " <= ",
+                    CompareOp::Greater => " > ",
+                    CompareOp::GreaterEq => " >= ",
+                });
+                self.push_operand(right, filter);
+                filter.sql(")");
+            }
+            ResidualExpr::And(left, right) | ResidualExpr::Or(left, right) => {
+                let op = if matches!(expr, ResidualExpr::And(..)) { " AND " } else { " OR " };
+                filter.sql("(");
+                self.condition(left, positive, filter);
+                filter.sql(op);
+                self.condition(right, positive, filter);
+                filter.sql(")");
+            }
+            ResidualExpr::Not(inner) => {
+                filter.sql("(NOT ");
+                self.condition(inner, !positive, filter);
+                filter.sql(")");
+            }
+            _ => filter.unsupported(positive),
+        }
+    }
+
+    /// Column or bind parameter for a comparison operand
+    fn operand(&self, expr: &ResidualExpr) -> Option<SqlPart> {
+        match expr {
+            ResidualExpr::Resource => Some(SqlPart::Sql(self.id_column.clone())),
+            ResidualExpr::Attr(target, attr) if matches!(**target, ResidualExpr::Resource) => {
+                self.column(attr).map(SqlPart::Sql)
+            }
+            ResidualExpr::Value(value) => Some(SqlPart::Param(match value {
+                ResidualValue::Bool(b) => SqlValue::Bool(*b),
+                ResidualValue::Long(n) => SqlValue::Long(*n),
+                ResidualValue::String(s) => SqlValue::Text(s.clone()),
+                ResidualValue::Entity { id, .. } => SqlValue::Text(id.clone()),
+            })),
+            _ => None,
+        }
+    }
+
+    fn push_operand(&self, expr: &ResidualExpr, filter: &mut SqlFilter) {
+        if let Some(part) = self.operand(expr) {
+            filter.parts.push(part);
+        }
+    }
+
+    /// Configured column, or the attribute name if it is a plain identifier
+    fn column(&self, attr: &str) -> Option<String> {
+        if let Some(column) = self.columns.get(attr) {
+            return Some(column.clone());
+        }
+
+        let mut chars = attr.chars();
+        let is_identifier = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        is_identifier.then(|| attr.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SqlValue {
+    Bool(bool),
+    Long(i64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SqlPart {
+    Sql(String),
+    Param(SqlValue),
+}
+
+/// A `WHERE` condition with its bind parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlFilter {
+    parts: Vec<SqlPart>,
+    exact: bool,
+}
+
+impl SqlFilter {
+    /// Returns false if the condition may keep rows the principal cannot
+    /// access, which must then be checked with `is_authorized`
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// Append the condition to `query`, binding its parameters
+    pub fn push_to(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        for part in &self.parts {
+            match part {
+                SqlPart::Sql(sql) => {
+                    query.push(sql);
+                }
+                SqlPart::Param(SqlValue::Bool(b)) => {
+                    query.push_bind(*b);
+                }
+                SqlPart::Param(SqlValue::Long(n)) => {
+                    query.push_bind(*n);
+                }
+                SqlPart::Param(SqlValue::Text(s)) => {
+                    query.push_bind(s.clone());
+                }
+            }
+        }
+    }
+
+    fn sql(&mut self, sql: &str) {
+        self.parts.push(SqlPart::Sql(sql.to_string()));
+    }
+
+    fn unsupported(&mut self, positive: bool) {
+        self.exact = false;
+        self.sql(if positive { "TRUE" } else { "FALSE" });
+    }
+}
@@ -1,45 +1,418 @@
 //! PostgreSQL adapter for Hodei authorization framework
 //!
-//! This crate provides a PostgreSQL implementation of the `PolicyStore` trait.
+//! This crate provides a PostgreSQL implementation of the `PolicyStore` trait,
+//! an entity store that doubles as an `EntityProvider`, an `AuditSink` for
+//! authorization decisions and a translator from residual conditions to SQL
+//! filters.
+
+mod audit;
+mod entities;
+mod filter;
+
+pub use audit::PostgresAuditSink;
+pub use entities::PostgresEntityStore;
+pub use filter::{SqlFilter, SqlFilterTranslator};
 
 use async_trait::async_trait;
-use cedar_policy::{Policy, PolicySet};
-use hodei_authz::{PolicyStore, PolicyStoreError};
-use sqlx::{PgPool, Row};
+use cedar_policy::{Effect, Policy, PolicyId, PolicySet, Schema, Validator};
+use hodei_authz::templates::{add_templates, add_templates_lenient, check_link, parse_template};
+use hodei_authz::{
+    validate_policy, validate_template, LinkedEntity, PolicyChange, PolicyFilter, PolicyLoadReport,
+    PolicyMetadata, PolicyPage, PolicyRecord, PolicyStore, PolicyStoreError, PolicyVersion,
+    TemplateLink,
+};
+use hodei_hrn::Hrn;
+use sqlx::postgres::PgRow;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
 use uuid::Uuid;
 
 /// PostgreSQL implementation of PolicyStore
+///
+/// Writes are parsed before they reach the database. With
+/// [`with_schema`](Self::with_schema) they are also validated against the
+/// schema, so a broken policy never makes `load_all_policies` fail.
+///
+/// Every write is recorded in the `policy_versions` table. History is kept
+/// when a policy is deleted, and the deletion itself is recorded as a
+/// tombstone version.
+///
+/// Policies carry [`PolicyMetadata`] and an enabled flag; disabled policies
+/// are left out of `load_all_policies`. Policies with a `tenant_id` are only
+/// returned by the tenant-scoped methods.
 pub struct PostgresPolicyStore {
     pool: PgPool,
+    validator: Option<Validator>,
 }
 
 impl PostgresPolicyStore {
     /// Create a new PostgreSQL policy store
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            validator: None,
+        }
+    }
+    
+    /// Validate every write against `schema`
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.validator = Some(Validator::new(schema));
+        self
     }
     
     /// Run database migrations
+    ///
+    /// Also fills in the `effect` column of policies stored before it existed.
     pub async fn migrate(&self) -> Result<(), sqlx::migrate::MigrateError> {
         sqlx::migrate!("./migrations")
             .run(&self.pool)
+            .await?;
+        self.backfill_effects()
             .await
+            .map_err(sqlx::migrate::MigrateError::Execute)
+    }
+    
+    /// Derive `effect` from the content of rows that lack it
+    ///
+    /// Rows whose content no longer parses are left as they are.
+    async fn backfill_effects(&self) -> Result<(), sqlx::Error> {
+        let records = sqlx::query("SELECT id, content FROM policies WHERE effect IS NULL")
+            .fetch_all(&self.pool)
+            .await?;
+        
+        for record in records {
+            let id: String = record.try_get("id")?;
+            let content: String = record.try_get("content")?;
+            if let Ok(policy) = Policy::parse(Some(PolicyId::new(&id)), content) {
+                sqlx::query("UPDATE policies SET effect = $1 WHERE id = $2")
+                    .bind(effect_name(policy.effect()))
+                    .bind(&id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Create a policy, recording who created it and why
+    pub async fn create_policy_with(
+        &self,
+        content: String,
+        change: PolicyChange,
+    ) -> Result<String, PolicyStoreError> {
+        self.create_policy_with_metadata(content, PolicyMetadata::default(), change)
+            .await
+    }
+    
+    /// Create an enabled global policy with its metadata
+    pub async fn create_policy_with_metadata(
+        &self,
+        content: String,
+        metadata: PolicyMetadata,
+        change: PolicyChange,
+    ) -> Result<String, PolicyStoreError> {
+        self.insert_policy(None, content, metadata, change).await
+    }
+    
+    /// Create an enabled policy scoped to `tenant_id` with its metadata
+    pub async fn create_tenant_policy_with_metadata(
+        &self,
+        tenant_id: &str,
+        content: String,
+        metadata: PolicyMetadata,
+        change: PolicyChange,
+    ) -> Result<String, PolicyStoreError> {
+        self.insert_policy(Some(tenant_id), content, metadata, change)
+            .await
+    }
+    
+    /// Delete a policy, recording who deleted it and why
+    ///
+    /// The deletion is added to the history as a tombstone version holding
+    /// the last content.
+    pub async fn delete_policy_with(
+        &self,
+        id: &str,
+        change: PolicyChange,
+    ) -> Result<(), PolicyStoreError> {
+        let mut tx = self.pool.begin().await.map_err(database_error)?;
+        let deleted = sqlx::query("DELETE FROM policies WHERE id = $1 RETURNING content, version")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(database_error)?
+            .ok_or_else(|| PolicyStoreError::NotFound(id.to_string()))?;
+        
+        let content: String = deleted.try_get("content").map_err(database_error)?;
+        let version: i64 = deleted.try_get("version").map_err(database_error)?;
+        record_version(&mut tx, id, version + 1, &content, &change, true).await?;
+        tx.commit().await.map_err(database_error)?;
+        
+        Ok(())
+    }
+    
+    async fn insert_policy(
+        &self,
+        tenant_id: Option<&str>,
+        content: String,
+        metadata: PolicyMetadata,
+        change: PolicyChange,
+    ) -> Result<String, PolicyStoreError> {
+        let policy_id = Uuid::new_v4().to_string();
+        let policy = self.check_policy(&policy_id, &content)?;
+        
+        let mut tx = self.pool.begin().await.map_err(database_error)?;
+        sqlx::query(
+            "INSERT INTO policies \
+             (id, content, created_at, version, effect, name, description, tags, owner, tenant_id) \
+             VALUES ($1, $2, NOW(), 1, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(&policy_id)
+        .bind(&content)
+        .bind(effect_name(policy.effect()))
+        .bind(&metadata.name)
+        .bind(&metadata.description)
+        .bind(&metadata.tags)
+        .bind(&metadata.owner)
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+        record_version(&mut tx, &policy_id, 1, &content, &change, false).await?;
+        tx.commit().await.map_err(database_error)?;
+        
+        Ok(policy_id)
+    }
+    
+    /// Update a policy and record the new version
+    ///
+    /// With `expected_version`, the update only applies if the stored version
+    /// still matches; otherwise `PolicyStoreError::Conflict` is returned.
+    async fn write_policy(
+        &self,
+        id: &str,
+        content: &str,
+        expected_version: Option<i64>,
+        change: &PolicyChange,
+    ) -> Result<i64, PolicyStoreError> {
+        let policy = self.check_policy(id, content)?;
+        
+        let mut tx = self.pool.begin().await.map_err(database_error)?;
+        let updated = sqlx::query(
+            "UPDATE policies SET content = $1, effect = $4, updated_at = NOW(), version = version + 1 \
+             WHERE id = $2 AND ($3::BIGINT IS NULL OR version = $3) RETURNING version",
+        )
+        .bind(content)
+        .bind(id)
+        .bind(expected_version)
+        .bind(effect_name(policy.effect()))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(database_error)?;
+        
+        let version: i64 = match updated {
+            Some(row) => row.try_get("version").map_err(database_error)?,
+            None => {
+                let current = sqlx::query("SELECT version FROM policies WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(database_error)?;
+                
+                return Err(match (current, expected_version) {
+                    (Some(row), Some(expected)) => PolicyStoreError::Conflict {
+                        policy_id: id.to_string(),
+                        expected,
+                        actual: row.try_get("version").map_err(database_error)?,
+                    },
+                    _ => PolicyStoreError::NotFound(id.to_string()),
+                });
+            }
+        };
+        
+        record_version(&mut tx, id, version, content, change, false).await?;
+        tx.commit().await.map_err(database_error)?;
+        
+        Ok(version)
+    }
+    
+    /// Parse the policy and validate it when a schema is configured
+    fn check_policy(&self, id: &str, content: &str) -> Result<Policy, PolicyStoreError> {
+        let policy = Policy::parse(Some(PolicyId::new(id)), content)
+            .map_err(|e| PolicyStoreError::Parse(e.to_string()))?;
+        if let Some(validator) = &self.validator {
+            validate_policy(validator, &policy)?;
+        }
+        Ok(policy)
+    }
+    
+    /// (id, content) of the policies of a tenant, or the global ones for `None`
+    async fn scoped_policies(
+        &self,
+        tenant_id: Option<&str>,
+        only_enabled: bool,
+    ) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        let records = sqlx::query(
+            "SELECT id, content FROM policies \
+             WHERE tenant_id IS NOT DISTINCT FROM $1 AND (enabled OR NOT $2) ORDER BY created_at",
+        )
+        .bind(tenant_id)
+        .bind(only_enabled)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(database_error)?;
+        
+        records
+            .into_iter()
+            .map(|r| {
+                let id: String = r.try_get("id").map_err(database_error)?;
+                let content: String = r.try_get("content").map_err(database_error)?;
+                Ok((id, content))
+            })
+            .collect()
     }
 }
 
+/// Parse stored `(id, content)` pairs, failing on the first broken policy
+fn parse_policies(policies: Vec<(String, String)>) -> Result<PolicySet, PolicyStoreError> {
+    let parsed = policies
+        .into_iter()
+        .map(|(id, content)| {
+            Policy::parse(Some(PolicyId::new(id)), content)
+                .map_err(|e| PolicyStoreError::Parse(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    PolicySet::from_policies(parsed).map_err(|e| PolicyStoreError::Internal(e.to_string()))
+}
+
+fn database_error(e: sqlx::Error) -> PolicyStoreError {
+    PolicyStoreError::Database(e.to_string())
+}
+
+/// Insert a row in `policy_versions` within the write's transaction
+async fn record_version(
+    tx: &mut Transaction<'_, Postgres>,
+    id: &str,
+    version: i64,
+    content: &str,
+    change: &PolicyChange,
+    deleted: bool,
+) -> Result<(), PolicyStoreError> {
+    sqlx::query(
+        "INSERT INTO policy_versions \
+         (policy_id, version, content, author, change_reason, deleted, created_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, NOW())",
+    )
+    .bind(id)
+    .bind(version)
+    .bind(content)
+    .bind(&change.author)
+    .bind(&change.reason)
+    .bind(deleted)
+    .execute(&mut **tx)
+    .await
+    .map_err(database_error)?;
+    
+    Ok(())
+}
+
+/// Escape `LIKE` wildcards so `value` only matches itself
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn effect_name(effect: Effect) -> &'static str {
+    match effect {
+        Effect::Permit => "permit",
+        Effect::Forbid => "forbid",
+    }
+}
+
+const RECORD_COLUMNS: &str = "id, content, tenant_id, name, description, tags, owner, enabled, \
+     effect, version, created_at, updated_at";
+
+fn record_from_row(row: &PgRow) -> Result<PolicyRecord, PolicyStoreError> {
+    let effect: Option<String> = row.try_get("effect").map_err(database_error)?;
+    let created_at: DateTime<Utc> = row.try_get("created_at").map_err(database_error)?;
+    let updated_at: Option<DateTime<Utc>> = row.try_get("updated_at").map_err(database_error)?;
+    
+    Ok(PolicyRecord {
+        id: row.try_get("id").map_err(database_error)?,
+        content: row.try_get("content").map_err(database_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(database_error)?,
+        metadata: PolicyMetadata {
+            name: row.try_get("name").map_err(database_error)?,
+            description: row.try_get("description").map_err(database_error)?,
+            tags: row.try_get("tags").map_err(database_error)?,
+            owner: row.try_get("owner").map_err(database_error)?,
+        },
+        enabled: row.try_get("enabled").map_err(database_error)?,
+        effect: match effect.as_deref() {
+            Some("permit") => Some(Effect::Permit),
+            Some("forbid") => Some(Effect::Forbid),
+            _ => None,
+        },
+        version: row.try_get("version").map_err(database_error)?,
+        created_at: created_at.into(),
+        updated_at: updated_at.map(Into::into),
+    })
+}
+
+fn version_from_row(row: &PgRow) -> Result<PolicyVersion, PolicyStoreError> {
+    let created_at: DateTime<Utc> = row.try_get("created_at").map_err(database_error)?;
+    Ok(PolicyVersion {
+        policy_id: row.try_get("policy_id").map_err(database_error)?,
+        version: row.try_get("version").map_err(database_error)?,
+        content: row.try_get("content").map_err(database_error)?,
+        author: row.try_get("author").map_err(database_error)?,
+        reason: row.try_get("change_reason").map_err(database_error)?,
+        created_at: created_at.into(),
+        deleted: row.try_get("deleted").map_err(database_error)?,
+    })
+}
+
+/// Read an optional slot binding stored as (entity type, HRN) columns
+fn linked_entity(
+    row: &PgRow,
+    type_column: &str,
+    hrn_column: &str,
+) -> Result<Option<LinkedEntity>, PolicyStoreError> {
+    let entity_type: Option<String> = row.try_get(type_column).map_err(database_error)?;
+    let hrn: Option<String> = row.try_get(hrn_column).map_err(database_error)?;
+    
+    match (entity_type, hrn) {
+        (Some(entity_type), Some(hrn)) => {
+            let hrn: Hrn = hrn
+                .parse()
+                .map_err(|e| PolicyStoreError::Internal(format!("Invalid HRN {}: {}", hrn, e)))?;
+            Ok(Some(LinkedEntity::new(entity_type, hrn)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn link_from_row(row: &PgRow) -> Result<TemplateLink, PolicyStoreError> {
+    Ok(TemplateLink {
+        link_id: row.try_get("id").map_err(database_error)?,
+        template_id: row.try_get("template_id").map_err(database_error)?,
+        principal: linked_entity(row, "principal_type", "principal_hrn")?,
+        resource: linked_entity(row, "resource_type", "resource_hrn")?,
+    })
+}
+
 #[async_trait]
 impl PolicyStore for PostgresPolicyStore {
     async fn create_policy(&self, content: String) -> Result<String, PolicyStoreError> {
-        let policy_id = Uuid::new_v4().to_string();
-        
-        sqlx::query("INSERT INTO policies (id, content, created_at) VALUES ($1, $2, NOW())")
-            .bind(&policy_id)
-            .bind(&content)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
-        
-        Ok(policy_id)
+        self.create_policy_with(content, PolicyChange::default()).await
     }
     
     async fn get_policy(&self, id: &str) -> Result<Option<String>, PolicyStoreError> {
@@ -49,34 +422,154 @@ impl PolicyStore for PostgresPolicyStore {
             .await
             .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
         
-        Ok(record.map(|r| r.try_get("content").unwrap()))
+        record
+            .map(|r| r.try_get("content"))
+            .transpose()
+            .map_err(|e| PolicyStoreError::Internal(e.to_string()))
     }
     
+    /// Global policies only, enabled or not
     async fn list_policies(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
-        let records = sqlx::query("SELECT id, content FROM policies ORDER BY created_at")
+        self.scoped_policies(None, false).await
+    }
+    
+    async fn update_policy(&self, id: &str, content: String) -> Result<(), PolicyStoreError> {
+        self.write_policy(id, &content, None, &PolicyChange::default())
+            .await
+            .map(|_| ())
+    }
+    
+    async fn delete_policy(&self, id: &str) -> Result<(), PolicyStoreError> {
+        self.delete_policy_with(id, PolicyChange::default()).await
+    }
+    
+    /// Disabled policies are skipped
+    async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
+        let mut policy_set = parse_policies(self.scoped_policies(None, true).await?)?;
+        add_templates(
+            &mut policy_set,
+            self.list_templates().await?,
+            self.list_template_links(None).await?,
+        )?;
+        
+        Ok(policy_set)
+    }
+    
+    /// Disabled policies are skipped, not reported as rejected
+    async fn load_policies_lenient(&self) -> Result<PolicyLoadReport, PolicyStoreError> {
+        let mut report = PolicyLoadReport::from_policies(self.scoped_policies(None, true).await?);
+        add_templates_lenient(
+            &mut report,
+            self.list_templates().await?,
+            self.list_template_links(None).await?,
+        );
+        Ok(report)
+    }
+    
+    async fn policy_history(&self, id: &str) -> Result<Vec<PolicyVersion>, PolicyStoreError> {
+        let records = sqlx::query(
+            "SELECT policy_id, version, content, author, change_reason, deleted, created_at \
+             FROM policy_versions WHERE policy_id = $1 ORDER BY version",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(database_error)?;
+        
+        if records.is_empty() {
+            return Err(PolicyStoreError::NotFound(id.to_string()));
+        }
+        
+        records.iter().map(version_from_row).collect()
+    }
+    
+    async fn get_policy_version(
+        &self,
+        id: &str,
+        version: i64,
+    ) -> Result<Option<PolicyVersion>, PolicyStoreError> {
+        let record = sqlx::query(
+            "SELECT policy_id, version, content, author, change_reason, deleted, created_at \
+             FROM policy_versions WHERE policy_id = $1 AND version = $2",
+        )
+        .bind(id)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(database_error)?;
+        
+        record.as_ref().map(version_from_row).transpose()
+    }
+    
+    async fn update_policy_versioned(
+        &self,
+        id: &str,
+        content: String,
+        expected_version: i64,
+        change: PolicyChange,
+    ) -> Result<i64, PolicyStoreError> {
+        self.write_policy(id, &content, Some(expected_version), &change)
+            .await
+    }
+    
+    /// The restored content is validated against the current schema
+    async fn rollback_policy(
+        &self,
+        id: &str,
+        version: i64,
+        mut change: PolicyChange,
+    ) -> Result<i64, PolicyStoreError> {
+        let target = self
+            .get_policy_version(id, version)
+            .await?
+            .ok_or_else(|| PolicyStoreError::NotFound(format!("{} (version {})", id, version)))?;
+        
+        change
+            .reason
+            .get_or_insert_with(|| format!("Rollback to version {}", version));
+        self.write_policy(id, &target.content, None, &change).await
+    }
+    
+    async fn create_template(&self, content: String) -> Result<String, PolicyStoreError> {
+        let template_id = Uuid::new_v4().to_string();
+        let template = parse_template(&template_id, &content)?;
+        if let Some(validator) = &self.validator {
+            validate_template(validator, &template)?;
+        }
+        
+        sqlx::query("INSERT INTO policy_templates (id, content, created_at) VALUES ($1, $2, NOW())")
+            .bind(&template_id)
+            .bind(&content)
+            .execute(&self.pool)
+            .await
+            .map_err(database_error)?;
+        
+        Ok(template_id)
+    }
+    
+    async fn list_templates(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        let records = sqlx::query("SELECT id, content FROM policy_templates ORDER BY created_at")
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
+            .map_err(database_error)?;
         
-        let policies = records
+        records
             .into_iter()
             .map(|r| {
-                let id: String = r.try_get("id").unwrap();
-                let content: String = r.try_get("content").unwrap();
-                (id, content)
+                let id: String = r.try_get("id").map_err(database_error)?;
+                let content: String = r.try_get("content").map_err(database_error)?;
+                Ok((id, content))
             })
-            .collect();
-        
-        Ok(policies)
+            .collect()
     }
     
-    async fn update_policy(&self, id: &str, content: String) -> Result<(), PolicyStoreError> {
-        let result = sqlx::query("UPDATE policies SET content = $1, updated_at = NOW() WHERE id = $2")
-            .bind(&content)
+    /// Links are removed by the `ON DELETE CASCADE` foreign key
+    async fn delete_template(&self, id: &str) -> Result<(), PolicyStoreError> {
+        let result = sqlx::query("DELETE FROM policy_templates WHERE id = $1")
             .bind(id)
             .execute(&self.pool)
             .await
-            .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
+            .map_err(database_error)?;
         
         if result.rows_affected() == 0 {
             return Err(PolicyStoreError::NotFound(id.to_string()));
@@ -85,12 +578,150 @@ impl PolicyStore for PostgresPolicyStore {
         Ok(())
     }
     
-    async fn delete_policy(&self, id: &str) -> Result<(), PolicyStoreError> {
-        let result = sqlx::query("DELETE FROM policies WHERE id = $1")
-            .bind(id)
+    async fn link_template(
+        &self,
+        template_id: &str,
+        principal: Option<LinkedEntity>,
+        resource: Option<LinkedEntity>,
+    ) -> Result<String, PolicyStoreError> {
+        let record = sqlx::query("SELECT content FROM policy_templates WHERE id = $1")
+            .bind(template_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(database_error)?
+            .ok_or_else(|| PolicyStoreError::NotFound(template_id.to_string()))?;
+        let content: String = record.try_get("content").map_err(database_error)?;
+        
+        let link = TemplateLink {
+            link_id: Uuid::new_v4().to_string(),
+            template_id: template_id.to_string(),
+            principal,
+            resource,
+        };
+        check_link(&parse_template(template_id, &content)?, &link)?;
+        
+        sqlx::query(
+            "INSERT INTO policy_template_links \
+             (id, template_id, principal_type, principal_hrn, resource_type, resource_hrn, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, NOW())",
+        )
+        .bind(&link.link_id)
+        .bind(&link.template_id)
+        .bind(link.principal.as_ref().map(|p| p.entity_type.clone()))
+        .bind(link.principal.as_ref().map(|p| p.hrn.to_string()))
+        .bind(link.resource.as_ref().map(|r| r.entity_type.clone()))
+        .bind(link.resource.as_ref().map(|r| r.hrn.to_string()))
+        .execute(&self.pool)
+        .await
+        .map_err(database_error)?;
+        
+        Ok(link.link_id)
+    }
+    
+    async fn list_template_links(
+        &self,
+        template_id: Option<&str>,
+    ) -> Result<Vec<TemplateLink>, PolicyStoreError> {
+        let records = sqlx::query(
+            "SELECT id, template_id, principal_type, principal_hrn, resource_type, resource_hrn \
+             FROM policy_template_links \
+             WHERE ($1::TEXT IS NULL OR template_id = $1) ORDER BY created_at",
+        )
+        .bind(template_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(database_error)?;
+        
+        records.iter().map(link_from_row).collect()
+    }
+    
+    async fn unlink_template(&self, link_id: &str) -> Result<(), PolicyStoreError> {
+        let result = sqlx::query("DELETE FROM policy_template_links WHERE id = $1")
+            .bind(link_id)
             .execute(&self.pool)
             .await
-            .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
+            .map_err(database_error)?;
+        
+        if result.rows_affected() == 0 {
+            return Err(PolicyStoreError::NotFound(link_id.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    async fn list_policy_records(
+        &self,
+        filter: &PolicyFilter,
+    ) -> Result<PolicyPage, PolicyStoreError> {
+        const WHERE: &str = "WHERE ($1::TEXT IS NULL OR name ILIKE '%' || $1 || '%' ESCAPE '\\') \
+             AND (cardinality($2::TEXT[]) = 0 OR tags @> $2) \
+             AND ($3::TEXT IS NULL OR owner = $3) \
+             AND ($4::BOOLEAN IS NULL OR enabled = $4) \
+             AND ($5::TEXT IS NULL OR effect = $5) \
+             AND ($6::TEXT IS NULL OR tenant_id = $6)";
+        let effect = filter.effect.map(effect_name);
+        let name_contains = filter.name_contains.as_deref().map(escape_like);
+        
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM policies {}", WHERE))
+            .bind(&name_contains)
+            .bind(&filter.tags)
+            .bind(&filter.owner)
+            .bind(filter.enabled)
+            .bind(effect)
+            .bind(&filter.tenant_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(database_error)?;
+        
+        let records = sqlx::query(&format!(
+            "SELECT {} FROM policies {} ORDER BY created_at, id OFFSET $7 LIMIT $8",
+            RECORD_COLUMNS, WHERE
+        ))
+        .bind(&name_contains)
+        .bind(&filter.tags)
+        .bind(&filter.owner)
+        .bind(filter.enabled)
+        .bind(effect)
+        .bind(&filter.tenant_id)
+        .bind(filter.offset as i64)
+        .bind(filter.limit.map(|limit| limit as i64))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(database_error)?;
+        
+        Ok(PolicyPage {
+            records: records.iter().map(record_from_row).collect::<Result<_, _>>()?,
+            total: total as usize,
+        })
+    }
+    
+    async fn get_policy_record(&self, id: &str) -> Result<Option<PolicyRecord>, PolicyStoreError> {
+        let record = sqlx::query(&format!("SELECT {} FROM policies WHERE id = $1", RECORD_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(database_error)?;
+        
+        record.as_ref().map(record_from_row).transpose()
+    }
+    
+    async fn update_policy_metadata(
+        &self,
+        id: &str,
+        metadata: PolicyMetadata,
+    ) -> Result<(), PolicyStoreError> {
+        let result = sqlx::query(
+            "UPDATE policies SET name = $1, description = $2, tags = $3, owner = $4, updated_at = NOW() \
+             WHERE id = $5",
+        )
+        .bind(&metadata.name)
+        .bind(&metadata.description)
+        .bind(&metadata.tags)
+        .bind(&metadata.owner)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(database_error)?;
         
         if result.rows_affected() == 0 {
             return Err(PolicyStoreError::NotFound(id.to_string()));
@@ -99,26 +730,54 @@ impl PolicyStore for PostgresPolicyStore {
         Ok(())
     }
     
-    async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
-        let records = sqlx::query("SELECT id, content FROM policies")
-            .fetch_all(&self.pool)
+    async fn set_policy_enabled(&self, id: &str, enabled: bool) -> Result<(), PolicyStoreError> {
+        let result = sqlx::query("UPDATE policies SET enabled = $1, updated_at = NOW() WHERE id = $2")
+            .bind(enabled)
+            .bind(id)
+            .execute(&self.pool)
             .await
-            .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
+            .map_err(database_error)?;
         
-        let mut policies = Vec::new();
-        for record in records {
-            let db_id: String = record.try_get("id")
-                .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
-            let content: String = record.try_get("content")
-                .map_err(|e| PolicyStoreError::Database(e.to_string()))?;
-            
-            let policy_id = cedar_policy::PolicyId::new(db_id.clone());
-            let policy = Policy::parse(Some(policy_id), content)
-                .map_err(|e| PolicyStoreError::Parse(e.to_string()))?;
-            policies.push(policy);
+        if result.rows_affected() == 0 {
+            return Err(PolicyStoreError::NotFound(id.to_string()));
         }
         
-        PolicySet::from_policies(policies)
-            .map_err(|e| PolicyStoreError::Internal(e.to_string()))
+        Ok(())
+    }
+    
+    async fn create_tenant_policy(
+        &self,
+        tenant_id: &str,
+        content: String,
+    ) -> Result<String, PolicyStoreError> {
+        self.insert_policy(
+            Some(tenant_id),
+            content,
+            PolicyMetadata::default(),
+            PolicyChange::default(),
+        )
+        .await
+    }
+    
+    async fn list_tenant_policies(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        self.scoped_policies(Some(tenant_id), false).await
+    }
+    
+    /// Disabled policies are skipped
+    async fn load_tenant_policies(&self, tenant_id: &str) -> Result<PolicySet, PolicyStoreError> {
+        parse_policies(self.scoped_policies(Some(tenant_id), true).await?)
+    }
+    
+    /// Disabled policies are skipped, not reported as rejected
+    async fn load_tenant_policies_lenient(
+        &self,
+        tenant_id: &str,
+    ) -> Result<PolicyLoadReport, PolicyStoreError> {
+        Ok(PolicyLoadReport::from_policies(
+            self.scoped_policies(Some(tenant_id), true).await?,
+        ))
     }
 }
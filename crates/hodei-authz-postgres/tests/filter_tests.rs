@@ -0,0 +1,138 @@
+//! Tests for the residual to SQL translator
+//!
+//! These only build SQL text and do not need a database.
+
+use cedar_policy::Effect;
+use hodei_authz::{CompareOp, ResidualExpr, ResidualFilter, ResidualPolicy, ResidualValue};
+use hodei_authz_postgres::{SqlFilter, SqlFilterTranslator};
+use sqlx::{Postgres, QueryBuilder};
+
+fn attr(name: &str) -> Box<ResidualExpr> {
+    Box::new(ResidualExpr::Attr(Box::new(ResidualExpr::Resource), name.to_string()))
+}
+
+fn value(value: ResidualValue) -> Box<ResidualExpr> {
+    Box::new(ResidualExpr::Value(value))
+}
+
+fn policy(effect: Effect, condition: ResidualExpr) -> ResidualPolicy {
+    ResidualPolicy {
+        id: "policy".to_string(),
+        effect,
+        condition,
+    }
+}
+
+fn sql(filter: &SqlFilter) -> String {
+    let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM documents WHERE ");
+    filter.push_to(&mut query);
+    query.sql().to_string()
+}
+
+#[test]
+fn test_owner_or_public() {
+    let owner = ResidualExpr::Compare(
+        CompareOp::Eq,
+        attr("owner_id"),
+        value(ResidualValue::Entity {
+            type_name: "MyApp::User".to_string(),
+            id: "hrn:hodei:docapp:global:tenant-1:user/alice".to_string(),
+        }),
+    );
+    let public = ResidualExpr::Compare(
+        CompareOp::Eq,
+        attr("is_public"),
+        value(ResidualValue::Bool(true)),
+    );
+    let residual = ResidualFilter::Conditional {
+        permits: vec![policy(Effect::Permit, owner), policy(Effect::Permit, public)],
+        forbids: Vec::new(),
+    };
+    
+    let filter = SqlFilterTranslator::new().translate(&residual);
+    
+    assert!(filter.is_exact());
+    assert_eq!(
+        sql(&filter),
+        "SELECT * FROM documents WHERE (COALESCE((owner_id = $1), FALSE) OR COALESCE((is_public = $2), FALSE))"
+    );
+}
+
+#[test]
+fn test_unsupported_expressions_keep_rows() {
+    let unsupported = ResidualExpr::Unsupported("{}".to_string());
+    let residual = ResidualFilter::Conditional {
+        permits: vec![policy(
+            Effect::Permit,
+            ResidualExpr::And(attr("is_public"), Box::new(unsupported.clone())),
+        )],
+        forbids: vec![policy(Effect::Forbid, unsupported)],
+    };
+    
+    let filter = SqlFilterTranslator::new()
+        .with_column("is_public", "d.public")
+        .translate(&residual);
+    
+    assert!(!filter.is_exact());
+    assert_eq!(
+        sql(&filter),
+        "SELECT * FROM documents WHERE (COALESCE((d.public AND TRUE), FALSE)) AND NOT COALESCE(FALSE, FALSE)"
+    );
+}
+
+#[test]
+fn test_attribute_names_must_be_identifiers() {
+    let residual = ResidualFilter::Conditional {
+        permits: vec![policy(
+            Effect::Permit,
+            ResidualExpr::Compare(
+                CompareOp::Eq,
+                attr("x; DROP TABLE documents"),
+                value(ResidualValue::Long(1)),
+            ),
+        )],
+        forbids: Vec::new(),
+    };
+    
+    let filter = SqlFilterTranslator::new().translate(&residual);
+    
+    assert!(!filter.is_exact());
+    assert_eq!(sql(&filter), "SELECT * FROM documents WHERE (COALESCE(TRUE, FALSE))");
+}
+
+#[test]
+fn test_decided_filters() {
+    let translator = SqlFilterTranslator::new();
+    assert_eq!(
+        sql(&translator.translate(&ResidualFilter::AllowAll)),
+        "SELECT * FROM documents WHERE TRUE"
+    );
+    assert_eq!(
+        sql(&translator.translate(&ResidualFilter::DenyAll)),
+        "SELECT * FROM documents WHERE FALSE"
+    );
+}
+
+#[test]
+fn test_tenant_restriction() {
+    let public = ResidualExpr::Compare(
+        CompareOp::Eq,
+        attr("is_public"),
+        value(ResidualValue::Bool(true)),
+    );
+    let residual = ResidualFilter::Conditional {
+        permits: vec![policy(
+            Effect::Permit,
+            ResidualExpr::And(Box::new(ResidualExpr::InTenant("tenant_1".to_string())), Box::new(public)),
+        )],
+        forbids: Vec::new(),
+    };
+    
+    let filter = SqlFilterTranslator::new().with_id_column("hrn").translate(&residual);
+    
+    assert!(filter.is_exact());
+    assert_eq!(
+        sql(&filter),
+        "SELECT * FROM documents WHERE (COALESCE(((hrn LIKE $1 ESCAPE '\\') AND (is_public = $2)), FALSE))"
+    );
+}
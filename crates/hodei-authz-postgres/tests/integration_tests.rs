@@ -3,9 +3,16 @@
 //! Note: These tests require a running PostgreSQL instance
 //! Run with: docker-compose up -d postgres
 
-use hodei_authz::{PolicyStore, PolicyStoreError};
-use hodei_authz_postgres::PostgresPolicyStore;
+use cedar_policy::{Decision, PolicyId};
+use hodei_authz::{
+    AuditEvent, AuditSink, EntityProvider, LinkedEntity, PolicyChange, PolicyFilter, PolicyMetadata, PolicyStore,
+    PolicyStoreError,
+};
+use hodei_hrn::Hrn;
+use hodei_authz_postgres::{PostgresAuditSink, PostgresEntityStore, PostgresPolicyStore};
 use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
 
 async fn create_test_pool() -> PgPool {
     let database_url = std::env::var("DATABASE_URL")
@@ -152,13 +159,25 @@ async fn test_load_all_policies() {
         .expect("Failed to load policies");
     
     // Verify policies are loaded
-    // Note: We can't easily inspect PolicySet, but we can verify it was created
+    assert!(policy_set.policy(&PolicyId::new(&id1)).is_some());
+    assert!(policy_set.policy(&PolicyId::new(&id2)).is_some());
     
     // Cleanup
     store.delete_policy(&id1).await.ok();
     store.delete_policy(&id2).await.ok();
 }
 
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_list_policies_contract() {
+    let pool = create_test_pool().await;
+    let store = PostgresPolicyStore::new(pool);
+    
+    store.migrate().await.expect("Migration failed");
+    
+    hodei_authz::testing::check_list_policies(&store).await;
+}
+
 #[tokio::test]
 #[ignore] // Requires database
 async fn test_update_nonexistent_policy() {
@@ -168,7 +187,9 @@ async fn test_update_nonexistent_policy() {
     store.migrate().await.expect("Migration failed");
     
     // Try to update non-existent policy
-    let result = store.update_policy("nonexistent-id", "content".to_string()).await;
+    let result = store
+        .update_policy("nonexistent-id", "permit(principal, action, resource);".to_string())
+        .await;
     
     assert!(matches!(result, Err(PolicyStoreError::NotFound(_))));
 }
@@ -186,3 +207,411 @@ async fn test_delete_nonexistent_policy() {
     
     assert!(matches!(result, Err(PolicyStoreError::NotFound(_))));
 }
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_rejects_invalid_policies() {
+    let pool = create_test_pool().await;
+    let (schema, _) = cedar_policy::Schema::from_cedarschema_str(
+        "entity User; action Read appliesTo { principal: User, resource: User };",
+    )
+    .expect("Invalid schema");
+    let store = PostgresPolicyStore::new(pool).with_schema(schema);
+    
+    store.migrate().await.expect("Migration failed");
+    
+    // Syntax errors never reach the database
+    let result = store.create_policy("permit(principal, action".to_string()).await;
+    assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+    
+    // Unknown entity types are rejected with diagnostics
+    let result = store
+        .create_policy("permit(principal is Group, action, resource);".to_string())
+        .await;
+    match result {
+        Err(PolicyStoreError::Validation(diagnostics)) => assert!(!diagnostics.is_empty()),
+        other => panic!("Expected validation error, got {:?}", other),
+    }
+    
+    let policy_id = store
+        .create_policy(r#"permit(principal is User, action == Action::"Read", resource);"#.to_string())
+        .await
+        .expect("Failed to create valid policy");
+    
+    let result = store
+        .update_policy(&policy_id, "permit(principal is Group, action, resource);".to_string())
+        .await;
+    assert!(matches!(result, Err(PolicyStoreError::Validation(_))));
+    
+    // Cleanup
+    store.delete_policy(&policy_id).await.ok();
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_policy_history_and_rollback() {
+    let pool = create_test_pool().await;
+    let store = PostgresPolicyStore::new(pool);
+    
+    store.migrate().await.expect("Migration failed");
+    
+    let permit = "permit(principal, action, resource);";
+    let forbid = "forbid(principal, action, resource);";
+    
+    let policy_id = store
+        .create_policy_with(permit.to_string(), PolicyChange::by("alice").with_reason("Initial"))
+        .await
+        .expect("Failed to create policy");
+    
+    let version = store
+        .update_policy_versioned(&policy_id, forbid.to_string(), 1, PolicyChange::by("bob"))
+        .await
+        .expect("Failed to update policy");
+    assert_eq!(version, 2);
+    
+    // A stale expected version is rejected
+    let result = store
+        .update_policy_versioned(&policy_id, permit.to_string(), 1, PolicyChange::by("carol"))
+        .await;
+    assert!(matches!(
+        result,
+        Err(PolicyStoreError::Conflict { expected: 1, actual: 2, .. })
+    ));
+    
+    let history = store.policy_history(&policy_id).await.expect("Failed to list history");
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].author.as_deref(), Some("alice"));
+    assert_eq!(history[0].reason.as_deref(), Some("Initial"));
+    assert_eq!(history[1].content, forbid);
+    
+    let version = store
+        .rollback_policy(&policy_id, 1, PolicyChange::by("alice"))
+        .await
+        .expect("Failed to roll back");
+    assert_eq!(version, 3);
+    assert_eq!(store.get_policy(&policy_id).await.unwrap(), Some(permit.to_string()));
+    
+    let rollback = store
+        .get_policy_version(&policy_id, 3)
+        .await
+        .expect("Failed to get version")
+        .expect("Version 3 should exist");
+    assert_eq!(rollback.reason.as_deref(), Some("Rollback to version 1"));
+    
+    // The deletion is recorded as a tombstone version
+    store
+        .delete_policy_with(&policy_id, PolicyChange::by("dave").with_reason("Retired"))
+        .await
+        .expect("Failed to delete policy");
+    let history = store.policy_history(&policy_id).await.expect("Failed to list history");
+    assert_eq!(history.len(), 4);
+    assert!(history[..3].iter().all(|version| !version.deleted));
+    let tombstone = &history[3];
+    assert!(tombstone.deleted);
+    assert_eq!(tombstone.version, 4);
+    assert_eq!(tombstone.content, permit);
+    assert_eq!(tombstone.author.as_deref(), Some("dave"));
+    assert_eq!(tombstone.reason.as_deref(), Some("Retired"));
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_template_links() {
+    let pool = create_test_pool().await;
+    let store = PostgresPolicyStore::new(pool);
+    
+    store.migrate().await.expect("Migration failed");
+    
+    let template_id = store
+        .create_template("permit(principal == ?principal, action, resource == ?resource);".to_string())
+        .await
+        .expect("Failed to create template");
+    
+    let alice: Hrn = "hrn:hodei:docs:global:tenant-1:user/alice".parse().unwrap();
+    let document: Hrn = "hrn:hodei:docs:global:tenant-1:document/1".parse().unwrap();
+    let link_id = store
+        .link_template(
+            &template_id,
+            Some(LinkedEntity::new("User", alice.clone())),
+            Some(LinkedEntity::new("Document", document)),
+        )
+        .await
+        .expect("Failed to link template");
+    
+    let links = store
+        .list_template_links(Some(&template_id))
+        .await
+        .expect("Failed to list links");
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].principal.as_ref().map(|p| &p.hrn), Some(&alice));
+    
+    let policy_set = store.load_all_policies().await.expect("Failed to load policies");
+    assert!(policy_set.policy(&cedar_policy::PolicyId::new(&link_id)).is_some());
+    
+    // Links without every slot are rejected
+    let result = store
+        .link_template(&template_id, Some(LinkedEntity::new("User", alice)), None)
+        .await;
+    assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+    
+    store.unlink_template(&link_id).await.expect("Failed to unlink");
+    store.delete_template(&template_id).await.expect("Failed to delete template");
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_policy_metadata_and_enabled_flag() {
+    let pool = create_test_pool().await;
+    let store = PostgresPolicyStore::new(pool);
+    
+    store.migrate().await.expect("Migration failed");
+    
+    let tag = format!("freeze-{}", uuid::Uuid::new_v4());
+    let metadata = PolicyMetadata {
+        name: Some("Billing freeze".to_string()),
+        description: Some("Blocks billing changes during the audit".to_string()),
+        tags: vec![tag.clone(), "temporary".to_string()],
+        owner: Some("finance".to_string()),
+    };
+    let policy_id = store
+        .create_policy_with_metadata(
+            "forbid(principal, action, resource);".to_string(),
+            metadata.clone(),
+            PolicyChange::by("alice"),
+        )
+        .await
+        .expect("Failed to create policy");
+    
+    let record = store
+        .get_policy_record(&policy_id)
+        .await
+        .expect("Failed to get record")
+        .expect("Record should exist");
+    assert_eq!(record.metadata, metadata);
+    assert!(record.enabled);
+    assert_eq!(record.effect, Some(cedar_policy::Effect::Forbid));
+    
+    // Disabled policies stay listed but are not loaded
+    store.set_policy_enabled(&policy_id, false).await.expect("Failed to disable");
+    let policy_set = store.load_all_policies().await.expect("Failed to load policies");
+    assert!(policy_set.policy(&cedar_policy::PolicyId::new(&policy_id)).is_none());
+    
+    let page = store
+        .list_policy_records(&PolicyFilter::default().with_tag(&tag).with_enabled(false))
+        .await
+        .expect("Failed to list records");
+    assert_eq!(page.total, 1);
+    assert_eq!(page.records[0].id, policy_id);
+    
+    let page = store
+        .list_policy_records(&PolicyFilter::default().with_tag(&tag).with_enabled(true))
+        .await
+        .expect("Failed to list records");
+    assert_eq!(page.total, 0);
+    
+    // Wildcards in the name filter are matched literally
+    let page = store
+        .list_policy_records(&PolicyFilter::default().with_tag(&tag).with_name("billing"))
+        .await
+        .expect("Failed to list records");
+    assert_eq!(page.total, 1);
+    let page = store
+        .list_policy_records(&PolicyFilter::default().with_tag(&tag).with_name("billing_"))
+        .await
+        .expect("Failed to list records");
+    assert_eq!(page.total, 0);
+    let page = store
+        .list_policy_records(&PolicyFilter::default().with_tag(&tag).with_name("%"))
+        .await
+        .expect("Failed to list records");
+    assert_eq!(page.total, 0);
+    
+    // Timestamps are stored with their time zone
+    let skew = match SystemTime::now().duration_since(record.created_at) {
+        Ok(skew) => skew,
+        Err(e) => e.duration(),
+    };
+    assert!(skew < Duration::from_secs(60));
+    
+    store.set_policy_enabled(&policy_id, true).await.expect("Failed to enable");
+    let policy_set = store.load_all_policies().await.expect("Failed to load policies");
+    assert!(policy_set.policy(&cedar_policy::PolicyId::new(&policy_id)).is_some());
+    
+    let missing = store.set_policy_enabled("nonexistent-id", false).await;
+    assert!(matches!(missing, Err(PolicyStoreError::NotFound(_))));
+    
+    // Cleanup
+    store.delete_policy(&policy_id).await.ok();
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_tenant_policies() {
+    let pool = create_test_pool().await;
+    let store = PostgresPolicyStore::new(pool);
+    
+    store.migrate().await.expect("Migration failed");
+    
+    let tenant = format!("tenant-{}", uuid::Uuid::new_v4());
+    let policy_id = store
+        .create_tenant_policy(&tenant, "permit(principal, action, resource);".to_string())
+        .await
+        .expect("Failed to create tenant policy");
+    
+    // Tenant policies stay out of the global set
+    let global = store.list_policies().await.expect("Failed to list policies");
+    assert!(global.iter().all(|(id, _)| id != &policy_id));
+    
+    let tenant_set = store
+        .load_tenant_policies(&tenant)
+        .await
+        .expect("Failed to load tenant policies");
+    assert_eq!(tenant_set.policies().count(), 1);
+    
+    let record = store
+        .get_policy_record(&policy_id)
+        .await
+        .expect("Failed to get record")
+        .expect("Record should exist");
+    assert_eq!(record.tenant_id.as_deref(), Some(tenant.as_str()));
+    
+    store.set_policy_enabled(&policy_id, false).await.expect("Failed to disable");
+    let tenant_set = store
+        .load_tenant_policies(&tenant)
+        .await
+        .expect("Failed to load tenant policies");
+    assert_eq!(tenant_set.policies().count(), 0);
+    
+    // Cleanup
+    store.delete_policy(&policy_id).await.ok();
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_audit_sink_records_decisions() {
+    let pool = create_test_pool().await;
+    let store = PostgresPolicyStore::new(pool.clone());
+    
+    store.migrate().await.expect("Migration failed");
+    
+    let principal: Hrn = format!("hrn:hodei:docs:global:tenant-1:user/{}", uuid::Uuid::new_v4())
+        .parse()
+        .unwrap();
+    let event = AuditEvent {
+        timestamp: SystemTime::now(),
+        principal: principal.clone(),
+        action: r#"MyApp::Action::"Read""#.to_string(),
+        resource: principal.clone(),
+        decision: Decision::Allow,
+        determining_policies: vec!["policy-1".to_string()],
+        errors: Vec::new(),
+        latency: Duration::from_micros(42),
+        context: BTreeMap::from([("ip".to_string(), serde_json::json!("10.0.0.1"))]),
+    };
+    
+    let sink = PostgresAuditSink::new(pool.clone());
+    sink.record(&[event.clone(), event]).await.expect("Failed to record events");
+    
+    let (count, allowed): (i64, bool) = sqlx::query_as(
+        "SELECT COUNT(*), BOOL_AND(allowed) FROM authz_decisions WHERE principal = $1",
+    )
+    .bind(principal.to_string())
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to query decisions");
+    assert_eq!(count, 2);
+    assert!(allowed);
+    
+    // Cleanup
+    sqlx::query("DELETE FROM authz_decisions WHERE principal = $1")
+        .bind(principal.to_string())
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_entity_store_hierarchy() {
+    let pool = create_test_pool().await;
+    let store = PostgresEntityStore::new(pool);
+    
+    store.migrate().await.expect("Migration failed");
+    
+    let run = uuid::Uuid::new_v4();
+    let alice: Hrn = format!("hrn:hodei:docs:global:tenant-1:user/alice-{}", run).parse().unwrap();
+    let staff: Hrn = format!("hrn:hodei:docs:global:tenant-1:group/staff-{}", run).parse().unwrap();
+    let all: Hrn = format!("hrn:hodei:docs:global:tenant-1:group/all-{}", run).parse().unwrap();
+    let staff_group = LinkedEntity::new("Group", staff.clone());
+    let all_group = LinkedEntity::new("Group", all.clone());
+    
+    let user = cedar_policy::Entity::new(
+        LinkedEntity::new("User", alice.clone()).to_euid().unwrap(),
+        [(
+            "email".to_string(),
+            cedar_policy::RestrictedExpression::new_string("alice@example.com".to_string()),
+        )]
+        .into(),
+        Default::default(),
+    )
+    .unwrap();
+    store
+        .upsert_entities(&[
+            user,
+            cedar_policy::Entity::with_uid(staff_group.to_euid().unwrap()),
+            cedar_policy::Entity::with_uid(all_group.to_euid().unwrap()),
+        ])
+        .await
+        .expect("Failed to upsert entities");
+    
+    store.add_parent(&alice, &staff_group).await.expect("Failed to add parent");
+    store.add_parent(&staff, &all_group).await.expect("Failed to add parent");
+    // Cycles must not break the ancestor query
+    store.add_parent(&all, &staff_group).await.expect("Failed to add parent");
+    
+    let ancestors = store.ancestors(&alice).await.expect("Failed to get ancestors");
+    assert_eq!(ancestors.len(), 2);
+    assert!(ancestors.contains(&staff_group));
+    assert!(ancestors.contains(&all_group));
+    
+    let loaded = store
+        .load(&LinkedEntity::new("User", alice.clone()).to_euid().unwrap())
+        .await
+        .expect("Failed to load entity")
+        .expect("Entity should exist");
+    assert!(loaded.attr("email").is_some());
+    // Loaded entities carry every ancestor, not only their direct parents
+    assert!(loaded.into_inner().2.contains(&all_group.to_euid().unwrap()));
+    
+    // A repeated HRN in one batch keeps the last entity
+    let renamed = |email: &str| {
+        cedar_policy::Entity::new(
+            LinkedEntity::new("User", alice.clone()).to_euid().unwrap(),
+            [(
+                "email".to_string(),
+                cedar_policy::RestrictedExpression::new_string(email.to_string()),
+            )]
+            .into(),
+            [staff_group.to_euid().unwrap()].into(),
+        )
+        .unwrap()
+    };
+    store
+        .upsert_entities(&[renamed("old@example.com"), renamed("new@example.com")])
+        .await
+        .expect("Failed to upsert repeated entities");
+    let stored = store.get_entity(&alice).await.expect("Failed to get entity").unwrap();
+    assert_eq!(
+        stored.attr("email").unwrap().unwrap(),
+        cedar_policy::EvalResult::String("new@example.com".to_string())
+    );
+    
+    assert!(store.remove_parent(&alice, &staff).await.expect("Failed to remove parent"));
+    assert!(store.ancestors(&alice).await.expect("Failed to get ancestors").is_empty());
+    
+    // Cleanup
+    for hrn in [&alice, &staff, &all] {
+        store.delete_entity(hrn).await.ok();
+    }
+    assert!(store.get_entity(&alice).await.expect("Failed to get entity").is_none());
+}
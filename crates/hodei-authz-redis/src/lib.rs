@@ -5,25 +5,190 @@
 use async_trait::async_trait;
 use futures_util::stream::StreamExt;
 use hodei_authz::{CacheError, CacheInvalidation};
+use redis::aio::PubSub;
 use redis::Client;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// Pub/Sub channel used to broadcast policy invalidations
+pub const INVALIDATION_CHANNEL: &str = "hodei:policy:invalidate";
+
+/// Backoff configuration used when the Pub/Sub connection is lost
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnection attempt
+    pub initial_delay: Duration,
+    /// Upper bound for the exponential backoff
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay to wait after a failed attempt that waited `delay`
+    fn next_delay(&self, delay: Duration) -> Duration {
+        (delay * 2).min(self.max_delay)
+    }
+}
+
+/// Handle to a running invalidation subscription
+///
+/// The background task stops when [`shutdown`](Self::shutdown) is called or
+/// when the handle is dropped.
+pub struct InvalidationSubscription {
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl InvalidationSubscription {
+    /// Stop the subscription and wait for the background task to finish
+    pub async fn shutdown(mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+
+    /// Returns true if the background task is no longer running
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
 
 /// Redis implementation of CacheInvalidation
 pub struct RedisCacheInvalidation {
     client: Client,
+    reconnect_policy: ReconnectPolicy,
+    subscription: Mutex<Option<InvalidationSubscription>>,
 }
 
 impl RedisCacheInvalidation {
     /// Create a new Redis cache invalidation handler
     pub async fn new(redis_url: &str) -> Result<Self, redis::RedisError> {
         let client = Client::open(redis_url)?;
-        
+
         // Test connection
         let mut conn = client.get_multiplexed_async_connection().await?;
         redis::cmd("PING")
             .query_async::<()>(&mut conn)
             .await?;
-        
-        Ok(Self { client })
+
+        Ok(Self {
+            client,
+            reconnect_policy: ReconnectPolicy::default(),
+            subscription: Mutex::new(None),
+        })
+    }
+
+    /// Override the backoff used to reconnect the Pub/Sub connection
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
+    /// Subscribe to invalidation events and return a handle to the subscription
+    ///
+    /// The callback is invoked for every message published on
+    /// [`INVALIDATION_CHANNEL`]. If the connection drops, the subscription is
+    /// re-established with exponential backoff and the callback is invoked once
+    /// after reconnecting, since messages published in between were lost.
+    pub async fn subscribe<F>(&self, callback: F) -> Result<InvalidationSubscription, CacheError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        // The first connection is made eagerly so configuration errors reach the caller
+        let pubsub = connect_pubsub(&self.client)
+            .await
+            .map_err(|e| CacheError::Subscribe(e.to_string()))?;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let task = tokio::spawn(run_subscription(
+            self.client.clone(),
+            self.reconnect_policy.clone(),
+            pubsub,
+            callback,
+            shutdown_rx,
+        ));
+
+        Ok(InvalidationSubscription {
+            shutdown: Some(shutdown_tx),
+            task,
+        })
+    }
+
+    /// Stop the subscription started through `subscribe_to_invalidations`, if any
+    pub async fn unsubscribe(&self) {
+        let subscription = self.subscription.lock().unwrap().take();
+        if let Some(subscription) = subscription {
+            subscription.shutdown().await;
+        }
+    }
+}
+
+async fn connect_pubsub(client: &Client) -> Result<PubSub, redis::RedisError> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(INVALIDATION_CHANNEL).await?;
+    Ok(pubsub)
+}
+
+async fn run_subscription<F>(
+    client: Client,
+    policy: ReconnectPolicy,
+    mut pubsub: PubSub,
+    callback: F,
+    mut shutdown: oneshot::Receiver<()>,
+) where
+    F: Fn() + Send + Sync + 'static,
+{
+    loop {
+        {
+            let mut messages = pubsub.on_message();
+            loop {
+                tokio::select! {
+                    _ = &mut shutdown => {
+                        tracing::debug!("Redis invalidation subscription stopped");
+                        return;
+                    }
+                    message = messages.next() => match message {
+                        Some(_) => callback(),
+                        None => break,
+                    },
+                }
+            }
+        }
+
+        tracing::warn!("Redis invalidation subscription lost, reconnecting");
+
+        let mut delay = policy.initial_delay;
+        pubsub = loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    tracing::debug!("Redis invalidation subscription stopped while reconnecting");
+                    return;
+                }
+                _ = tokio::time::sleep(delay) => {}
+            }
+
+            match connect_pubsub(&client).await {
+                Ok(pubsub) => break pubsub,
+                Err(e) => {
+                    tracing::warn!("Redis reconnection failed, retrying in {:?}: {}", delay, e);
+                    delay = policy.next_delay(delay);
+                }
+            }
+        };
+
+        tracing::info!("Redis invalidation subscription restored, triggering full reload");
+        callback();
     }
 }
 
@@ -35,33 +200,53 @@ impl CacheInvalidation for RedisCacheInvalidation {
             .get_multiplexed_async_connection()
             .await
             .map_err(|e| CacheError::Connection(e.to_string()))?;
-        
+
         redis::cmd("PUBLISH")
-            .arg("hodei:policy:invalidate")
+            .arg(INVALIDATION_CHANNEL)
             .arg("reload")
             .query_async::<()>(&mut conn)
             .await
             .map_err(|e| CacheError::Publish(e.to_string()))?;
-        
+
         Ok(())
     }
-    
+
+    /// Starts a background subscription owned by this handler
+    ///
+    /// Any previous subscription is stopped. Use [`RedisCacheInvalidation::subscribe`]
+    /// to manage the subscription handle directly.
     async fn subscribe_to_invalidations<F>(&self, callback: F) -> Result<(), CacheError>
     where
         F: Fn() + Send + Sync + 'static,
     {
-        // Note: This is a simplified implementation
-        // In production, you'd want proper PubSub handling with reconnection logic
-        
-        tracing::warn!("Redis PubSub subscription is not fully implemented in this version");
-        tracing::info!("Cache invalidation will work via explicit invalidate_policies() calls");
-        
-        // For now, we just acknowledge the subscription request
-        // A full implementation would require:
-        // 1. A dedicated PubSub connection (not MultiplexedConnection)
-        // 2. Proper error handling and reconnection
-        // 3. Background task management
-        
+        let subscription = self.subscribe(callback).await?;
+        let previous = self.subscription.lock().unwrap().replace(subscription);
+        if let Some(previous) = previous {
+            previous.shutdown().await;
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max_delay() {
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        };
+
+        let mut delay = policy.initial_delay;
+        let mut delays = Vec::new();
+        for _ in 0..5 {
+            delay = policy.next_delay(delay);
+            delays.push(delay.as_millis());
+        }
+
+        assert_eq!(delays, vec![200, 400, 500, 500, 500]);
+    }
+}
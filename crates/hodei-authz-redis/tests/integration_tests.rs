@@ -4,33 +4,70 @@
 //! Run with: docker-compose up -d redis
 
 use hodei_authz::CacheInvalidation;
-use hodei_authz_redis::RedisCacheInvalidation;
-use std::sync::{Arc, Mutex};
+use hodei_authz_redis::{ReconnectPolicy, RedisCacheInvalidation};
 use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+
+/// The tests share the invalidation channel and kill Pub/Sub connections, so
+/// they run one at a time
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+fn redis_url() -> String {
+    std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string())
+}
+
+async fn connect() -> RedisCacheInvalidation {
+    RedisCacheInvalidation::new(&redis_url())
+        .await
+        .expect("Failed to connect to Redis")
+        .with_reconnect_policy(ReconnectPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(100),
+        })
+}
+
+/// Callback that reports every invocation on the returned channel
+fn callback() -> (impl Fn() + Send + Sync + 'static, mpsc::UnboundedReceiver<()>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let callback = move || {
+        let _ = tx.send(());
+    };
+    (callback, rx)
+}
+
+/// Wait for the next callback invocation
+async fn next_call(rx: &mut mpsc::UnboundedReceiver<()>) {
+    tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("Callback was not invoked")
+        .expect("Subscription stopped");
+}
+
+/// Close every Pub/Sub connection from the server side
+async fn kill_pubsub_connections() {
+    let client = redis::Client::open(redis_url()).unwrap();
+    let mut conn = client.get_multiplexed_async_connection().await.unwrap();
+    let killed: i64 = redis::cmd("CLIENT")
+        .arg("KILL")
+        .arg("TYPE")
+        .arg("pubsub")
+        .query_async(&mut conn)
+        .await
+        .expect("Failed to kill Pub/Sub connections");
+    assert!(killed > 0, "No Pub/Sub connection to kill");
+}
 
 #[tokio::test]
 #[ignore] // Requires Redis
 async fn test_redis_connection() {
-    let redis_url = std::env::var("REDIS_URL")
-        .unwrap_or_else(|_| "redis://localhost:6379".to_string());
-    
-    let cache = RedisCacheInvalidation::new(&redis_url)
-        .await
-        .expect("Failed to connect to Redis");
-    
-    // If we get here, connection was successful
+    connect().await;
 }
 
 #[tokio::test]
 #[ignore] // Requires Redis
 async fn test_invalidate_policies() {
-    let redis_url = std::env::var("REDIS_URL")
-        .unwrap_or_else(|_| "redis://localhost:6379".to_string());
-    
-    let cache = RedisCacheInvalidation::new(&redis_url)
-        .await
-        .expect("Failed to connect to Redis");
-    
+    let cache = connect().await;
+
     // Publish invalidation
     cache.invalidate_policies()
         .await
@@ -38,50 +75,89 @@ async fn test_invalidate_policies() {
 }
 
 #[tokio::test]
-#[ignore] // Requires Redis - This test is complex and may timeout
+#[ignore] // Requires Redis
 async fn test_pubsub_invalidation() {
-    let redis_url = std::env::var("REDIS_URL")
-        .unwrap_or_else(|_| "redis://localhost:6379".to_string());
-    
-    let cache_publisher = RedisCacheInvalidation::new(&redis_url)
-        .await
-        .expect("Failed to connect to Redis");
-    
-    let cache_subscriber = RedisCacheInvalidation::new(&redis_url)
+    let _serial = SERIAL.lock().await;
+    let cache_publisher = connect().await;
+    let cache_subscriber = connect().await;
+
+    // Subscription runs in a background task owned by the subscriber
+    let (callback, mut calls) = callback();
+    cache_subscriber
+        .subscribe_to_invalidations(callback)
         .await
-        .expect("Failed to connect to Redis");
-    
-    let received = Arc::new(Mutex::new(false));
-    let received_clone = received.clone();
-    
-    // Spawn subscriber in background
-    let subscriber_handle = tokio::spawn(async move {
-        let result = cache_subscriber
-            .subscribe_to_invalidations(move || {
-                *received_clone.lock().unwrap() = true;
-            })
-            .await;
-        
-        result
-    });
-    
-    // Give subscriber time to connect
-    tokio::time::sleep(Duration::from_millis(100)).await;
-    
+        .expect("Failed to subscribe");
+
     // Publish invalidation
     cache_publisher
         .invalidate_policies()
         .await
         .expect("Failed to publish invalidation");
-    
-    // Give time for message to be received
-    tokio::time::sleep(Duration::from_millis(100)).await;
-    
-    // Check if message was received
-    let was_received = *received.lock().unwrap();
-    
-    // Cancel subscriber
-    subscriber_handle.abort();
-    
-    assert!(was_received, "Invalidation message was not received");
+
+    next_call(&mut calls).await;
+
+    cache_subscriber.unsubscribe().await;
+}
+
+#[tokio::test]
+#[ignore] // Requires Redis
+async fn test_reconnects_and_reloads_after_connection_loss() {
+    let _serial = SERIAL.lock().await;
+    let cache = connect().await;
+
+    let (callback, mut calls) = callback();
+    let subscription = cache.subscribe(callback).await.expect("Failed to subscribe");
+
+    // Dropping the connection triggers a reconnect followed by a full reload
+    kill_pubsub_connections().await;
+    next_call(&mut calls).await;
+    assert!(!subscription.is_finished());
+
+    // The new connection is subscribed again
+    cache.invalidate_policies()
+        .await
+        .expect("Failed to publish invalidation");
+    next_call(&mut calls).await;
+
+    // Every connection loss is recovered, not just the first
+    kill_pubsub_connections().await;
+    next_call(&mut calls).await;
+
+    subscription.shutdown().await;
+}
+
+#[tokio::test]
+#[ignore] // Requires Redis
+async fn test_subscription_shutdown() {
+    let _serial = SERIAL.lock().await;
+    let cache = connect().await;
+
+    let (callback, mut calls) = callback();
+    let subscription = cache.subscribe(callback).await.expect("Failed to subscribe");
+
+    assert!(!subscription.is_finished());
+    subscription.shutdown().await;
+
+    // The stopped task has dropped the callback, so nothing published after
+    // shutdown can reach it
+    cache.invalidate_policies()
+        .await
+        .expect("Failed to publish invalidation");
+    assert_eq!(calls.recv().await, None);
+}
+
+#[tokio::test]
+#[ignore] // Requires Redis
+async fn test_unsubscribe_stops_owned_subscription() {
+    let _serial = SERIAL.lock().await;
+    let cache = connect().await;
+
+    let (callback, mut calls) = callback();
+    cache
+        .subscribe_to_invalidations(callback)
+        .await
+        .expect("Failed to subscribe");
+
+    cache.unsubscribe().await;
+    assert_eq!(calls.recv().await, None);
 }
@@ -0,0 +1,332 @@
+//! Auditoría de decisiones de autorización
+//!
+//! Con `HodeiAuthServiceBuilder::with_audit_sink` cada evaluación genera un
+//! [`AuditEvent`]. Los eventos se encolan sin bloquear y una tarea en segundo
+//! plano los entrega al sink por lotes, así la auditoría no añade latencia a
+//! `is_authorized`.
+
+use async_trait::async_trait;
+use hodei_authz::{AuditError, AuditEvent, AuditSink};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+/// Configuración del muestreo y del envío por lotes
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditConfig {
+    /// Fracción de decisiones que se registran, entre 0.0 y 1.0
+    pub sample_rate: f64,
+    /// Registra todas las denegaciones aunque no entren en la muestra
+    pub always_record_denied: bool,
+    /// Número de eventos que fuerza el envío de un lote
+    pub batch_size: usize,
+    /// Tiempo máximo que un evento espera en un lote incompleto
+    pub flush_interval: Duration,
+    /// Eventos pendientes a partir de los cuales se descartan los nuevos
+    pub queue_capacity: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 1.0,
+            always_record_denied: true,
+            batch_size: 100,
+            flush_interval: Duration::from_secs(1),
+            queue_capacity: 10_000,
+        }
+    }
+}
+
+impl AuditConfig {
+    /// Registra solo una fracción de las decisiones (las denegaciones siempre,
+    /// salvo que se desactive `always_record_denied`)
+    pub fn with_sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = sample_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Tamaño y espera máxima de cada lote
+    pub fn with_batching(mut self, batch_size: usize, flush_interval: Duration) -> Self {
+        self.batch_size = batch_size.max(1);
+        self.flush_interval = flush_interval;
+        self
+    }
+}
+
+/// Sink que emite cada decisión como un evento de `tracing`
+///
+/// Los eventos usan el target `hodei::audit`, de modo que se pueden filtrar o
+/// redirigir con la configuración del subscriber.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingAuditSink;
+
+#[async_trait]
+impl AuditSink for TracingAuditSink {
+    async fn record(&self, events: &[AuditEvent]) -> Result<(), AuditError> {
+        for event in events {
+            tracing::info!(
+                target: "hodei::audit",
+                principal = %event.principal,
+                action = %event.action,
+                resource = %event.resource,
+                allowed = event.is_allowed(),
+                policies = ?event.determining_policies,
+                errors = event.errors.len(),
+                latency_us = event.latency.as_micros() as u64,
+                "Authorization decision"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Sink que añade cada decisión como una línea JSON a un fichero
+///
+/// El formato de cada línea es el de [`AuditEvent::to_json`].
+pub struct JsonLinesAuditSink {
+    file: Mutex<tokio::fs::File>,
+}
+
+impl JsonLinesAuditSink {
+    /// Abre (o crea) el fichero en modo append
+    pub async fn new(path: impl AsRef<Path>) -> Result<Self, AuditError> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .await
+            .map_err(|e| AuditError::Io(format!("{}: {}", path.as_ref().display(), e)))?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonLinesAuditSink {
+    async fn record(&self, events: &[AuditEvent]) -> Result<(), AuditError> {
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(&event.to_json().to_string());
+            lines.push('\n');
+        }
+
+        let mut file = self.file.lock().await;
+        file.write_all(lines.as_bytes())
+            .await
+            .map_err(|e| AuditError::Io(e.to_string()))?;
+        file.flush().await.map_err(|e| AuditError::Io(e.to_string()))
+    }
+}
+
+enum AuditMessage {
+    Event(Box<AuditEvent>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Cola de eventos hacia la tarea que alimenta el sink
+pub(crate) struct AuditLogger {
+    sender: mpsc::Sender<AuditMessage>,
+    sample_rate: f64,
+    always_record_denied: bool,
+    /// Decisiones vistas, para el muestreo
+    seen: AtomicU64,
+    /// Eventos descartados por tener la cola llena
+    dropped: AtomicU64,
+}
+
+impl AuditLogger {
+    /// Lanza la tarea de envío en el runtime actual
+    pub(crate) fn spawn(sink: Arc<dyn AuditSink>, config: AuditConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(config.queue_capacity.max(1));
+        tokio::spawn(deliver(
+            sink,
+            receiver,
+            config.batch_size.max(1),
+            config.flush_interval.max(Duration::from_millis(1)),
+        ));
+
+        Self {
+            sender,
+            sample_rate: config.sample_rate,
+            always_record_denied: config.always_record_denied,
+            seen: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Decide si la decisión entra en la muestra
+    ///
+    /// El muestreo es determinista: con `sample_rate = 0.25` se registra una
+    /// de cada cuatro decisiones.
+    pub(crate) fn should_record(&self, allowed: bool) -> bool {
+        if !allowed && self.always_record_denied {
+            return true;
+        }
+
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) + 1;
+        (n as f64 * self.sample_rate).floor() > ((n - 1) as f64 * self.sample_rate).floor()
+    }
+
+    /// Encola un evento sin esperar; si la cola está llena se descarta
+    pub(crate) fn log(&self, event: AuditEvent) {
+        if self.sender.try_send(AuditMessage::Event(Box::new(event))).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!("Audit queue full, event dropped");
+        }
+    }
+
+    /// Espera a que los eventos encolados hasta ahora lleguen al sink
+    pub(crate) async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(AuditMessage::Flush(done)).await.is_ok() {
+            let _ = wait.await;
+        }
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Agrupa los eventos en lotes y los entrega al sink
+///
+/// Termina, entregando lo pendiente, cuando se cierra la cola.
+async fn deliver(
+    sink: Arc<dyn AuditSink>,
+    mut receiver: mpsc::Receiver<AuditMessage>,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut ticker = tokio::time::interval(flush_interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            message = receiver.recv() => match message {
+                Some(AuditMessage::Event(event)) => {
+                    batch.push(*event);
+                    if batch.len() >= batch_size {
+                        write_batch(sink.as_ref(), &mut batch).await;
+                    }
+                }
+                Some(AuditMessage::Flush(done)) => {
+                    write_batch(sink.as_ref(), &mut batch).await;
+                    let _ = done.send(());
+                }
+                None => {
+                    write_batch(sink.as_ref(), &mut batch).await;
+                    break;
+                }
+            },
+            _ = ticker.tick() => write_batch(sink.as_ref(), &mut batch).await,
+        }
+    }
+}
+
+async fn write_batch(sink: &dyn AuditSink, batch: &mut Vec<AuditEvent>) {
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(e) = sink.record(batch).await {
+        tracing::error!(events = batch.len(), "Failed to record audit events: {}", e);
+    }
+    batch.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::HodeiAuthService;
+    use crate::test_support::{doc, Read};
+    use cedar_policy::{Context, RestrictedExpression, Schema};
+    use hodei_authz::InMemoryPolicyStore;
+
+    /// Schema con una entidad `Doc` y una acción `Read` que recibe la IP
+    fn doc_schema() -> Schema {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity Doc; action Read appliesTo { principal: Doc, resource: Doc, context: { ip?: String } };",
+        )
+        .unwrap();
+        schema
+    }
+
+    /// Sink que guarda los lotes recibidos
+    #[derive(Default)]
+    struct CollectingSink {
+        batches: std::sync::Mutex<Vec<Vec<AuditEvent>>>,
+    }
+
+    #[async_trait]
+    impl AuditSink for CollectingSink {
+        async fn record(&self, events: &[AuditEvent]) -> Result<(), AuditError> {
+            self.batches.lock().unwrap().push(events.to_vec());
+            Ok(())
+        }
+    }
+
+    fn logger(sample_rate: f64) -> AuditLogger {
+        AuditLogger::spawn(
+            Arc::new(TracingAuditSink),
+            AuditConfig::default().with_sample_rate(sample_rate),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_sampling() {
+        let quarter = logger(0.25);
+        let sampled = (0..100).filter(|_| quarter.should_record(true)).count();
+        assert_eq!(sampled, 25);
+
+        let none = logger(0.0);
+        assert!(!none.should_record(true));
+        assert!(none.should_record(false));
+    }
+
+    #[tokio::test]
+    async fn test_service_records_decisions_in_batches() {
+        let store = InMemoryPolicyStore::new();
+        store
+            .insert("read".to_string(), r#"permit(principal, action, resource);"#.to_string())
+            .unwrap();
+        let sink = Arc::new(CollectingSink::default());
+
+        let auth = HodeiAuthService::builder()
+            .with_policy_store(store)
+            .with_schema(doc_schema())
+            .with_audit_sink(sink.clone())
+            .with_audit_config(AuditConfig::default().with_batching(2, Duration::from_secs(60)))
+            .build()
+            .await
+            .unwrap();
+
+        let context = Context::from_pairs([(
+            "ip".to_string(),
+            RestrictedExpression::new_string("10.0.0.1".to_string()),
+        )])
+        .unwrap();
+        for n in 0..3 {
+            let resource = doc("acme", &n.to_string());
+            auth.is_authorized(&doc("acme", "alice"), &Read, &resource, context.clone())
+                .await
+                .unwrap();
+        }
+        auth.flush_audit().await;
+
+        let batches = sink.batches.lock().unwrap();
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 1]);
+
+        let event = &batches[0][0];
+        assert!(event.is_allowed());
+        assert_eq!(event.determining_policies, vec!["read".to_string()]);
+        assert_eq!(event.principal, doc("acme", "alice").0);
+        assert_eq!(event.context.get("ip"), Some(&serde_json::json!("10.0.0.1")));
+    }
+}
@@ -0,0 +1,179 @@
+//! Evaluación de peticiones de autorización
+//!
+//! Construye la `Request` y las `Entities` de Cedar a partir de los mappers
+//! generados por los derives `HodeiEntity` y `HodeiAction`, de forma que las
+//! aplicaciones no tengan que montar `EntityUid` ni JSON a mano.
+
+use crate::builder::HodeiAuthService;
+use async_trait::async_trait;
+use cedar_policy::{Context, Entities, Entity, EntityUid, Request};
+use hodei_authz::{
+    AuditEvent, AuthorizationDecision, AuthorizationError, AuthorizationEvaluator, EntityResolver,
+    HodeiContext, RuntimeHodeiActionMapper, RuntimeHodeiEntityMapper,
+};
+use hodei_hrn::Hrn;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+impl HodeiAuthService {
+    /// Evalúa si `principal` puede ejecutar `action` sobre `resource`
+    ///
+    /// La petición y las entidades se validan contra el schema del servicio.
+    /// Con aislamiento por tenant se usan las políticas del tenant del recurso
+    /// y las peticiones entre tenants se deniegan salvo permiso explícito.
+    /// Con un `EntityProvider` se incluyen también las entidades relacionadas.
+    /// Con un sink de auditoría configurado la decisión se encola para
+    /// registrarla; las peticiones que fallan antes de evaluarse no se auditan.
+    ///
+    /// # Ejemplo
+    ///
+    /// ```rust,ignore
+    /// let decision = auth
+    ///     .is_authorized(&user, &DocumentCommand::Read { id }, &document, Context::empty())
+    ///     .await?;
+    ///
+    /// if decision.is_allowed() {
+    ///     // ...
+    /// }
+    /// ```
+    pub async fn is_authorized(
+        &self,
+        principal: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        action: &(impl RuntimeHodeiActionMapper + ?Sized),
+        resource: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        context: Context,
+    ) -> Result<AuthorizationDecision, AuthorizationError> {
+        let started = Instant::now();
+        let audit_context = self.audit.is_some().then(|| context.clone());
+        let request = Request::new(
+            principal.to_cedar_euid(),
+            action.to_cedar_action_euid(),
+            resource.to_cedar_euid(),
+            context,
+            Some(&self.schema),
+        )
+        .map_err(|e| AuthorizationError::Request(e.to_string()))?;
+
+        let entities = self.build_entities(
+            self.with_related(vec![principal.to_cedar_entity(), resource.to_cedar_entity()])
+                .await?,
+        )?;
+
+        let principal_tenant = &principal.hodei_hrn().tenant_id;
+        let resource_tenant = &resource.hodei_hrn().tenant_id;
+        let policy_set = self.policy_set_for(resource_tenant).await?;
+        let response = self.authorizer.is_authorized(&request, &policy_set, &entities);
+
+        let decision = self.enforce_tenant_boundary(
+            principal_tenant,
+            resource_tenant,
+            &policy_set,
+            AuthorizationDecision::from(&response),
+        );
+
+        self.record_decision(
+            principal.hodei_hrn(),
+            &action.to_cedar_action_euid(),
+            resource.hodei_hrn(),
+            audit_context,
+            &decision,
+            started.elapsed(),
+        );
+
+        Ok(decision)
+    }
+
+    /// Evalúa la petición con un contexto tipado (`#[derive(HodeiContext)]`)
+    ///
+    /// El contexto se valida contra el `context` declarado por la acción en el schema.
+    ///
+    /// # Ejemplo
+    ///
+    /// ```rust,ignore
+    /// let context = RequestContext { source_ip: addr.ip(), mfa: true };
+    /// let decision = auth
+    ///     .is_authorized_with_context(&user, &DocumentCommand::Read { id }, &document, &context)
+    ///     .await?;
+    /// ```
+    pub async fn is_authorized_with_context(
+        &self,
+        principal: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        action: &(impl RuntimeHodeiActionMapper + ?Sized),
+        resource: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        context: &impl HodeiContext,
+    ) -> Result<AuthorizationDecision, AuthorizationError> {
+        self.is_authorized(principal, action, resource, context.to_cedar_context()?)
+            .await
+    }
+
+    /// Encola la decisión en el sink de auditoría, si hay uno y entra en la
+    /// muestra; `context` es `None` cuando no hay auditoría
+    pub(crate) fn record_decision(
+        &self,
+        principal: &Hrn,
+        action: &EntityUid,
+        resource: &Hrn,
+        context: Option<Context>,
+        decision: &AuthorizationDecision,
+        latency: Duration,
+    ) {
+        if let (Some(audit), Some(context)) = (&self.audit, context)
+            && audit.should_record(decision.is_allowed())
+        {
+            audit.log(AuditEvent::new(
+                principal.clone(),
+                action.to_string(),
+                resource.clone(),
+                context,
+                decision,
+                latency,
+            ));
+        }
+    }
+
+    /// Añade a `roots` las entidades que referencian (atributos y padres),
+    /// cargadas con el `EntityProvider` configurado
+    ///
+    /// Cada llamada usa su propio `EntityResolver`, así que una entidad se
+    /// pide como mucho una vez por petición o lote.
+    pub(crate) async fn with_related(
+        &self,
+        roots: Vec<Entity>,
+    ) -> Result<Vec<Entity>, AuthorizationError> {
+        match &self.entity_provider {
+            Some(provider) => EntityResolver::new(provider.as_ref(), self.entity_depth)
+                .resolve(roots)
+                .await
+                .map_err(|e| AuthorizationError::Entities(e.to_string())),
+            None => Ok(roots),
+        }
+    }
+
+    /// Construye el conjunto de entidades validado contra el schema
+    ///
+    /// Las entidades repetidas (p.ej. cuando principal y resource son el mismo
+    /// usuario) se incluyen una sola vez.
+    pub(crate) fn build_entities(
+        &self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> Result<Entities, AuthorizationError> {
+        let mut seen = HashSet::new();
+        let unique = entities.into_iter().filter(|e| seen.insert(e.uid()));
+
+        Entities::from_entities(unique, Some(&self.schema))
+            .map_err(|e| AuthorizationError::Entities(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl AuthorizationEvaluator for HodeiAuthService {
+    async fn evaluate(
+        &self,
+        principal: &(dyn RuntimeHodeiEntityMapper + Sync),
+        action: &(dyn RuntimeHodeiActionMapper + Sync),
+        resource: &(dyn RuntimeHodeiEntityMapper + Sync),
+        context: Context,
+    ) -> Result<AuthorizationDecision, AuthorizationError> {
+        self.is_authorized(principal, action, resource, context).await
+    }
+}
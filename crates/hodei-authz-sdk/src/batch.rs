@@ -0,0 +1,278 @@
+//! Evaluación de varias peticiones de un mismo principal
+//!
+//! Pensado para listados con acciones por fila: las entidades se construyen
+//! una sola vez y el conjunto de políticas de cada tenant se obtiene una vez
+//! por lote en lugar de una vez por petición.
+
+use crate::builder::HodeiAuthService;
+use cedar_policy::{Authorizer, Context, Entities, EntityUid, PolicySet, Request};
+use hodei_authz::{
+    AuthorizationDecision, AuthorizationError, RuntimeHodeiActionMapper, RuntimeHodeiEntityMapper,
+};
+use std::collections::HashMap;
+use std::iter;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Acción y recurso de una petición del lote
+pub type BatchItem<'a> = (
+    &'a (dyn RuntimeHodeiActionMapper + Sync),
+    &'a (dyn RuntimeHodeiEntityMapper + Sync),
+);
+
+/// Cómo evaluar las peticiones de un lote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchMode {
+    /// Una tras otra en la tarea actual
+    #[default]
+    Sequential,
+    /// Repartidas entre hilos bloqueantes de tokio; compensa con lotes
+    /// grandes o políticas costosas
+    Parallel,
+}
+
+/// Resultado de cada petición, en el orden del lote
+pub type BatchDecisions = Vec<Result<AuthorizationDecision, AuthorizationError>>;
+
+impl HodeiAuthService {
+    /// Evalúa varias peticiones de `principal` con el mismo `context`
+    ///
+    /// Devuelve una decisión (o error) por elemento, en el mismo orden. Falla
+    /// entero solo si las entidades no son válidas. Cada decisión se audita
+    /// como en `is_authorized`, con la latencia del lote completo.
+    ///
+    /// # Ejemplo
+    ///
+    /// ```rust,ignore
+    /// let read = DocumentCommand::Read { id };
+    /// let delete = DocumentCommand::Delete { id };
+    /// let mut items: Vec<BatchItem> = Vec::new();
+    /// for document in &documents {
+    ///     items.push((&read, document));
+    ///     items.push((&delete, document));
+    /// }
+    ///
+    /// let decisions = auth
+    ///     .is_authorized_batch(&user, &items, Context::empty(), BatchMode::Sequential)
+    ///     .await?;
+    /// ```
+    pub async fn is_authorized_batch(
+        &self,
+        principal: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        items: &[BatchItem<'_>],
+        context: Context,
+        mode: BatchMode,
+    ) -> Result<BatchDecisions, AuthorizationError> {
+        let items = items
+            .iter()
+            .map(|(action, resource)| (action.to_cedar_action_euid(), *resource))
+            .collect();
+        self.evaluate_batch(principal, items, context, mode).await
+    }
+
+    /// Acciones del schema que `principal` puede ejecutar sobre `resource`
+    ///
+    /// Se evalúan con contexto vacío, así que se omiten las acciones que no
+    /// aplican a estos tipos de entidad y las que exigen atributos de contexto.
+    ///
+    /// # Ejemplo
+    ///
+    /// ```rust,ignore
+    /// let actions = auth.permitted_actions(&user, &document).await?;
+    /// let can_delete = actions.contains(&DocumentCommand::Delete { id }.to_cedar_action_euid());
+    /// ```
+    pub async fn permitted_actions(
+        &self,
+        principal: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        resource: &(dyn RuntimeHodeiEntityMapper + Sync),
+    ) -> Result<Vec<EntityUid>, AuthorizationError> {
+        let actions: Vec<EntityUid> = self.schema.actions().cloned().collect();
+        let items = actions.iter().map(|action| (action.clone(), resource)).collect();
+        let decisions = self
+            .evaluate_batch(principal, items, Context::empty(), BatchMode::Sequential)
+            .await?;
+
+        Ok(actions
+            .into_iter()
+            .zip(decisions)
+            .filter(|(_, decision)| decision.as_ref().is_ok_and(|d| d.is_allowed()))
+            .map(|(action, _)| action)
+            .collect())
+    }
+
+    async fn evaluate_batch(
+        &self,
+        principal: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        items: Vec<(EntityUid, &(dyn RuntimeHodeiEntityMapper + Sync))>,
+        context: Context,
+        mode: BatchMode,
+    ) -> Result<BatchDecisions, AuthorizationError> {
+        let started = Instant::now();
+        let roots = iter::once(principal.to_cedar_entity())
+            .chain(items.iter().map(|(_, resource)| resource.to_cedar_entity()))
+            .collect();
+        let entities = Arc::new(self.build_entities(self.with_related(roots).await?)?);
+
+        // Un acceso al conjunto de políticas por tenant, no por petición
+        let mut policy_sets: HashMap<&str, Result<Arc<PolicySet>, String>> = HashMap::new();
+        for (_, resource) in &items {
+            let tenant_id = resource.hodei_hrn().tenant_id.as_str();
+            if !policy_sets.contains_key(tenant_id) {
+                let policy_set = self.policy_set_for(tenant_id).await.map_err(|e| e.to_string());
+                policy_sets.insert(tenant_id, policy_set);
+            }
+        }
+
+        let mut results: Vec<Option<Result<AuthorizationDecision, AuthorizationError>>> =
+            Vec::with_capacity(items.len());
+        let mut jobs = Vec::new();
+        for (index, (action, resource)) in items.iter().enumerate() {
+            let policy_set = match &policy_sets[resource.hodei_hrn().tenant_id.as_str()] {
+                Ok(policy_set) => policy_set.clone(),
+                Err(e) => {
+                    results.push(Some(Err(AuthorizationError::Policies(e.clone()))));
+                    continue;
+                }
+            };
+            match Request::new(
+                principal.to_cedar_euid(),
+                action.clone(),
+                resource.to_cedar_euid(),
+                context.clone(),
+                Some(&self.schema),
+            ) {
+                Ok(request) => {
+                    jobs.push((index, request, policy_set));
+                    results.push(None);
+                }
+                Err(e) => results.push(Some(Err(AuthorizationError::Request(e.to_string())))),
+            }
+        }
+
+        let evaluated = match mode {
+            BatchMode::Sequential => evaluate(&self.authorizer, jobs, &entities),
+            BatchMode::Parallel => evaluate_parallel(jobs, entities).await,
+        };
+
+        let principal_tenant = &principal.hodei_hrn().tenant_id;
+        for (index, policy_set, decision) in evaluated {
+            let (action, resource) = &items[index];
+            let decision = self.enforce_tenant_boundary(
+                principal_tenant,
+                &resource.hodei_hrn().tenant_id,
+                &policy_set,
+                decision,
+            );
+            self.record_decision(
+                principal.hodei_hrn(),
+                action,
+                resource.hodei_hrn(),
+                self.audit.is_some().then(|| context.clone()),
+                &decision,
+                started.elapsed(),
+            );
+            results[index] = Some(Ok(decision));
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+}
+
+type Job = (usize, Request, Arc<PolicySet>);
+type Evaluated = (usize, Arc<PolicySet>, AuthorizationDecision);
+
+fn evaluate(authorizer: &Authorizer, jobs: Vec<Job>, entities: &Entities) -> Vec<Evaluated> {
+    jobs.into_iter()
+        .map(|(index, request, policy_set)| {
+            let response = authorizer.is_authorized(&request, &policy_set, entities);
+            (index, policy_set, AuthorizationDecision::from(&response))
+        })
+        .collect()
+}
+
+/// Reparte los trabajos en un bloque por hilo disponible
+async fn evaluate_parallel(jobs: Vec<Job>, entities: Arc<Entities>) -> Vec<Evaluated> {
+    let workers = std::thread::available_parallelism().map_or(1, usize::from);
+    let chunk_size = jobs.len().div_ceil(workers).max(1);
+
+    let mut jobs = jobs.into_iter().peekable();
+    let mut handles = Vec::new();
+    while jobs.peek().is_some() {
+        let chunk: Vec<Job> = jobs.by_ref().take(chunk_size).collect();
+        let entities = entities.clone();
+        handles.push(tokio::task::spawn_blocking(move || {
+            evaluate(&Authorizer::new(), chunk, &entities)
+        }));
+    }
+
+    let mut evaluated = Vec::new();
+    for handle in handles {
+        match handle.await {
+            Ok(chunk) => evaluated.extend(chunk),
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+    evaluated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{doc, Read};
+    use cedar_policy::Schema;
+    use hodei_authz::InMemoryPolicyStore;
+
+    async fn service() -> HodeiAuthService {
+        let store = InMemoryPolicyStore::new();
+        store
+            .insert(
+                "self".to_string(),
+                r#"permit(principal, action == Action::"Read", resource) when { resource == principal };"#
+                    .to_string(),
+            )
+            .unwrap();
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity Doc; action Read, Write appliesTo { principal: Doc, resource: Doc };",
+        )
+        .unwrap();
+
+        HodeiAuthService::builder()
+            .with_policy_store(store)
+            .with_schema(schema)
+            .build()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_decisions_keep_order() {
+        let auth = service().await;
+        let alice = doc("acme", "alice");
+        let bob = doc("acme", "bob");
+        let items: [BatchItem; 3] = [(&Read, &alice), (&Read, &bob), (&Read, &alice)];
+
+        for mode in [BatchMode::Sequential, BatchMode::Parallel] {
+            let decisions = auth
+                .is_authorized_batch(&alice, &items, Context::empty(), mode)
+                .await
+                .unwrap();
+            let allowed: Vec<bool> = decisions
+                .iter()
+                .map(|decision| decision.as_ref().unwrap().is_allowed())
+                .collect();
+            assert_eq!(allowed, vec![true, false, true]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permitted_actions() {
+        let auth = service().await;
+        let alice = doc("acme", "alice");
+
+        let actions = auth.permitted_actions(&alice, &alice).await.unwrap();
+        assert_eq!(actions, vec![Read.to_cedar_action_euid()]);
+
+        let actions = auth.permitted_actions(&alice, &doc("acme", "bob")).await.unwrap();
+        assert!(actions.is_empty());
+    }
+}
@@ -1,8 +1,14 @@
 //! Builder pattern para configurar HodeiAuthService fácilmente
 
+use crate::audit::{AuditConfig, AuditLogger};
 use crate::schema::{auto_discover_schema, SchemaError};
+use crate::tenant::TenantPolicySets;
+use async_trait::async_trait;
 use cedar_policy::{Authorizer, PolicySet, Schema};
-use hodei_authz::{CacheInvalidation, PolicyStore};
+use hodei_authz::{
+    AuditSink, CacheError, CacheInvalidation, EntityProvider, NoopCacheInvalidation,
+    PolicyLoadReport, PolicyStore, RejectedPolicy,
+};
 
 #[cfg(feature = "postgres")]
 use hodei_authz_postgres::PostgresPolicyStore;
@@ -12,17 +18,15 @@ use sqlx::PgPool;
 #[cfg(feature = "redis")]
 use hodei_authz_redis::RedisCacheInvalidation;
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 /// Error al construir el servicio
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
-    #[error("PostgreSQL pool is required")]
-    MissingPostgres,
-    
-    #[error("Redis URL is required")]
-    MissingRedis,
+    #[error("A policy store is required. Call with_policy_store() or with_postgres()")]
+    MissingPolicyStore,
     
     #[error("Schema error: {0}")]
     Schema(#[from] SchemaError),
@@ -37,24 +41,75 @@ pub enum BuildError {
     Migration(String),
 }
 
+/// Cómo tratar las políticas que no se pueden cargar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PolicyLoadMode {
+    /// Una política inválida hace fallar la carga completa (y `build()`)
+    #[default]
+    Strict,
+    /// Las políticas inválidas se omiten, se registran con `tracing` y quedan
+    /// disponibles en `HodeiAuthService::rejected_policies`
+    Lenient,
+}
+
+/// Versión object-safe de `CacheInvalidation`
+///
+/// `subscribe_to_invalidations` es genérico, así que el servicio guarda la
+/// invalidación detrás de este trait para poder aceptar cualquier implementación.
+#[async_trait]
+trait DynCacheInvalidation: Send + Sync {
+    async fn invalidate(&self) -> Result<(), CacheError>;
+
+    async fn subscribe(&self, callback: Box<dyn Fn() + Send + Sync>) -> Result<(), CacheError>;
+}
+
+#[async_trait]
+impl<C: CacheInvalidation> DynCacheInvalidation for C {
+    async fn invalidate(&self) -> Result<(), CacheError> {
+        self.invalidate_policies().await
+    }
+
+    async fn subscribe(&self, callback: Box<dyn Fn() + Send + Sync>) -> Result<(), CacheError> {
+        self.subscribe_to_invalidations(callback).await
+    }
+}
+
+/// Saltos por defecto al cargar entidades relacionadas
+const DEFAULT_ENTITY_DEPTH: usize = 2;
+
 /// Servicio de autorización completo
 pub struct HodeiAuthService {
-    #[cfg(feature = "postgres")]
-    pub(crate) policy_store: Arc<PostgresPolicyStore>,
-    #[cfg(feature = "redis")]
-    pub(crate) cache_invalidation: Arc<RedisCacheInvalidation>,
+    pub(crate) policy_store: Arc<dyn PolicyStore>,
+    cache_invalidation: Arc<dyn DynCacheInvalidation>,
     pub(crate) authorizer: Authorizer,
     pub(crate) schema: Arc<Schema>,
-    pub(crate) policy_set: Arc<RwLock<PolicySet>>,
+    pub(crate) policy_set: Arc<RwLock<Arc<PolicySet>>>,
+    pub(crate) load_mode: PolicyLoadMode,
+    rejected_policies: Arc<RwLock<Vec<RejectedPolicy>>>,
+    pub(crate) rejected_count: Arc<AtomicU64>,
+    reload_lock: Arc<Mutex<()>>,
+    pub(crate) tenant_isolation: bool,
+    pub(crate) tenant_policy_sets: Arc<TenantPolicySets>,
+    pub(crate) audit: Option<AuditLogger>,
+    pub(crate) entity_provider: Option<Arc<dyn EntityProvider>>,
+    pub(crate) entity_depth: usize,
 }
 
 /// Builder para HodeiAuthService
 pub struct HodeiAuthServiceBuilder {
+    policy_store: Option<Arc<dyn PolicyStore>>,
+    cache_invalidation: Option<Arc<dyn DynCacheInvalidation>>,
     #[cfg(feature = "postgres")]
     postgres_pool: Option<PgPool>,
     #[cfg(feature = "redis")]
     redis_url: Option<String>,
     schema: Option<Schema>,
+    load_mode: PolicyLoadMode,
+    tenant_isolation: bool,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    audit_config: AuditConfig,
+    entity_provider: Option<Arc<dyn EntityProvider>>,
+    entity_depth: usize,
     #[cfg(feature = "postgres")]
     auto_migrate: bool,
 }
@@ -69,16 +124,46 @@ impl HodeiAuthServiceBuilder {
     /// Crea un nuevo builder
     pub fn new() -> Self {
         Self {
+            policy_store: None,
+            cache_invalidation: None,
             #[cfg(feature = "postgres")]
             postgres_pool: None,
             #[cfg(feature = "redis")]
             redis_url: None,
             schema: None,
+            load_mode: PolicyLoadMode::Strict,
+            tenant_isolation: false,
+            audit_sink: None,
+            audit_config: AuditConfig::default(),
+            entity_provider: None,
+            entity_depth: DEFAULT_ENTITY_DEPTH,
             #[cfg(feature = "postgres")]
             auto_migrate: true,
         }
     }
     
+    /// Usa cualquier implementación de `PolicyStore`
+    ///
+    /// Tiene prioridad sobre `with_postgres`. Para conservar un handle al
+    /// store, pasa un `Arc` y quédate con un clon. Para validar las escrituras,
+    /// configura el store con el mismo schema (`with_schema`).
+    pub fn with_policy_store(mut self, store: impl PolicyStore + 'static) -> Self {
+        self.policy_store = Some(Arc::new(store));
+        self
+    }
+    
+    /// Usa cualquier implementación de `CacheInvalidation`
+    ///
+    /// Tiene prioridad sobre `with_redis`. Si no se configura ninguna se usa
+    /// `NoopCacheInvalidation`, válido para despliegues de una sola instancia.
+    pub fn with_cache_invalidation(
+        mut self,
+        cache_invalidation: impl CacheInvalidation + 'static,
+    ) -> Self {
+        self.cache_invalidation = Some(Arc::new(cache_invalidation));
+        self
+    }
+    
     /// Configura el pool de PostgreSQL
     #[cfg(feature = "postgres")]
     pub fn with_postgres(mut self, pool: PgPool) -> Self {
@@ -108,6 +193,60 @@ impl HodeiAuthServiceBuilder {
         self
     }
     
+    /// Configura cómo se tratan las políticas inválidas al cargar y recargar
+    ///
+    /// Por defecto `PolicyLoadMode::Strict`: una política rota impide arrancar y
+    /// las recargas fallidas conservan el conjunto anterior. Con
+    /// `PolicyLoadMode::Lenient` se cargan las válidas y se informa del resto.
+    pub fn with_policy_load_mode(mut self, mode: PolicyLoadMode) -> Self {
+        self.load_mode = mode;
+        self
+    }
+    
+    /// Aísla las políticas de cada tenant
+    ///
+    /// Cada petición se evalúa con las políticas globales más las del tenant
+    /// del recurso (`Hrn::tenant_id`), cargadas la primera vez que se usan.
+    /// Las peticiones en las que principal y resource son de tenants distintos
+    /// se deniegan salvo que las permita una política con la anotación
+    /// `@cross_tenant` (ver [`CROSS_TENANT_ANNOTATION`](crate::tenant::CROSS_TENANT_ANNOTATION)).
+    pub fn with_tenant_isolation(mut self) -> Self {
+        self.tenant_isolation = true;
+        self
+    }
+    
+    /// Registra cada decisión en `sink`
+    ///
+    /// Los eventos se entregan por lotes desde una tarea en segundo plano
+    /// según [`with_audit_config`](Self::with_audit_config).
+    pub fn with_audit_sink(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.audit_sink = Some(Arc::new(sink));
+        self
+    }
+    
+    /// Configura el muestreo y los lotes de auditoría
+    pub fn with_audit_config(mut self, config: AuditConfig) -> Self {
+        self.audit_config = config;
+        self
+    }
+    
+    /// Carga las entidades relacionadas con cada petición desde `provider`
+    ///
+    /// Además del principal y el recurso, la petición incluye las entidades a
+    /// las que referencian sus atributos y sus padres, hasta la profundidad de
+    /// [`with_entity_depth`](Self::with_entity_depth). Así políticas como
+    /// `resource.owner.department == principal.department` tienen al `owner`.
+    pub fn with_entity_provider(mut self, provider: impl EntityProvider + 'static) -> Self {
+        self.entity_provider = Some(Arc::new(provider));
+        self
+    }
+    
+    /// Saltos que se siguen desde el principal y el recurso (por defecto 2)
+    pub fn with_entity_depth(mut self, depth: usize) -> Self {
+        self.entity_depth = depth;
+        self
+    }
+    
     /// Deshabilita las migraciones automáticas
     #[cfg(feature = "postgres")]
     pub fn without_auto_migrate(mut self) -> Self {
@@ -116,61 +255,210 @@ impl HodeiAuthServiceBuilder {
     }
     
     /// Construye el servicio
-    #[cfg(all(feature = "postgres", feature = "redis"))]
-    pub async fn build(self) -> Result<HodeiAuthService, BuildError> {
-        // Validar configuración
-        let pool = self.postgres_pool.ok_or(BuildError::MissingPostgres)?;
-        let redis_url = self.redis_url.ok_or(BuildError::MissingRedis)?;
-        let schema = self.schema.ok_or_else(|| {
+    ///
+    /// Carga las políticas iniciales y se suscribe a la invalidación de caché
+    /// para recargarlas cuando cambien.
+    pub async fn build(mut self) -> Result<HodeiAuthService, BuildError> {
+        let schema = self.schema.take().ok_or_else(|| {
             SchemaError::InvalidStructure(
                 "Schema is required. Call auto_discover_schema() or with_schema()".to_string()
             )
         })?;
         
-        // Setup policy store
-        let policy_store = PostgresPolicyStore::new(pool);
-        
-        if self.auto_migrate {
-            policy_store
-                .migrate()
-                .await
-                .map_err(|e| BuildError::Migration(e.to_string()))?;
-            tracing::info!("✅ Database migrations completed");
-        }
-        
-        // Setup cache invalidation
-        let cache_invalidation = RedisCacheInvalidation::new(&redis_url)
-            .await
-            .map_err(|e| BuildError::Cache(e.to_string()))?;
-        tracing::info!("✅ Redis cache connected");
+        let policy_store = self.resolve_policy_store(&schema).await?;
+        let cache_invalidation = self.resolve_cache_invalidation().await?;
         
         // Load policies
-        let policy_set = Self::load_initial_policies(&policy_store).await?;
+        let load_mode = self.load_mode;
+        let rejected_count = Arc::new(AtomicU64::new(0));
+        let report = load_policies(policy_store.as_ref(), load_mode, &rejected_count).await?;
         tracing::info!("✅ Policies loaded");
+        let policy_set = Arc::new(RwLock::new(Arc::new(report.policy_set)));
+        let rejected_policies = Arc::new(RwLock::new(report.rejected));
+        let tenant_policy_sets = Arc::new(TenantPolicySets::new());
+        let reload_lock = Arc::new(Mutex::new(()));
         
-        let authorizer = Authorizer::new();
+        // Recargar las políticas en cada invalidación. El callback puede
+        // ejecutarse fuera del runtime (p.ej. en el hilo de un watcher).
+        let handle = tokio::runtime::Handle::current();
+        let store = policy_store.clone();
+        let shared_policy_set = policy_set.clone();
+        let shared_rejected = rejected_policies.clone();
+        let shared_tenant_sets = tenant_policy_sets.clone();
+        let shared_reload_lock = reload_lock.clone();
+        let shared_rejected_count = rejected_count.clone();
+        cache_invalidation
+            .subscribe(Box::new(move || {
+                let store = store.clone();
+                let policy_set = shared_policy_set.clone();
+                let rejected = shared_rejected.clone();
+                let tenant_sets = shared_tenant_sets.clone();
+                let reload_lock = shared_reload_lock.clone();
+                let rejected_count = shared_rejected_count.clone();
+                handle.spawn(async move {
+                    let swapped = swap_policies(
+                        store.as_ref(),
+                        load_mode,
+                        &reload_lock,
+                        &policy_set,
+                        &rejected,
+                        &rejected_count,
+                        &tenant_sets,
+                    )
+                    .await;
+                    match swapped {
+                        Ok(()) => tracing::info!("🔄 Policies reloaded after invalidation"),
+                        Err(e) => tracing::error!("Failed to reload policies: {}", e),
+                    }
+                });
+            }))
+            .await
+            .map_err(|e| BuildError::Cache(e.to_string()))?;
         
         Ok(HodeiAuthService {
-            policy_store: Arc::new(policy_store),
-            cache_invalidation: Arc::new(cache_invalidation),
-            authorizer,
+            policy_store,
+            cache_invalidation,
+            authorizer: Authorizer::new(),
             schema: Arc::new(schema),
-            policy_set: Arc::new(RwLock::new(policy_set)),
+            policy_set,
+            load_mode,
+            rejected_policies,
+            rejected_count,
+            reload_lock,
+            tenant_isolation: self.tenant_isolation,
+            tenant_policy_sets,
+            audit: self
+                .audit_sink
+                .take()
+                .map(|sink| AuditLogger::spawn(sink, self.audit_config.clone())),
+            entity_provider: self.entity_provider.take(),
+            entity_depth: self.entity_depth,
         })
     }
     
-    /// Carga las políticas iniciales
-    #[cfg(feature = "postgres")]
-    async fn load_initial_policies(
-        store: &PostgresPolicyStore,
-    ) -> Result<PolicySet, BuildError> {
-        store
-            .load_all_policies()
+    /// Devuelve el store configurado o, en su defecto, el de PostgreSQL
+    ///
+    /// El store de PostgreSQL valida las escrituras contra el schema del servicio.
+    #[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+    async fn resolve_policy_store(
+        &mut self,
+        schema: &Schema,
+    ) -> Result<Arc<dyn PolicyStore>, BuildError> {
+        if let Some(store) = self.policy_store.take() {
+            return Ok(store);
+        }
+        
+        #[cfg(feature = "postgres")]
+        if let Some(pool) = self.postgres_pool.take() {
+            let policy_store = PostgresPolicyStore::new(pool).with_schema(schema.clone());
+            
+            if self.auto_migrate {
+                policy_store
+                    .migrate()
+                    .await
+                    .map_err(|e| BuildError::Migration(e.to_string()))?;
+                tracing::info!("✅ Database migrations completed");
+            }
+            
+            return Ok(Arc::new(policy_store));
+        }
+        
+        Err(BuildError::MissingPolicyStore)
+    }
+    
+    /// Devuelve la invalidación configurada, la de Redis o una no-op
+    async fn resolve_cache_invalidation(
+        &mut self,
+    ) -> Result<Arc<dyn DynCacheInvalidation>, BuildError> {
+        if let Some(cache_invalidation) = self.cache_invalidation.take() {
+            return Ok(cache_invalidation);
+        }
+        
+        #[cfg(feature = "redis")]
+        if let Some(redis_url) = self.redis_url.take() {
+            let cache_invalidation = RedisCacheInvalidation::new(&redis_url)
+                .await
+                .map_err(|e| BuildError::Cache(e.to_string()))?;
+            tracing::info!("✅ Redis cache connected");
+            
+            return Ok(Arc::new(cache_invalidation));
+        }
+        
+        Ok(Arc::new(NoopCacheInvalidation))
+    }
+}
+
+/// Carga todas las políticas del store según el modo configurado
+async fn load_policies(
+    store: &dyn PolicyStore,
+    mode: PolicyLoadMode,
+    rejected_count: &AtomicU64,
+) -> Result<PolicyLoadReport, BuildError> {
+    let report = match mode {
+        PolicyLoadMode::Strict => PolicyLoadReport {
+            policy_set: store
+                .load_all_policies()
+                .await
+                .map_err(|e| BuildError::PolicyStore(e.to_string()))?,
+            rejected: Vec::new(),
+        },
+        PolicyLoadMode::Lenient => store
+            .load_policies_lenient()
             .await
-            .map_err(|e| BuildError::PolicyStore(e.to_string()))
+            .map_err(|e| BuildError::PolicyStore(e.to_string()))?,
+    };
+    record_rejected(&report, rejected_count);
+    
+    Ok(report)
+}
+
+/// Registra con `tracing` las políticas omitidas en una carga y las suma a
+/// `rejected_count`
+pub(crate) fn record_rejected(report: &PolicyLoadReport, rejected_count: &AtomicU64) {
+    rejected_count.fetch_add(report.rejected.len() as u64, Ordering::Relaxed);
+    for rejected in &report.rejected {
+        tracing::warn!(
+            policy_id = %rejected.policy_id,
+            reason = %rejected.reason,
+            "⚠️ Skipping invalid policy"
+        );
+    }
+    if !report.is_complete() {
+        tracing::warn!(
+            loaded = report.policy_set.policies().count(),
+            rejected = report.rejected.len(),
+            "⚠️ Policy set loaded with rejected policies"
+        );
     }
 }
 
+/// Sustituye el `PolicySet` activo por el contenido actual del store
+///
+/// El nuevo conjunto se carga antes de tomar el lock de escritura, así las
+/// evaluaciones en curso nunca ven un estado intermedio. Si la carga falla se
+/// conserva el conjunto anterior. Los conjuntos por tenant se descartan y se
+/// vuelven a cargar cuando se necesiten.
+///
+/// Las recargas se serializan con `reload_lock`: si dos invalidaciones llegan
+/// seguidas, la segunda lee el store después de que la primera haya publicado
+/// su conjunto, así una carga lenta y antigua nunca pisa a una más reciente.
+async fn swap_policies(
+    store: &dyn PolicyStore,
+    mode: PolicyLoadMode,
+    reload_lock: &Mutex<()>,
+    policy_set: &RwLock<Arc<PolicySet>>,
+    rejected_policies: &RwLock<Vec<RejectedPolicy>>,
+    rejected_count: &AtomicU64,
+    tenant_policy_sets: &TenantPolicySets,
+) -> Result<(), BuildError> {
+    let _reload = reload_lock.lock().await;
+    let report = load_policies(store, mode, rejected_count).await?;
+    *policy_set.write().await = Arc::new(report.policy_set);
+    *rejected_policies.write().await = report.rejected;
+    tenant_policy_sets.clear().await;
+    Ok(())
+}
+
 impl HodeiAuthService {
     /// Crea un nuevo builder
     pub fn builder() -> HodeiAuthServiceBuilder {
@@ -182,27 +470,245 @@ impl HodeiAuthService {
         &self.schema
     }
     
+    /// Obtiene el store de políticas
+    pub fn policy_store(&self) -> &dyn PolicyStore {
+        self.policy_store.as_ref()
+    }
+    
     /// Recarga las políticas
-    #[cfg(feature = "postgres")]
     pub async fn reload_policies(&self) -> Result<(), BuildError> {
-        let new_policy_set = self
-            .policy_store
-            .load_all_policies()
-            .await
-            .map_err(|e| BuildError::PolicyStore(e.to_string()))?;
-        
-        let mut policy_set = self.policy_set.write().await;
-        *policy_set = new_policy_set;
-        
-        Ok(())
+        swap_policies(
+            self.policy_store.as_ref(),
+            self.load_mode,
+            &self.reload_lock,
+            &self.policy_set,
+            &self.rejected_policies,
+            &self.rejected_count,
+            &self.tenant_policy_sets,
+        )
+        .await
+    }
+    
+    /// Políticas omitidas en la última carga
+    ///
+    /// Siempre vacío con `PolicyLoadMode::Strict`.
+    pub async fn rejected_policies(&self) -> Vec<RejectedPolicy> {
+        self.rejected_policies.read().await.clone()
+    }
+    
+    /// Total de políticas omitidas desde que se creó el servicio
+    ///
+    /// Contador acumulado de todas las cargas, globales y por tenant: una
+    /// política rota cuenta una vez por cada recarga que la omite. Pensado para
+    /// exportarse como métrica; siempre 0 con `PolicyLoadMode::Strict`.
+    pub fn rejected_policy_count(&self) -> u64 {
+        self.rejected_count.load(Ordering::Relaxed)
+    }
+    
+    /// Espera a que las decisiones auditadas hasta ahora lleguen al sink
+    ///
+    /// Útil antes de apagar el servicio. Sin sink configurado no hace nada.
+    pub async fn flush_audit(&self) {
+        if let Some(audit) = &self.audit {
+            audit.flush().await;
+        }
+    }
+    
+    /// Eventos de auditoría descartados por tener la cola llena
+    pub fn dropped_audit_events(&self) -> u64 {
+        self.audit.as_ref().map_or(0, AuditLogger::dropped)
     }
     
     /// Invalida el caché
-    #[cfg(feature = "redis")]
+    ///
+    /// Con `NoopCacheInvalidation` no hay nada que notificar; usa
+    /// `reload_policies` para refrescar esta instancia.
     pub async fn invalidate_cache(&self) -> Result<(), BuildError> {
         self.cache_invalidation
-            .invalidate_policies()
+            .invalidate()
             .await
             .map_err(|e| BuildError::Cache(e.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hodei_authz::{InMemoryPolicyStore, PolicyStoreError};
+    use std::time::Duration;
+    
+    const PERMIT_ALL: &str = "permit(principal, action, resource);";
+    
+    fn empty_schema() -> Schema {
+        Schema::from_json_str("{}").unwrap()
+    }
+    
+    /// Invalidación local que notifica al suscriptor de forma síncrona
+    #[derive(Default)]
+    struct LocalInvalidation {
+        callback: std::sync::Mutex<Option<Box<dyn Fn() + Send + Sync>>>,
+    }
+    
+    #[async_trait]
+    impl CacheInvalidation for LocalInvalidation {
+        async fn invalidate_policies(&self) -> Result<(), CacheError> {
+            if let Some(callback) = self.callback.lock().unwrap().as_ref() {
+                callback();
+            }
+            Ok(())
+        }
+        
+        async fn subscribe_to_invalidations<F>(&self, callback: F) -> Result<(), CacheError>
+        where
+            F: Fn() + Send + Sync + 'static,
+        {
+            *self.callback.lock().unwrap() = Some(Box::new(callback));
+            Ok(())
+        }
+    }
+    
+    /// Espera a que el conjunto activo tenga `count` políticas
+    async fn wait_for_policies(auth: &HodeiAuthService, count: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while auth.policy_set.read().await.policies().count() != count {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("policies were not reloaded in time");
+    }
+    
+    #[tokio::test]
+    async fn test_build_requires_policy_store() {
+        let result = HodeiAuthService::builder()
+            .with_schema(empty_schema())
+            .build()
+            .await;
+        
+        assert!(matches!(result, Err(BuildError::MissingPolicyStore)));
+    }
+    
+    #[tokio::test]
+    async fn test_build_with_in_memory_store() {
+        let store = InMemoryPolicyStore::new();
+        store.insert("public".to_string(), PERMIT_ALL.to_string()).unwrap();
+        
+        let auth = HodeiAuthService::builder()
+            .with_policy_store(store)
+            .with_schema(empty_schema())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(auth.policy_set.read().await.policies().count(), 1);
+        
+        // Sin invalidación configurada, la recarga es explícita
+        auth.policy_store()
+            .create_policy("forbid(principal, action, resource);".to_string())
+            .await
+            .unwrap();
+        auth.invalidate_cache().await.unwrap();
+        assert_eq!(auth.policy_set.read().await.policies().count(), 1);
+        
+        auth.reload_policies().await.unwrap();
+        assert_eq!(auth.policy_set.read().await.policies().count(), 2);
+    }
+    
+    /// Store de solo lectura que devuelve contenido sin validar, como filas
+    /// antiguas en base de datos
+    struct RawPolicyStore(Vec<(String, String)>);
+    
+    #[async_trait]
+    impl PolicyStore for RawPolicyStore {
+        async fn create_policy(&self, _content: String) -> Result<String, PolicyStoreError> {
+            Err(PolicyStoreError::Unsupported("create_policy".to_string()))
+        }
+        
+        async fn get_policy(&self, id: &str) -> Result<Option<String>, PolicyStoreError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|(existing, _)| existing == id)
+                .map(|(_, content)| content.clone()))
+        }
+        
+        async fn list_policies(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+            Ok(self.0.clone())
+        }
+        
+        async fn update_policy(
+            &self,
+            _id: &str,
+            _content: String,
+        ) -> Result<(), PolicyStoreError> {
+            Err(PolicyStoreError::Unsupported("update_policy".to_string()))
+        }
+        
+        async fn delete_policy(&self, _id: &str) -> Result<(), PolicyStoreError> {
+            Err(PolicyStoreError::Unsupported("delete_policy".to_string()))
+        }
+        
+        async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
+            Err(PolicyStoreError::Parse("broken".to_string()))
+        }
+    }
+    
+    fn raw_store() -> RawPolicyStore {
+        RawPolicyStore(vec![
+            ("good".to_string(), PERMIT_ALL.to_string()),
+            ("broken".to_string(), "permit(principal, action".to_string()),
+        ])
+    }
+    
+    #[tokio::test]
+    async fn test_strict_mode_fails_on_broken_policy() {
+        let result = HodeiAuthService::builder()
+            .with_policy_store(raw_store())
+            .with_schema(empty_schema())
+            .build()
+            .await;
+        
+        assert!(matches!(result, Err(BuildError::PolicyStore(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_lenient_mode_skips_broken_policy() {
+        let auth = HodeiAuthService::builder()
+            .with_policy_store(raw_store())
+            .with_policy_load_mode(PolicyLoadMode::Lenient)
+            .with_schema(empty_schema())
+            .build()
+            .await
+            .unwrap();
+        
+        assert_eq!(auth.policy_set.read().await.policies().count(), 1);
+        let rejected = auth.rejected_policies().await;
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].policy_id, "broken");
+        assert_eq!(auth.rejected_policy_count(), 1);
+        
+        // El contador es acumulado: cada recarga vuelve a omitir la política
+        auth.reload_policies().await.unwrap();
+        assert_eq!(auth.rejected_policies().await.len(), 1);
+        assert_eq!(auth.rejected_policy_count(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_invalidation_reloads_policies() {
+        let store = Arc::new(InMemoryPolicyStore::new());
+        
+        let auth = HodeiAuthService::builder()
+            .with_policy_store(store.clone())
+            .with_cache_invalidation(LocalInvalidation::default())
+            .with_schema(empty_schema())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(auth.policy_set.read().await.policies().count(), 0);
+        
+        store.create_policy(PERMIT_ALL.to_string()).await.unwrap();
+        auth.invalidate_cache().await.unwrap();
+        
+        // La recarga se ejecuta en una tarea aparte
+        wait_for_policies(&auth, 1).await;
+    }
+}
@@ -23,22 +23,43 @@
 //!     .auto_discover_schema()?
 //!     .build()
 //!     .await?;
+//!
+//! // O con cualquier otro backend; sin invalidación se usa una no-op
+//! let auth = HodeiAuthService::builder()
+//!     .with_policy_store(InMemoryPolicyStore::from_dir("./policies")?)
+//!     .auto_discover_schema()?
+//!     .build()
+//!     .await?;
 //! ```
 
+pub mod audit;
+pub mod authorize;
+pub mod batch;
 pub mod builder;
+pub mod residual;
 pub mod schema;
+pub mod tenant;
+#[cfg(test)]
+mod test_support;
 
-pub use builder::{BuildError, HodeiAuthService, HodeiAuthServiceBuilder};
+pub use audit::{AuditConfig, JsonLinesAuditSink, TracingAuditSink};
+pub use batch::{BatchDecisions, BatchItem, BatchMode};
+pub use builder::{BuildError, HodeiAuthService, HodeiAuthServiceBuilder, PolicyLoadMode};
+pub use hodei_authz::{AuthorizationDecision, AuthorizationError, ResidualFilter};
 pub use schema::{auto_discover_schema, SchemaError};
+pub use tenant::CROSS_TENANT_ANNOTATION;
 
 /// Prelude con todos los imports comunes
 pub mod prelude {
-    pub use hodei_hrn::*;
-    pub use hodei_derive::{HodeiEntity, HodeiAction};
+    pub use hodei_hrn::{Hrn, HrnBuilder, HrnError, HrnPattern};
+    pub use hodei_derive::{HodeiEntity, HodeiAction, HodeiContext, HodeiRecord, HodeiVirtualEntity};
     pub use hodei_authz::*;
     
-    pub use crate::builder::{HodeiAuthService, HodeiAuthServiceBuilder, BuildError};
+    pub use crate::audit::{AuditConfig, JsonLinesAuditSink, TracingAuditSink};
+    pub use crate::batch::{BatchDecisions, BatchItem, BatchMode};
+    pub use crate::builder::{HodeiAuthService, HodeiAuthServiceBuilder, BuildError, PolicyLoadMode};
     pub use crate::schema::{auto_discover_schema, SchemaError};
+    pub use crate::tenant::CROSS_TENANT_ANNOTATION;
     
     #[cfg(feature = "postgres")]
     pub use hodei_authz_postgres::*;
@@ -46,6 +67,9 @@ pub mod prelude {
     #[cfg(feature = "redis")]
     pub use hodei_authz_redis::*;
     
+    #[cfg(feature = "file")]
+    pub use hodei_authz_file::*;
+    
     #[cfg(feature = "axum")]
     pub use hodei_authz_axum::*;
 }
@@ -61,5 +85,8 @@ pub use hodei_authz_postgres;
 #[cfg(feature = "redis")]
 pub use hodei_authz_redis;
 
+#[cfg(feature = "file")]
+pub use hodei_authz_file;
+
 #[cfg(feature = "axum")]
 pub use hodei_authz_axum;
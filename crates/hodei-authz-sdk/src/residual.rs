@@ -0,0 +1,233 @@
+//! Filtrado de listados mediante evaluación parcial
+//!
+//! En lugar de autorizar cada fila de un listado, se evalúan las políticas con
+//! el recurso desconocido y se obtiene la condición que deben cumplir los
+//! recursos permitidos (ver `hodei_authz::residual`). Con
+//! `hodei_authz_postgres::SqlFilterTranslator` esa condición se convierte en
+//! un `WHERE`.
+
+use crate::builder::HodeiAuthService;
+use crate::tenant::is_cross_tenant;
+use cedar_policy::{Context, EntityTypeName, RequestBuilder};
+use hodei_authz::{
+    AuthorizationError, ResidualFilter, RuntimeHodeiActionMapper, RuntimeHodeiEntityMapper,
+};
+
+impl HodeiAuthService {
+    /// Condición que deben cumplir los recursos de tipo `resource_type` para
+    /// que `principal` pueda ejecutar `action` sobre ellos
+    ///
+    /// La petición se evalúa con contexto vacío. Con aislamiento por tenant se
+    /// usan las políticas del tenant del principal y el filtro se limita a los
+    /// recursos de ese tenant (`ResidualExpr::InTenant`), salvo en las
+    /// políticas globales marcadas con `@cross_tenant`: las de un tenant no se
+    /// evalúan sobre recursos de otros, igual que en `is_authorized`. Estas
+    /// evaluaciones no se auditan.
+    ///
+    /// # Ejemplo
+    ///
+    /// ```rust,ignore
+    /// let residual = auth
+    ///     .residual_filter(&user, &DocumentCommand::List, "MyApp::Document")
+    ///     .await?;
+    ///
+    /// match residual {
+    ///     ResidualFilter::AllowAll => { /* todos */ }
+    ///     ResidualFilter::DenyAll => { /* ninguno */ }
+    ///     ResidualFilter::Conditional { .. } => { /* traducir a SQL */ }
+    /// }
+    /// ```
+    pub async fn residual_filter(
+        &self,
+        principal: &(impl RuntimeHodeiEntityMapper + ?Sized),
+        action: &(impl RuntimeHodeiActionMapper + ?Sized),
+        resource_type: &str,
+    ) -> Result<ResidualFilter, AuthorizationError> {
+        let resource_type: EntityTypeName = resource_type.parse().map_err(|e| {
+            AuthorizationError::Request(format!("Invalid entity type {}: {}", resource_type, e))
+        })?;
+        let request = RequestBuilder::default()
+            .principal(principal.to_cedar_euid())
+            .action(action.to_cedar_action_euid())
+            .unknown_resource_with_type(resource_type)
+            .context(Context::empty())
+            .schema(&self.schema)
+            .build()
+            .map_err(|e| AuthorizationError::Request(e.to_string()))?;
+
+        let entities =
+            self.build_entities(self.with_related(vec![principal.to_cedar_entity()]).await?)?;
+        let tenant_id = &principal.hodei_hrn().tenant_id;
+        let policy_set = self.policy_set_for(tenant_id).await?;
+        let response = self
+            .authorizer
+            .is_authorized_partial(&request, &policy_set, &entities);
+
+        if !self.tenant_isolation {
+            return Ok(ResidualFilter::from(&response));
+        }
+        let global = self.policy_set.read().await.clone();
+        Ok(ResidualFilter::within_tenant(&response, tenant_id, |id| {
+            is_cross_tenant(&global, id)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::{HodeiAuthService, HodeiAuthServiceBuilder};
+    use crate::test_support::{doc, Read};
+    use cedar_policy::{Context, Schema};
+    use hodei_authz::{CompareOp, InMemoryPolicyStore, ResidualExpr, ResidualFilter, ResidualValue};
+
+    async fn service(policies: &[&str]) -> HodeiAuthService {
+        builder(policies).build().await.unwrap()
+    }
+
+    fn builder(policies: &[&str]) -> HodeiAuthServiceBuilder {
+        let store = InMemoryPolicyStore::new();
+        for (i, policy) in policies.iter().enumerate() {
+            store.insert(format!("p{}", i), policy.to_string()).unwrap();
+        }
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity Doc = { owner?: Doc }; action Read appliesTo { principal: Doc, resource: Doc };",
+        )
+        .unwrap();
+
+        HodeiAuthService::builder()
+            .with_policy_store(store)
+            .with_schema(schema)
+    }
+
+    fn in_tenant(tenant_id: &str) -> ResidualExpr {
+        ResidualExpr::InTenant(tenant_id.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_residual_filter_on_owner() {
+        let auth =
+            service(&["permit(principal, action, resource) when { resource.owner == principal };"])
+                .await;
+        let alice = doc("acme", "alice");
+
+        let residual = auth.residual_filter(&alice, &Read, "Doc").await.unwrap();
+        let ResidualFilter::Conditional { permits, forbids } = residual else {
+            panic!("Expected a conditional filter, got {:?}", residual);
+        };
+        assert!(forbids.is_empty());
+        assert_eq!(permits.len(), 1);
+        assert_eq!(
+            permits[0].condition,
+            ResidualExpr::Compare(
+                CompareOp::Eq,
+                Box::new(ResidualExpr::Attr(Box::new(ResidualExpr::Resource), "owner".to_string())),
+                Box::new(ResidualExpr::Value(ResidualValue::Entity {
+                    type_name: "Doc".to_string(),
+                    id: alice.0.to_string(),
+                })),
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_residual_filter_decided_without_resource() {
+        let auth = service(&["permit(principal, action, resource);"]).await;
+        let residual = auth.residual_filter(&doc("acme", "alice"), &Read, "Doc").await.unwrap();
+        assert_eq!(residual, ResidualFilter::AllowAll);
+
+        let auth = service(&[]).await;
+        let residual = auth.residual_filter(&doc("acme", "alice"), &Read, "Doc").await.unwrap();
+        assert_eq!(residual, ResidualFilter::DenyAll);
+    }
+
+    #[tokio::test]
+    async fn test_residual_filter_with_tenant_isolation() {
+        let auth = builder(&[
+            "permit(principal, action, resource) when { resource.owner == principal };",
+            r#"@cross_tenant("true") permit(principal, action, resource) when { resource.owner == principal };"#,
+        ])
+        .with_tenant_isolation()
+        .build()
+        .await
+        .unwrap();
+
+        let residual = auth.residual_filter(&doc("acme", "alice"), &Read, "Doc").await.unwrap();
+        let ResidualFilter::Conditional { permits, .. } = residual else {
+            panic!("Expected a conditional filter, got {:?}", residual);
+        };
+        let scoped = permits.iter().find(|permit| permit.id == "p0").unwrap();
+        let cross_tenant = permits.iter().find(|permit| permit.id == "p1").unwrap();
+        assert_eq!(
+            scoped.condition,
+            ResidualExpr::And(
+                Box::new(in_tenant("acme")),
+                Box::new(cross_tenant.condition.clone()),
+            )
+        );
+        assert!(matches!(cross_tenant.condition, ResidualExpr::Compare(..)));
+
+        // Una política global sin condiciones no abre los recursos de otros tenants
+        let auth = builder(&["permit(principal, action, resource);"])
+            .with_tenant_isolation()
+            .build()
+            .await
+            .unwrap();
+        let residual = auth.residual_filter(&doc("acme", "alice"), &Read, "Doc").await.unwrap();
+        let ResidualFilter::Conditional { permits, forbids } = residual else {
+            panic!("Expected a conditional filter, got {:?}", residual);
+        };
+        assert!(forbids.is_empty());
+        assert_eq!(permits.len(), 1);
+        assert_eq!(permits[0].condition, in_tenant("acme"));
+
+        // Una política global `@cross_tenant` sin condiciones lo permite todo
+        let auth = builder(&[r#"@cross_tenant("true") permit(principal, action, resource);"#])
+            .with_tenant_isolation()
+            .build()
+            .await
+            .unwrap();
+        let residual = auth.residual_filter(&doc("acme", "alice"), &Read, "Doc").await.unwrap();
+        assert_eq!(residual, ResidualFilter::AllowAll);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_cross_tenant_policy_stays_in_its_tenant() {
+        let store = InMemoryPolicyStore::new();
+        store
+            .insert_for_tenant(
+                "acme",
+                "acme-support".to_string(),
+                r#"@cross_tenant("true") permit(principal, action, resource);"#.to_string(),
+            )
+            .unwrap();
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity Doc = { owner?: Doc }; action Read appliesTo { principal: Doc, resource: Doc };",
+        )
+        .unwrap();
+        let auth = HodeiAuthService::builder()
+            .with_policy_store(store)
+            .with_schema(schema)
+            .with_tenant_isolation()
+            .build()
+            .await
+            .unwrap();
+        let alice = doc("acme", "alice");
+
+        // `is_authorized` no aplica la política a recursos de otro tenant...
+        let decision = auth
+            .is_authorized(&alice, &Read, &doc("globex", "d1"), Context::empty())
+            .await
+            .unwrap();
+        assert!(!decision.is_allowed());
+
+        // ...así que el filtro tampoco los incluye
+        let residual = auth.residual_filter(&alice, &Read, "Doc").await.unwrap();
+        let ResidualFilter::Conditional { permits, forbids } = residual else {
+            panic!("Expected a conditional filter, got {:?}", residual);
+        };
+        assert!(forbids.is_empty());
+        assert_eq!(permits.len(), 1);
+        assert_eq!(permits[0].id, "acme-support");
+        assert_eq!(permits[0].condition, in_tenant("acme"));
+    }
+}
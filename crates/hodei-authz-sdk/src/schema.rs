@@ -1,10 +1,10 @@
 //! Auto-discovery de schema Cedar usando inventory
 //!
-//! Este módulo recolecta automáticamente todos los EntitySchemaFragment y ActionSchemaFragment
-//! registrados por los derives HodeiEntity y HodeiAction.
+//! Este módulo recolecta automáticamente todos los EntitySchemaFragment, ActionSchemaFragment
+//! y RecordSchemaFragment registrados por los derives HodeiEntity, HodeiAction y HodeiRecord.
 
 use cedar_policy::Schema;
-use hodei_authz::{ActionSchemaFragment, EntitySchemaFragment};
+use hodei_authz::{ActionSchemaFragment, EntitySchemaFragment, RecordSchemaFragment};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
@@ -35,9 +35,15 @@ pub fn auto_discover_schema() -> Result<Schema, SchemaError> {
         merge_entity_fragment(&mut namespaces, fragment);
     }
     
+    // Recolectar records anidados (common types)
+    for fragment in hodei_authz::inventory::iter::<RecordSchemaFragment>() {
+        tracing::debug!("Discovered record: {}", fragment.type_name);
+        merge_record_fragment(&mut namespaces, fragment);
+    }
+    
     // Recolectar action fragments
     for fragment in hodei_authz::inventory::iter::<ActionSchemaFragment>() {
-        tracing::debug!("Discovered action: {}", fragment.name);
+        tracing::debug!("Discovered action: {}::Action::\"{}\"", fragment.namespace, fragment.name);
         merge_action_fragment(&mut namespaces, fragment);
     }
     
@@ -56,15 +62,11 @@ fn merge_entity_fragment(
     fragment: &EntitySchemaFragment,
 ) {
     // Parsear el entity_type para extraer namespace y entity name
-    // Formato esperado: "Namespace::EntityName"
-    let parts: Vec<&str> = fragment.entity_type.split("::").collect();
-    if parts.len() != 2 {
+    // Formato esperado: "Namespace::EntityName" (el namespace puede ser "Org::App")
+    let Some((namespace, entity_name)) = fragment.entity_type.rsplit_once("::") else {
         tracing::warn!("Invalid entity_type format: {}", fragment.entity_type);
         return;
-    }
-    
-    let namespace = parts[0];
-    let entity_name = parts[1];
+    };
     
     // Obtener o crear el namespace
     let ns = namespaces
@@ -77,36 +79,31 @@ fn merge_entity_fragment(
         });
     
     // Agregar el entity type
-    if let Some(entity_types) = ns.get_mut("entityTypes") {
-        if let Some(obj) = entity_types.as_object_mut() {
-            obj.insert(
-                entity_name.to_string(),
-                serde_json::from_str(&fragment.fragment_json)
-                    .unwrap_or_else(|_| json!({})),
-            );
-        }
+    if let Some(obj) = ns.get_mut("entityTypes").and_then(Value::as_object_mut) {
+        obj.insert(
+            entity_name.to_string(),
+            serde_json::from_str(fragment.fragment_json)
+                .unwrap_or_else(|_| json!({})),
+        );
     }
 }
 
-/// Merge un ActionSchemaFragment en el schema
+/// Merge un ActionSchemaFragment en el namespace declarado por la acción
 fn merge_action_fragment(
     namespaces: &mut HashMap<String, Value>,
     fragment: &ActionSchemaFragment,
 ) {
-    // Parsear el name para extraer namespace
-    // Formato esperado: "Namespace::ActionName" o "Namespace::Resource::ActionName"
-    let parts: Vec<&str> = fragment.name.split("::").collect();
-    if parts.is_empty() {
-        tracing::warn!("Invalid action name format: {}", fragment.name);
-        return;
-    }
+    let mut action: Value = serde_json::from_str(fragment.fragment_json)
+        .unwrap_or_else(|_| json!({}));
     
-    let namespace = parts[0];
-    let action_key = parts[1..].join("::");
+    // El contexto tipado (#[derive(HodeiContext)]) se referencia como common type
+    if let (Some(context_type), Some(applies_to)) = (fragment.context_type, action.get_mut("appliesTo")) {
+        applies_to["context"] = json!({ "type": context_type });
+    }
     
     // Obtener o crear el namespace
     let ns = namespaces
-        .entry(namespace.to_string())
+        .entry(fragment.namespace.to_string())
         .or_insert_with(|| {
             json!({
                 "entityTypes": {},
@@ -115,11 +112,42 @@ fn merge_action_fragment(
         });
     
     // Agregar la action
-    if let Some(actions) = ns.get_mut("actions") {
-        if let Some(obj) = actions.as_object_mut() {
-            obj.insert(
-                action_key,
-                serde_json::from_str(&fragment.fragment_json)
+    if let Some(obj) = ns.get_mut("actions").and_then(Value::as_object_mut) {
+        // Un grupo de acciones (sin appliesTo) puede declararse desde varios enums:
+        // solo se registra si no existe ya
+        if action.get("appliesTo").is_none() && obj.contains_key(fragment.name) {
+            return;
+        }
+        obj.insert(fragment.name.to_string(), action);
+    }
+}
+
+/// Merge un RecordSchemaFragment en los commonTypes del namespace
+fn merge_record_fragment(
+    namespaces: &mut HashMap<String, Value>,
+    fragment: &RecordSchemaFragment,
+) {
+    // Formato esperado: "Namespace::RecordName"
+    let Some((namespace, record_name)) = fragment.type_name.rsplit_once("::") else {
+        tracing::warn!("Invalid record type format: {}", fragment.type_name);
+        return;
+    };
+    
+    let ns = namespaces
+        .entry(namespace.to_string())
+        .or_insert_with(|| {
+            json!({
+                "entityTypes": {},
+                "actions": {}
+            })
+        });
+    
+    if let Some(obj) = ns.as_object_mut() {
+        let common_types = obj.entry("commonTypes").or_insert_with(|| json!({}));
+        if let Some(common_types) = common_types.as_object_mut() {
+            common_types.insert(
+                record_name.to_string(),
+                serde_json::from_str(fragment.fragment_json)
                     .unwrap_or_else(|_| json!({})),
             );
         }
@@ -183,4 +211,91 @@ mod tests {
         let schema = example_schema();
         assert!(schema.is_ok());
     }
+    
+    #[test]
+    fn test_merge_nested_record_and_sets() {
+        let mut namespaces = HashMap::new();
+        
+        merge_record_fragment(&mut namespaces, &RecordSchemaFragment {
+            type_name: "MyApp::Address",
+            fragment_json: r#"{"type":"Record","attributes":{"city":{"type":"String","required":true}}}"#,
+        });
+        merge_entity_fragment(&mut namespaces, &EntitySchemaFragment {
+            entity_type: "MyApp::User",
+            fragment_json: r#"{"memberOfTypes":[],"shape":{"type":"Record","attributes":{
+                "address":{"type":"Address","required":true},
+                "nickname":{"type":"String","required":false},
+                "tags":{"type":"Set","element":{"type":"String"},"required":true},
+                "friends":{"type":"Set","element":{"type":"Entity","name":"MyApp::User"},"required":true}
+            }}}"#,
+        });
+        
+        let schema = Schema::from_json_str(&json!(namespaces).to_string());
+        assert!(schema.is_ok(), "{:?}", schema.err());
+    }
+    
+    #[test]
+    fn test_merge_namespaced_actions_and_groups() {
+        let mut namespaces = HashMap::new();
+        
+        for entity_type in ["MyApp::User", "MyApp::Document"] {
+            merge_entity_fragment(&mut namespaces, &EntitySchemaFragment {
+                entity_type,
+                fragment_json: r#"{"memberOfTypes":[],"shape":{"type":"Record","attributes":{}}}"#,
+            });
+        }
+        let group = ActionSchemaFragment {
+            namespace: "MyApp",
+            name: "DocumentActions",
+            fragment_json: "{}",
+            context_type: None,
+        };
+        merge_action_fragment(&mut namespaces, &group);
+        merge_action_fragment(&mut namespaces, &ActionSchemaFragment {
+            namespace: "MyApp",
+            name: "Document::Read",
+            fragment_json: r#"{"appliesTo":{"principalTypes":["User"],"resourceTypes":["Document"]},"memberOf":[{"id":"DocumentActions"}]}"#,
+            context_type: None,
+        });
+        // Un segundo registro del grupo no sobrescribe nada
+        merge_action_fragment(&mut namespaces, &group);
+        
+        assert!(!namespaces.contains_key("Document"));
+        let schema = Schema::from_json_str(&json!(namespaces).to_string()).unwrap();
+        let actions: Vec<String> = schema.actions().map(|uid| uid.to_string()).collect();
+        assert!(actions.contains(&r#"MyApp::Action::"Document::Read""#.to_string()));
+        assert!(actions.contains(&r#"MyApp::Action::"DocumentActions""#.to_string()));
+    }
+    
+    #[test]
+    fn test_merge_action_context() {
+        let mut namespaces = HashMap::new();
+        
+        for entity_type in ["MyApp::User", "MyApp::Document"] {
+            merge_entity_fragment(&mut namespaces, &EntitySchemaFragment {
+                entity_type,
+                fragment_json: r#"{"memberOfTypes":[],"shape":{"type":"Record","attributes":{}}}"#,
+            });
+        }
+        merge_record_fragment(&mut namespaces, &RecordSchemaFragment {
+            type_name: "MyApp::RequestContext",
+            fragment_json: r#"{"type":"Record","attributes":{
+                "source_ip":{"type":"Extension","name":"ipaddr","required":true},
+                "mfa":{"type":"Boolean","required":true}
+            }}"#,
+        });
+        merge_action_fragment(&mut namespaces, &ActionSchemaFragment {
+            namespace: "MyApp",
+            name: "Document::Read",
+            fragment_json: r#"{"appliesTo":{"principalTypes":["User"],"resourceTypes":["Document"]}}"#,
+            context_type: Some("MyApp::RequestContext"),
+        });
+        
+        assert_eq!(
+            namespaces["MyApp"]["actions"]["Document::Read"]["appliesTo"]["context"],
+            json!({ "type": "MyApp::RequestContext" })
+        );
+        let schema = Schema::from_json_str(&json!(namespaces).to_string());
+        assert!(schema.is_ok(), "{:?}", schema.err());
+    }
 }
@@ -0,0 +1,344 @@
+//! Aislamiento de políticas por tenant
+//!
+//! Con `HodeiAuthServiceBuilder::with_tenant_isolation` cada petición se evalúa
+//! con las políticas globales más las del tenant del recurso (`Hrn::tenant_id`).
+//! Los conjuntos por tenant se cargan la primera vez que se necesitan y se
+//! descartan en cada recarga.
+
+use crate::builder::{record_rejected, BuildError, HodeiAuthService, PolicyLoadMode};
+use cedar_policy::{Decision, PolicyId, PolicySet};
+use hodei_authz::{
+    AuthorizationDecision, AuthorizationError, PolicyLoadReport, PolicyStore, PolicyStoreError,
+    RejectedPolicy,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Anotación que permite a una política autorizar peticiones entre tenants
+///
+/// ```cedar
+/// @cross_tenant("true")
+/// permit(principal in MyApp::Group::"support", action, resource);
+/// ```
+pub const CROSS_TENANT_ANNOTATION: &str = "cross_tenant";
+
+/// Caché perezosa de `PolicySet`s (globales + tenant) por tenant
+pub(crate) struct TenantPolicySets {
+    sets: RwLock<HashMap<String, Arc<PolicySet>>>,
+    /// Se incrementa en cada recarga para no guardar conjuntos calculados
+    /// con un conjunto global ya sustituido
+    generation: AtomicU64,
+}
+
+impl TenantPolicySets {
+    pub(crate) fn new() -> Self {
+        Self {
+            sets: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Descarta todos los conjuntos cargados
+    pub(crate) async fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.sets.write().await.clear();
+    }
+
+    /// Devuelve el conjunto de `tenant_id`, cargándolo si no está en caché
+    pub(crate) async fn get_or_load(
+        &self,
+        tenant_id: &str,
+        store: &dyn PolicyStore,
+        mode: PolicyLoadMode,
+        global: &RwLock<Arc<PolicySet>>,
+        rejected_count: &AtomicU64,
+    ) -> Result<Arc<PolicySet>, BuildError> {
+        if let Some(set) = self.sets.read().await.get(tenant_id) {
+            return Ok(set.clone());
+        }
+
+        // La generación se lee antes que el conjunto global: si hay una
+        // recarga entre medias, el resultado se usa pero no se guarda
+        let generation = self.generation.load(Ordering::SeqCst);
+        let global = global.read().await.clone();
+        let set = load_tenant_set(tenant_id, store, mode, global, rejected_count).await?;
+
+        let mut sets = self.sets.write().await;
+        if self.generation.load(Ordering::SeqCst) == generation {
+            sets.insert(tenant_id.to_string(), set.clone());
+        }
+        Ok(set)
+    }
+}
+
+/// Añade las políticas de `tenant_id` a una copia del conjunto global
+///
+/// Si el store no soporta tenants se usa el conjunto global tal cual. Una
+/// política del tenant con el mismo id que una global se descarta y se cuenta
+/// como rechazada: la global prevalece y el resto del tenant sigue cargando.
+async fn load_tenant_set(
+    tenant_id: &str,
+    store: &dyn PolicyStore,
+    mode: PolicyLoadMode,
+    global: Arc<PolicySet>,
+    rejected_count: &AtomicU64,
+) -> Result<Arc<PolicySet>, BuildError> {
+    let loaded = match mode {
+        PolicyLoadMode::Strict => store
+            .load_tenant_policies(tenant_id)
+            .await
+            .map(|policy_set| PolicyLoadReport {
+                policy_set,
+                rejected: Vec::new(),
+            }),
+        PolicyLoadMode::Lenient => store.load_tenant_policies_lenient(tenant_id).await,
+    };
+    let mut report = match loaded {
+        Ok(report) => report,
+        Err(PolicyStoreError::Unsupported(_)) => return Ok(global),
+        Err(e) => return Err(BuildError::PolicyStore(e.to_string())),
+    };
+    let mut set = (*global).clone();
+    for policy in report.policy_set.policies() {
+        if let Err(e) = set.add(policy.clone()) {
+            report.rejected.push(RejectedPolicy {
+                policy_id: policy.id().to_string(),
+                reason: format!("{}: {}", tenant_id, e),
+            });
+        }
+    }
+    record_rejected(&report, rejected_count);
+    tracing::debug!(tenant_id, "Tenant policies loaded");
+    Ok(Arc::new(set))
+}
+
+/// Indica si la política `id` está marcada con [`CROSS_TENANT_ANNOTATION`]
+pub(crate) fn is_cross_tenant(policy_set: &PolicySet, id: &str) -> bool {
+    policy_set
+        .policy(&PolicyId::new(id))
+        .and_then(|policy| policy.annotation(CROSS_TENANT_ANNOTATION))
+        .is_some_and(|value| value != "false")
+}
+
+/// Indica si alguna de las políticas que permitieron la petición está marcada
+/// con [`CROSS_TENANT_ANNOTATION`]
+fn allows_cross_tenant(policy_set: &PolicySet, decision: &AuthorizationDecision) -> bool {
+    decision
+        .determining_policies
+        .iter()
+        .any(|id| is_cross_tenant(policy_set, id))
+}
+
+impl HodeiAuthService {
+    /// Conjunto con el que evaluar peticiones sobre recursos de `tenant_id`
+    pub(crate) async fn policy_set_for(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Arc<PolicySet>, AuthorizationError> {
+        if !self.tenant_isolation {
+            return Ok(self.policy_set.read().await.clone());
+        }
+
+        self.tenant_policy_sets
+            .get_or_load(
+                tenant_id,
+                self.policy_store.as_ref(),
+                self.load_mode,
+                &self.policy_set,
+                &self.rejected_count,
+            )
+            .await
+            .map_err(|e| AuthorizationError::Policies(e.to_string()))
+    }
+
+    /// Deniega las peticiones entre tenants que ninguna política permite
+    /// explícitamente
+    pub(crate) fn enforce_tenant_boundary(
+        &self,
+        principal_tenant: &str,
+        resource_tenant: &str,
+        policy_set: &PolicySet,
+        decision: AuthorizationDecision,
+    ) -> AuthorizationDecision {
+        if !self.tenant_isolation
+            || principal_tenant == resource_tenant
+            || !decision.is_allowed()
+            || allows_cross_tenant(policy_set, &decision)
+        {
+            return decision;
+        }
+
+        tracing::debug!(
+            principal_tenant,
+            resource_tenant,
+            "Cross-tenant request denied"
+        );
+        AuthorizationDecision {
+            decision: Decision::Deny,
+            determining_policies: Vec::new(),
+            errors: decision.errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::HodeiAuthService;
+    use crate::test_support::{doc, Read};
+    use async_trait::async_trait;
+    use cedar_policy::{Context, Policy, PolicyId, PolicySet, Schema};
+    use hodei_authz::{InMemoryPolicyStore, PolicyStore, PolicyStoreError};
+    use std::sync::Arc;
+
+    /// Store que, a diferencia de los incluidos, no impide que una política
+    /// del tenant reutilice el id de una global
+    struct CollidingStore;
+
+    fn single(id: &str, content: &str) -> PolicySet {
+        let policy = Policy::parse(Some(PolicyId::new(id)), content).unwrap();
+        PolicySet::from_policies([policy]).unwrap()
+    }
+
+    #[async_trait]
+    impl PolicyStore for CollidingStore {
+        async fn create_policy(&self, _content: String) -> Result<String, PolicyStoreError> {
+            Err(PolicyStoreError::Unsupported("create_policy".to_string()))
+        }
+
+        async fn get_policy(&self, _id: &str) -> Result<Option<String>, PolicyStoreError> {
+            Ok(None)
+        }
+
+        async fn list_policies(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+            Ok(Vec::new())
+        }
+
+        async fn update_policy(&self, _id: &str, _content: String) -> Result<(), PolicyStoreError> {
+            Err(PolicyStoreError::Unsupported("update_policy".to_string()))
+        }
+
+        async fn delete_policy(&self, _id: &str) -> Result<(), PolicyStoreError> {
+            Err(PolicyStoreError::Unsupported("delete_policy".to_string()))
+        }
+
+        async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
+            Ok(single("shared", "permit(principal, action, resource);"))
+        }
+
+        async fn load_tenant_policies(&self, _tenant_id: &str) -> Result<PolicySet, PolicyStoreError> {
+            Ok(single("shared", "forbid(principal, action, resource);"))
+        }
+    }
+
+    async fn service(store: Arc<InMemoryPolicyStore>) -> HodeiAuthService {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity Doc; action Read appliesTo { principal: Doc, resource: Doc };",
+        )
+        .unwrap();
+        HodeiAuthService::builder()
+            .with_policy_store(store)
+            .with_schema(schema)
+            .with_tenant_isolation()
+            .build()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tenant_policies_only_apply_to_their_tenant() {
+        let store = Arc::new(InMemoryPolicyStore::new());
+        store
+            .create_tenant_policy("acme", "permit(principal, action, resource);".to_string())
+            .await
+            .unwrap();
+        let auth = service(store).await;
+
+        let acme = auth
+            .is_authorized(&doc("acme", "a"), &Read, &doc("acme", "b"), Context::empty())
+            .await
+            .unwrap();
+        assert!(acme.is_allowed());
+
+        let globex = auth
+            .is_authorized(&doc("globex", "a"), &Read, &doc("globex", "b"), Context::empty())
+            .await
+            .unwrap();
+        assert!(!globex.is_allowed());
+    }
+
+    #[tokio::test]
+    async fn test_cross_tenant_requires_annotation() {
+        let store = Arc::new(InMemoryPolicyStore::new());
+        store
+            .create_policy("permit(principal, action, resource);".to_string())
+            .await
+            .unwrap();
+        let auth = service(store.clone()).await;
+
+        let decision = auth
+            .is_authorized(&doc("acme", "a"), &Read, &doc("globex", "b"), Context::empty())
+            .await
+            .unwrap();
+        assert!(!decision.is_allowed());
+
+        store
+            .create_policy(r#"@cross_tenant("true") permit(principal, action, resource);"#.to_string())
+            .await
+            .unwrap();
+        auth.reload_policies().await.unwrap();
+
+        let decision = auth
+            .is_authorized(&doc("acme", "a"), &Read, &doc("globex", "b"), Context::empty())
+            .await
+            .unwrap();
+        assert!(decision.is_allowed());
+    }
+
+    #[tokio::test]
+    async fn test_tenant_policy_cannot_reuse_global_id() {
+        let store = Arc::new(InMemoryPolicyStore::new());
+        store
+            .insert("shared".to_string(), "permit(principal, action, resource);".to_string())
+            .unwrap();
+
+        let result = store.insert_for_tenant(
+            "acme",
+            "shared".to_string(),
+            "forbid(principal, action, resource);".to_string(),
+        );
+        assert!(matches!(result, Err(PolicyStoreError::AlreadyExists(id)) if id == "shared"));
+
+        let decision = service(store)
+            .await
+            .is_authorized(&doc("acme", "a"), &Read, &doc("acme", "b"), Context::empty())
+            .await
+            .unwrap();
+        assert!(decision.is_allowed());
+    }
+
+    #[tokio::test]
+    async fn test_colliding_tenant_policy_is_skipped() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity Doc; action Read appliesTo { principal: Doc, resource: Doc };",
+        )
+        .unwrap();
+        let auth = HodeiAuthService::builder()
+            .with_policy_store(CollidingStore)
+            .with_schema(schema)
+            .with_tenant_isolation()
+            .build()
+            .await
+            .unwrap();
+
+        // La política global prevalece y las peticiones del tenant siguen funcionando
+        let decision = auth
+            .is_authorized(&doc("acme", "a"), &Read, &doc("acme", "b"), Context::empty())
+            .await
+            .unwrap();
+        assert!(decision.is_allowed());
+        assert_eq!(decision.determining_policies, vec!["shared".to_string()]);
+        assert_eq!(auth.rejected_policy_count(), 1);
+    }
+}
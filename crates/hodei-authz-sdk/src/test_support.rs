@@ -0,0 +1,58 @@
+//! Entidades y acciones mínimas para los tests del servicio
+
+use cedar_policy::{Entity, EntityUid};
+use hodei_authz::{RuntimeHodeiActionMapper, RuntimeHodeiEntityMapper, VirtualEntityError};
+use hodei_hrn::Hrn;
+use std::collections::HashSet;
+
+/// Entidad `Doc` sin atributos
+pub(crate) struct Doc(pub(crate) Hrn);
+
+impl RuntimeHodeiEntityMapper for Doc {
+    fn hodei_type_name(&self) -> &'static str {
+        "Doc"
+    }
+
+    fn hodei_id(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn hodei_hrn(&self) -> &Hrn {
+        &self.0
+    }
+
+    fn to_cedar_entity(&self) -> Entity {
+        Entity::new_no_attrs(self.to_cedar_euid(), HashSet::new())
+    }
+}
+
+/// Acción `Read`
+pub(crate) struct Read;
+
+impl RuntimeHodeiActionMapper for Read {
+    fn to_cedar_action_euid(&self) -> EntityUid {
+        r#"Action::"Read""#.parse().unwrap()
+    }
+
+    fn creates_resource_from_payload(&self) -> bool {
+        false
+    }
+
+    fn get_payload_as_virtual_entity(
+        &self,
+        _context: &dyn std::any::Any,
+    ) -> Result<Option<Entity>, VirtualEntityError> {
+        Ok(None)
+    }
+}
+
+/// Documento `id` del tenant `tenant_id`
+pub(crate) fn doc(tenant_id: &str, id: &str) -> Doc {
+    Doc(Hrn::builder()
+        .service("docs")
+        .tenant_id(tenant_id)
+        .resource(&format!("doc/{}", id))
+        .unwrap()
+        .build()
+        .unwrap())
+}
@@ -1,4 +1,5 @@
-use cedar_policy::{Entity, EntityUid};
+use crate::decision::AuthorizationError;
+use cedar_policy::{Context, Entity, EntityUid, RestrictedExpression};
 use hodei_hrn::Hrn;
 
 pub struct EntitySchemaFragment {
@@ -6,9 +7,22 @@ pub struct EntitySchemaFragment {
     pub fragment_json: &'static str,
 }
 
+/// Action registered by `#[derive(HodeiAction)]`
 pub struct ActionSchemaFragment {
+    /// Namespace declared with `#[hodei(namespace = "...")]`
+    pub namespace: &'static str,
+    /// Action id within the namespace, e.g. `Document::Read`
     pub name: &'static str,
     pub fragment_json: &'static str,
+    /// Common type of the action context, from `#[hodei(request_context = "...")]`
+    pub context_type: Option<&'static str>,
+}
+
+/// Common type registered by `#[derive(HodeiRecord)]`
+pub struct RecordSchemaFragment {
+    /// Fully qualified name, e.g. `MyApp::Address`
+    pub type_name: &'static str,
+    pub fragment_json: &'static str,
 }
 
 pub trait RuntimeHodeiEntityMapper {
@@ -28,5 +42,52 @@ pub trait RuntimeHodeiEntityMapper {
 pub trait RuntimeHodeiActionMapper {
     fn to_cedar_action_euid(&self) -> EntityUid;
     fn creates_resource_from_payload(&self) -> bool;
-    fn get_payload_as_virtual_entity(&self, context: &dyn std::any::Any) -> Option<Entity>;
+    /// Build the entity a `creates_resource` action is about to create
+    ///
+    /// Returns `Ok(None)` for actions without a payload entity and an error if
+    /// `context` is not the type declared with `#[hodei(context = "...")]`.
+    fn get_payload_as_virtual_entity(
+        &self,
+        context: &dyn std::any::Any,
+    ) -> Result<Option<Entity>, VirtualEntityError>;
+}
+
+/// Error building the virtual entity of a `creates_resource` action
+#[derive(Debug, thiserror::Error)]
+pub enum VirtualEntityError {
+    #[error("Invalid context type: expected {0}")]
+    InvalidContext(&'static str),
+
+    #[error("Invalid HRN for virtual entity: {0}")]
+    Hrn(String),
+
+    #[error("Failed to build virtual entity: {0}")]
+    Entity(String),
+}
+
+/// Request data available when building virtual entities
+pub trait VirtualEntityContext {
+    /// Tenant the new resource will belong to
+    fn tenant_id(&self) -> &str;
+}
+
+/// Payload of a `creates_resource` action, evaluated as the resource it creates
+pub trait HodeiVirtualEntity<Ctx: ?Sized> {
+    fn to_virtual_entity(&self, context: &Ctx) -> Result<Entity, VirtualEntityError>;
+}
+
+/// Nested struct used as a Cedar `Record` attribute of an entity
+pub trait HodeiRecord {
+    fn to_cedar_record(&self) -> RestrictedExpression;
+}
+
+/// Typed request context passed to Cedar as `context`
+///
+/// Derived with `#[derive(HodeiContext)]`, which also registers the context
+/// shape as a common type so actions can declare it in the schema.
+pub trait HodeiContext {
+    /// Fully qualified common type name, e.g. `MyApp::RequestContext`
+    const CONTEXT_TYPE: &'static str;
+
+    fn to_cedar_context(&self) -> Result<Context, AuthorizationError>;
 }
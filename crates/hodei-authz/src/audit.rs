@@ -0,0 +1,142 @@
+//! Audit records of authorization decisions
+//!
+//! An [`AuditSink`] receives one [`AuditEvent`] per evaluated request. Sinks
+//! are called in batches from a background task, so they may do I/O without
+//! slowing down authorization.
+
+use crate::decision::{AuthorizationDecision, EvaluationError};
+use async_trait::async_trait;
+use cedar_policy::{Context, Decision, EvalResult};
+use hodei_hrn::Hrn;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Errors reported by audit sinks
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+/// A single evaluated authorization request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// When the decision was made
+    pub timestamp: SystemTime,
+    /// HRN of the principal
+    pub principal: Hrn,
+    /// Cedar uid of the action, e.g. `MyApp::Action::"Document::Read"`
+    pub action: String,
+    /// HRN of the resource
+    pub resource: Hrn,
+    /// Final decision
+    pub decision: Decision,
+    /// IDs of the policies that determined the decision
+    pub determining_policies: Vec<String>,
+    /// Policies that errored during evaluation
+    pub errors: Vec<EvaluationError>,
+    /// Time spent evaluating the request
+    pub latency: Duration,
+    /// Request context, attribute name to its JSON value
+    pub context: BTreeMap<String, serde_json::Value>,
+}
+
+impl AuditEvent {
+    /// Build an event from an evaluated request
+    pub fn new(
+        principal: Hrn,
+        action: String,
+        resource: Hrn,
+        context: Context,
+        decision: &AuthorizationDecision,
+        latency: Duration,
+    ) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            principal,
+            action,
+            resource,
+            decision: decision.decision,
+            determining_policies: decision.determining_policies.clone(),
+            errors: decision.errors.clone(),
+            latency,
+            context: context
+                .clone()
+                .into_iter()
+                .filter_map(|(name, _)| {
+                    let value = context.get(&name)?;
+                    Some((name, value_to_json(&value)))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns true if the request was allowed
+    pub fn is_allowed(&self) -> bool {
+        self.decision == Decision::Allow
+    }
+
+    /// JSON representation used by the built-in sinks
+    pub fn to_json(&self) -> serde_json::Value {
+        let timestamp_ms = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+
+        json!({
+            "timestamp_ms": timestamp_ms,
+            "principal": self.principal.to_string(),
+            "action": self.action,
+            "resource": self.resource.to_string(),
+            "decision": if self.is_allowed() { "allow" } else { "deny" },
+            "determining_policies": self.determining_policies,
+            "errors": self
+                .errors
+                .iter()
+                .map(|e| json!({ "policy_id": e.policy_id, "message": e.message }))
+                .collect::<Vec<_>>(),
+            "latency_us": self.latency.as_micros() as u64,
+            "context": self.context,
+        })
+    }
+}
+
+/// Convert a context value to JSON, using Cedar's `__entity` escape for
+/// entity references and the Cedar syntax for extension values
+fn value_to_json(value: &EvalResult) -> serde_json::Value {
+    match value {
+        EvalResult::Bool(b) => json!(b),
+        EvalResult::Long(l) => json!(l),
+        EvalResult::String(s) => json!(s),
+        EvalResult::EntityUid(uid) => json!({
+            "__entity": { "type": uid.type_name().to_string(), "id": uid.id().unescaped() }
+        }),
+        EvalResult::Set(set) => set.iter().map(value_to_json).collect(),
+        EvalResult::Record(record) => record
+            .iter()
+            .map(|(name, value)| (name.clone(), value_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        EvalResult::ExtensionValue(s) => json!(s),
+    }
+}
+
+/// Destination for audit events
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Record a batch of events, in the order they were decided
+    async fn record(&self, events: &[AuditEvent]) -> Result<(), AuditError>;
+}
+
+#[async_trait]
+impl<T: AuditSink + ?Sized> AuditSink for Arc<T> {
+    async fn record(&self, events: &[AuditEvent]) -> Result<(), AuditError> {
+        (**self).record(events).await
+    }
+}
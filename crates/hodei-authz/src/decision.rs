@@ -0,0 +1,63 @@
+//! Authorization decision types shared by every Hodei evaluation entry point
+
+use cedar_policy::{Decision, Response};
+use thiserror::Error;
+
+/// Errors that prevent an authorization request from being evaluated
+#[derive(Debug, Error)]
+pub enum AuthorizationError {
+    #[error("Invalid request: {0}")]
+    Request(String),
+    #[error("Invalid entities: {0}")]
+    Entities(String),
+    #[error("Invalid context: {0}")]
+    Context(String),
+    #[error("Failed to load policies: {0}")]
+    Policies(String),
+}
+
+/// An error raised by a single policy while it was being evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationError {
+    /// ID of the policy that failed to evaluate
+    pub policy_id: String,
+    /// Human readable description of the failure
+    pub message: String,
+}
+
+/// Outcome of an authorization request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationDecision {
+    /// Final Cedar decision
+    pub decision: Decision,
+    /// IDs of the policies that determined the decision
+    pub determining_policies: Vec<String>,
+    /// Policies that errored during evaluation (they are ignored by Cedar)
+    pub errors: Vec<EvaluationError>,
+}
+
+impl AuthorizationDecision {
+    /// Returns true if the request was allowed
+    pub fn is_allowed(&self) -> bool {
+        self.decision == Decision::Allow
+    }
+}
+
+impl From<&Response> for AuthorizationDecision {
+    fn from(response: &Response) -> Self {
+        let diagnostics = response.diagnostics();
+        Self {
+            decision: response.decision(),
+            determining_policies: diagnostics.reason().map(|id| id.to_string()).collect(),
+            errors: diagnostics
+                .errors()
+                .map(|e| match e {
+                    cedar_policy::AuthorizationError::PolicyEvaluationError(err) => EvaluationError {
+                        policy_id: err.policy_id().to_string(),
+                        message: e.to_string(),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
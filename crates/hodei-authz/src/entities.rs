@@ -0,0 +1,319 @@
+//! Loading of entities related to a request
+//!
+//! A request only carries the principal and the resource, but policies often
+//! reach further: `resource.owner.department`, `principal in Group::"admins"`.
+//! An [`EntityProvider`] supplies those entities and an [`EntityResolver`]
+//! follows references and parents from the request entities up to a depth.
+
+use async_trait::async_trait;
+use cedar_policy::{Entity, EntityUid};
+use hodei_hrn::Hrn;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Errors reported by entity providers
+#[derive(Debug, Error)]
+pub enum EntityProviderError {
+    #[error("Backend error: {0}")]
+    Backend(String),
+    #[error("Invalid entity: {0}")]
+    Invalid(String),
+}
+
+/// Source of entities referenced by policies
+///
+/// Entities are identified by their Cedar uid, whose id is the entity HRN for
+/// types mapped by the derives (see [`hrn_of`]).
+#[async_trait]
+pub trait EntityProvider: Send + Sync {
+    /// Load the entities with the given uids
+    ///
+    /// [`EntityResolver`] only passes uids of a single type per call, so an
+    /// implementation can fetch them with one query. Unknown uids are left out
+    /// of the result.
+    async fn load_many(&self, uids: &[EntityUid]) -> Result<Vec<Entity>, EntityProviderError>;
+
+    /// Load a single entity
+    async fn load(&self, uid: &EntityUid) -> Result<Option<Entity>, EntityProviderError> {
+        Ok(self
+            .load_many(std::slice::from_ref(uid))
+            .await?
+            .into_iter()
+            .find(|entity| entity.uid() == *uid))
+    }
+}
+
+#[async_trait]
+impl<T: EntityProvider + ?Sized> EntityProvider for Arc<T> {
+    async fn load_many(&self, uids: &[EntityUid]) -> Result<Vec<Entity>, EntityProviderError> {
+        (**self).load_many(uids).await
+    }
+
+    async fn load(&self, uid: &EntityUid) -> Result<Option<Entity>, EntityProviderError> {
+        (**self).load(uid).await
+    }
+}
+
+/// HRN used as id of `uid`, if it is one
+pub fn hrn_of(uid: &EntityUid) -> Option<Hrn> {
+    uid.id().unescaped().parse().ok()
+}
+
+/// Follows entity references and parents through an [`EntityProvider`]
+///
+/// Each entity is requested at most once per resolver, so a resolver shared
+/// by the checks of one request (or batch) memoizes the loads, and reference
+/// cycles end as soon as they reach an entity already seen.
+pub struct EntityResolver<'a> {
+    provider: &'a dyn EntityProvider,
+    max_depth: usize,
+    /// Every uid requested or given, with the entity if it exists
+    seen: HashMap<EntityUid, Option<Entity>>,
+}
+
+impl<'a> EntityResolver<'a> {
+    /// Resolver following references up to `max_depth` hops from the roots
+    pub fn new(provider: &'a dyn EntityProvider, max_depth: usize) -> Self {
+        Self {
+            provider,
+            max_depth,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// `roots` followed by the entities they reach
+    ///
+    /// The roots keep their attributes, but gain the parents the provider
+    /// knows for them (e.g. stored group memberships). Entities already
+    /// returned by a previous call on this resolver are included again when
+    /// reachable, without asking the provider.
+    pub async fn resolve(
+        &mut self,
+        roots: Vec<Entity>,
+    ) -> Result<Vec<Entity>, EntityProviderError> {
+        if self.max_depth == 0 {
+            return Ok(roots);
+        }
+        let root_uids: Vec<EntityUid> = roots.iter().map(Entity::uid).collect();
+        self.load_missing(&root_uids).await?;
+
+        let mut result = Vec::new();
+        let mut included = HashSet::new();
+        let mut frontier = Vec::new();
+
+        for root in roots {
+            let uid = root.uid();
+            if included.insert(uid.clone()) {
+                let root = match self.seen.get(&uid) {
+                    Some(Some(stored)) => with_parents_of(root, stored)?,
+                    _ => root,
+                };
+                frontier.extend(references(&root)?);
+                result.push(root);
+            }
+        }
+
+        for _ in 0..self.max_depth {
+            frontier.retain(|uid| !included.contains(uid));
+            if frontier.is_empty() {
+                break;
+            }
+            self.load_missing(&frontier).await?;
+
+            let mut next = Vec::new();
+            for uid in frontier.drain(..) {
+                if !included.insert(uid.clone()) {
+                    continue;
+                }
+                if let Some(Some(entity)) = self.seen.get(&uid) {
+                    next.extend(references(entity)?);
+                    result.push(entity.clone());
+                }
+            }
+            frontier = next;
+        }
+
+        Ok(result)
+    }
+
+    /// Ask the provider for the uids not seen yet, one call per type
+    async fn load_missing(&mut self, uids: &[EntityUid]) -> Result<(), EntityProviderError> {
+        let mut by_type: BTreeMap<String, Vec<EntityUid>> = BTreeMap::new();
+        for uid in uids {
+            if !self.seen.contains_key(uid) {
+                let pending = by_type.entry(uid.type_name().to_string()).or_default();
+                if !pending.contains(uid) {
+                    pending.push(uid.clone());
+                }
+            }
+        }
+
+        for pending in by_type.into_values() {
+            let loaded = self.provider.load_many(&pending).await?;
+            for uid in pending {
+                self.seen.insert(uid, None);
+            }
+            for entity in loaded {
+                self.seen.insert(entity.uid(), Some(entity));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `entity` with the parents of `stored` added to its own
+fn with_parents_of(entity: Entity, stored: &Entity) -> Result<Entity, EntityProviderError> {
+    let (uid, attrs, mut parents) = entity.into_inner();
+    let (_, _, stored_parents) = stored.clone().into_inner();
+    parents.extend(stored_parents);
+    Entity::new(uid, attrs, parents).map_err(|e| EntityProviderError::Invalid(e.to_string()))
+}
+
+/// Uids of the parents of `entity` and of the entities its attributes reference
+fn references(entity: &Entity) -> Result<Vec<EntityUid>, EntityProviderError> {
+    let json = entity
+        .to_json_value()
+        .map_err(|e| EntityProviderError::Invalid(e.to_string()))?;
+
+    let mut uids = Vec::new();
+    for parent in json.get("parents").and_then(Value::as_array).into_iter().flatten() {
+        uids.extend(uid_from_json(parent));
+    }
+    if let Some(attrs) = json.get("attrs") {
+        collect_entity_refs(attrs, &mut uids);
+    }
+    Ok(uids)
+}
+
+/// Entity references are written as `{"__entity": {"type": .., "id": ..}}`,
+/// possibly nested in sets and records
+fn collect_entity_refs(value: &Value, uids: &mut Vec<EntityUid>) {
+    match value {
+        Value::Object(obj) => match obj.get("__entity") {
+            Some(entity) => uids.extend(uid_from_json(entity)),
+            None => obj.values().for_each(|v| collect_entity_refs(v, uids)),
+        },
+        Value::Array(items) => items.iter().for_each(|v| collect_entity_refs(v, uids)),
+        _ => {}
+    }
+}
+
+fn uid_from_json(value: &Value) -> Option<EntityUid> {
+    let type_name = value.get("type")?.as_str()?.parse().ok()?;
+    let id = value.get("id")?.as_str()?.parse().ok()?;
+    Some(EntityUid::from_type_name_and_id(type_name, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedar_policy::RestrictedExpression;
+    use std::sync::Mutex;
+
+    /// In-memory provider recording every `load_many` call
+    #[derive(Default)]
+    struct MapProvider {
+        entities: HashMap<EntityUid, Entity>,
+        calls: Mutex<Vec<Vec<EntityUid>>>,
+    }
+
+    impl MapProvider {
+        fn with(mut self, entity: Entity) -> Self {
+            self.entities.insert(entity.uid(), entity);
+            self
+        }
+    }
+
+    #[async_trait]
+    impl EntityProvider for MapProvider {
+        async fn load_many(&self, uids: &[EntityUid]) -> Result<Vec<Entity>, EntityProviderError> {
+            self.calls.lock().unwrap().push(uids.to_vec());
+            Ok(uids.iter().filter_map(|uid| self.entities.get(uid).cloned()).collect())
+        }
+    }
+
+    fn uid(type_name: &str, id: &str) -> EntityUid {
+        format!(r#"{}::"{}""#, type_name, id).parse().unwrap()
+    }
+
+    /// User with an optional `manager` and groups as parents
+    fn user(id: &str, manager: Option<&str>, groups: &[&str]) -> Entity {
+        let attrs = manager
+            .map(|m| ("manager".to_string(), RestrictedExpression::new_entity_uid(uid("User", m))))
+            .into_iter()
+            .collect();
+        let parents = groups.iter().map(|g| uid("Group", g)).collect();
+        Entity::new(uid("User", id), attrs, parents).unwrap()
+    }
+
+    fn uids(entities: &[Entity]) -> Vec<EntityUid> {
+        entities.iter().map(Entity::uid).collect()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_follows_references_and_parents() {
+        let provider = MapProvider::default()
+            .with(user("bob", Some("carol"), &[]))
+            .with(user("carol", Some("dave"), &[]))
+            .with(Entity::with_uid(uid("Group", "admins")));
+        let mut resolver = EntityResolver::new(&provider, 2);
+
+        let resolved = resolver
+            .resolve(vec![user("alice", Some("bob"), &["admins"])])
+            .await
+            .unwrap();
+
+        // dave is three hops away
+        assert_eq!(
+            uids(&resolved),
+            vec![
+                uid("User", "alice"),
+                uid("Group", "admins"),
+                uid("User", "bob"),
+                uid("User", "carol"),
+            ]
+        );
+        // One call for the roots, then one per type and level
+        assert_eq!(provider.calls.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_stops_on_cycles_and_memoizes() {
+        let provider = MapProvider::default()
+            .with(user("bob", Some("alice"), &[]))
+            .with(user("alice", Some("bob"), &[]));
+        let mut resolver = EntityResolver::new(&provider, 10);
+
+        let resolved = resolver
+            .resolve(vec![user("alice", Some("bob"), &[])])
+            .await
+            .unwrap();
+        assert_eq!(uids(&resolved), vec![uid("User", "alice"), uid("User", "bob")]);
+
+        let resolved = resolver
+            .resolve(vec![
+                Entity::with_uid(uid("Doc", "d1")),
+                user("alice", Some("bob"), &[]),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(uids(&resolved).len(), 3);
+        // alice, bob and then only the unknown Doc
+        assert_eq!(provider.calls.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_roots_gain_stored_parents() {
+        let provider = MapProvider::default()
+            .with(user("alice", None, &["admins"]))
+            .with(Entity::with_uid(uid("Group", "admins")));
+        let mut resolver = EntityResolver::new(&provider, 1);
+
+        let resolved = resolver.resolve(vec![user("alice", None, &[])]).await.unwrap();
+
+        assert_eq!(uids(&resolved), vec![uid("User", "alice"), uid("Group", "admins")]);
+        assert_eq!(references(&resolved[0]).unwrap(), vec![uid("Group", "admins")]);
+    }
+}
@@ -1,12 +1,40 @@
 pub mod api;
+pub mod audit;
+pub mod decision;
+pub mod entities;
+pub mod records;
+pub mod residual;
+pub mod templates;
 pub mod traits;
+pub mod validation;
+#[cfg(feature = "in-memory")]
+pub mod memory;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use hodei_hrn;
-pub use hodei_derive::{HodeiAction, HodeiEntity};
+pub use hodei_derive::{HodeiAction, HodeiContext, HodeiEntity, HodeiRecord, HodeiVirtualEntity};
 pub use inventory;
 
-pub use api::{EntitySchemaFragment, ActionSchemaFragment, RuntimeHodeiEntityMapper, RuntimeHodeiActionMapper};
-pub use traits::{PolicyStore, CacheInvalidation, PolicyStoreError, CacheError};
+pub use api::{
+    EntitySchemaFragment, ActionSchemaFragment, RecordSchemaFragment, RuntimeHodeiEntityMapper,
+    RuntimeHodeiActionMapper, HodeiContext, HodeiRecord, HodeiVirtualEntity, VirtualEntityContext,
+    VirtualEntityError,
+};
+pub use audit::{AuditError, AuditEvent, AuditSink};
+pub use decision::{AuthorizationDecision, AuthorizationError, EvaluationError};
+pub use entities::{hrn_of, EntityProvider, EntityProviderError, EntityResolver};
+pub use traits::{
+    AuthorizationEvaluator, CacheError, CacheInvalidation, NoopCacheInvalidation, PolicyChange,
+    PolicyLoadReport, PolicyStore, PolicyStoreError, PolicyVersion, RejectedPolicy,
+};
+pub use records::{PolicyFilter, PolicyMetadata, PolicyPage, PolicyRecord};
+pub use residual::{CompareOp, ResidualExpr, ResidualFilter, ResidualPolicy, ResidualValue};
+pub use templates::{LinkedEntity, TemplateLink};
+pub use validation::{validate_policy, validate_template, PolicyDiagnostic, SourceSpan};
+#[cfg(feature = "in-memory")]
+pub use memory::InMemoryPolicyStore;
 
 inventory::collect!(api::EntitySchemaFragment);
-inventory::collect!(api::ActionSchemaFragment);
\ No newline at end of file
+inventory::collect!(api::ActionSchemaFragment);
+inventory::collect!(api::RecordSchemaFragment);
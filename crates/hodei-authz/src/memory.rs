@@ -0,0 +1,624 @@
+//! In-memory policy store
+//!
+//! [`InMemoryPolicyStore`] keeps policies in process memory. It is meant for
+//! tests and single-binary deployments that do not need a database.
+
+use crate::templates::{add_templates, check_link, parse_template, LinkedEntity, TemplateLink};
+use crate::traits::{PolicyStore, PolicyStoreError};
+use crate::validation::{validate_policy, validate_template};
+use async_trait::async_trait;
+use cedar_policy::{Policy, PolicyId, PolicySet, Schema, Validator};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// Policy store backed by process memory
+///
+/// Content is parsed on every write, so invalid Cedar is rejected immediately
+/// instead of failing later in `load_all_policies`. With
+/// [`with_schema`](Self::with_schema) writes are also validated against the
+/// schema. Each stored entry holds exactly one policy, either global or
+/// scoped to a tenant.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let store = InMemoryPolicyStore::from_dir("./policies")?;
+/// let id = store.create_policy(r#"permit(principal, action, resource);"#.into()).await?;
+/// ```
+#[derive(Default)]
+pub struct InMemoryPolicyStore {
+    /// Policies in creation order
+    policies: RwLock<Vec<StoredPolicy>>,
+    /// (id, content) of templates in creation order
+    templates: RwLock<Vec<(String, String)>>,
+    links: RwLock<Vec<TemplateLink>>,
+    validator: Option<Validator>,
+}
+
+struct StoredPolicy {
+    id: String,
+    content: String,
+    /// `None` for global policies
+    tenant_id: Option<String>,
+}
+
+impl InMemoryPolicyStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a store seeded with every `.cedar` file in `dir`
+    ///
+    /// Files are read in name order. See [`policies_from_source`] for how ids
+    /// are assigned.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, PolicyStoreError> {
+        Self::new().seeded_from_dir(dir.as_ref())
+    }
+
+    /// Like [`from_dir`](Self::from_dir), validating the policies and every
+    /// subsequent write against `schema`
+    pub fn from_dir_with_schema(
+        dir: impl AsRef<Path>,
+        schema: Schema,
+    ) -> Result<Self, PolicyStoreError> {
+        Self::new().with_schema(schema).seeded_from_dir(dir.as_ref())
+    }
+
+    fn seeded_from_dir(self, dir: &Path) -> Result<Self, PolicyStoreError> {
+        for (id, content) in read_policy_dir(dir)? {
+            self.insert(id, content)?;
+        }
+        Ok(self)
+    }
+
+    /// Validate every subsequent write against `schema`
+    ///
+    /// Policies already in the store are not checked; use
+    /// [`from_dir_with_schema`](Self::from_dir_with_schema) to validate
+    /// policies read from a directory.
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.validator = Some(Validator::new(schema));
+        self
+    }
+
+    /// Insert a policy with a caller-provided id
+    ///
+    /// Fails if the content is not a single valid policy or the id is taken.
+    pub fn insert(&self, id: String, content: String) -> Result<(), PolicyStoreError> {
+        self.insert_scoped(id, content, None)
+    }
+
+    /// Insert a policy scoped to `tenant_id` with a caller-provided id
+    ///
+    /// Ids are shared by every scope, so an id already used by a global
+    /// policy or by another tenant's is rejected with `AlreadyExists`.
+    pub fn insert_for_tenant(
+        &self,
+        tenant_id: &str,
+        id: String,
+        content: String,
+    ) -> Result<(), PolicyStoreError> {
+        self.insert_scoped(id, content, Some(tenant_id.to_string()))
+    }
+
+    fn insert_scoped(
+        &self,
+        id: String,
+        content: String,
+        tenant_id: Option<String>,
+    ) -> Result<(), PolicyStoreError> {
+        self.check_policy(&id, &content)?;
+
+        let mut policies = self.policies.write().unwrap();
+        if policies.iter().any(|policy| policy.id == id) {
+            return Err(PolicyStoreError::AlreadyExists(id));
+        }
+        policies.push(StoredPolicy {
+            id,
+            content,
+            tenant_id,
+        });
+        Ok(())
+    }
+
+    /// (id, content) of the policies in one scope, `None` being global
+    fn scoped_policies(&self, tenant_id: Option<&str>) -> Vec<(String, String)> {
+        self.policies
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|policy| policy.tenant_id.as_deref() == tenant_id)
+            .map(|policy| (policy.id.clone(), policy.content.clone()))
+            .collect()
+    }
+
+    /// Parse the policy and validate it when a schema is configured
+    fn check_policy(&self, id: &str, content: &str) -> Result<(), PolicyStoreError> {
+        let policy = parse_policy(id, content)?;
+        if let Some(validator) = &self.validator {
+            validate_policy(validator, &policy)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse `content` as exactly one static policy with the given id
+fn parse_policy(id: &str, content: &str) -> Result<Policy, PolicyStoreError> {
+    let set = PolicySet::from_str(content).map_err(|e| PolicyStoreError::Parse(e.to_string()))?;
+    if set.policies().count() != 1 || set.templates().count() != 0 {
+        return Err(PolicyStoreError::Parse(format!(
+            "Policy {} must contain exactly one static policy",
+            id
+        )));
+    }
+
+    Policy::parse(Some(PolicyId::new(id)), content)
+        .map_err(|e| PolicyStoreError::Parse(e.to_string()))
+}
+
+/// Build a `PolicySet` from stored `(id, content)` pairs
+fn policy_set(policies: Vec<(String, String)>) -> Result<PolicySet, PolicyStoreError> {
+    let parsed = policies
+        .iter()
+        .map(|(id, content)| parse_policy(id, content))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    PolicySet::from_policies(parsed).map_err(|e| PolicyStoreError::Internal(e.to_string()))
+}
+
+/// Split Cedar source into `(id, content)` pairs, one per policy
+///
+/// A policy annotated with `@id("...")` keeps that id. Otherwise the id is
+/// `name` for single-policy sources and `name-<n>` (in source order) when the
+/// source holds several policies.
+pub fn policies_from_source(
+    name: &str,
+    source: &str,
+) -> Result<Vec<(String, String)>, PolicyStoreError> {
+    // Parsed as a whole first, for error messages with positions in the file
+    PolicySet::from_str(source)
+        .map_err(|e| PolicyStoreError::Parse(format!("{}: {}", name, e)))?;
+
+    let mut policies = Vec::new();
+    for statement in split_statements(source) {
+        let set = PolicySet::from_str(statement)
+            .map_err(|e| PolicyStoreError::Parse(format!("{}: {}", name, e)))?;
+        policies.extend(set.policies().cloned());
+    }
+
+    let single = policies.len() == 1;
+    Ok(policies
+        .into_iter()
+        .enumerate()
+        .map(|(n, policy)| {
+            let id = match policy.annotation("id") {
+                Some(id) => id.to_string(),
+                None if single => name.to_string(),
+                None => format!("{}-{}", name, n),
+            };
+            (id, policy.to_string())
+        })
+        .collect())
+}
+
+/// Split Cedar source after the `;` ending each statement, in source order
+///
+/// Semicolons inside string literals and `//` comments are skipped. Text after
+/// the last statement (whitespace, comments) is dropped.
+fn split_statements(source: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                statements.push(&source[start..=i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements
+}
+
+/// Read every `.cedar` file in `dir`, in name order, as `(id, content)` pairs
+pub fn read_policy_dir(dir: &Path) -> Result<Vec<(String, String)>, PolicyStoreError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        PolicyStoreError::Internal(format!("Cannot read {}: {}", dir.display(), e))
+    })?;
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "cedar"))
+        .collect();
+    paths.sort();
+
+    let mut policies = Vec::new();
+    for path in paths {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let source = std::fs::read_to_string(&path).map_err(|e| {
+            PolicyStoreError::Internal(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        policies.extend(policies_from_source(&name, &source)?);
+    }
+    Ok(policies)
+}
+
+#[async_trait]
+impl PolicyStore for InMemoryPolicyStore {
+    async fn create_policy(&self, content: String) -> Result<String, PolicyStoreError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.insert(id.clone(), content)?;
+        Ok(id)
+    }
+
+    async fn get_policy(&self, id: &str) -> Result<Option<String>, PolicyStoreError> {
+        let policies = self.policies.read().unwrap();
+        Ok(policies
+            .iter()
+            .find(|policy| policy.id == id)
+            .map(|policy| policy.content.clone()))
+    }
+
+    async fn list_policies(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        Ok(self.scoped_policies(None))
+    }
+
+    async fn update_policy(&self, id: &str, content: String) -> Result<(), PolicyStoreError> {
+        self.check_policy(id, &content)?;
+
+        let mut policies = self.policies.write().unwrap();
+        let entry = policies
+            .iter_mut()
+            .find(|policy| policy.id == id)
+            .ok_or_else(|| PolicyStoreError::NotFound(id.to_string()))?;
+        entry.content = content;
+        Ok(())
+    }
+
+    async fn delete_policy(&self, id: &str) -> Result<(), PolicyStoreError> {
+        let mut policies = self.policies.write().unwrap();
+        let len = policies.len();
+        policies.retain(|policy| policy.id != id);
+        if policies.len() == len {
+            return Err(PolicyStoreError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
+        let mut set = policy_set(self.scoped_policies(None))?;
+        add_templates(
+            &mut set,
+            self.templates.read().unwrap().clone(),
+            self.links.read().unwrap().clone(),
+        )?;
+        Ok(set)
+    }
+
+    async fn create_template(&self, content: String) -> Result<String, PolicyStoreError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let template = parse_template(&id, &content)?;
+        if let Some(validator) = &self.validator {
+            validate_template(validator, &template)?;
+        }
+
+        self.templates.write().unwrap().push((id.clone(), content));
+        Ok(id)
+    }
+
+    async fn list_templates(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        Ok(self.templates.read().unwrap().clone())
+    }
+
+    async fn delete_template(&self, id: &str) -> Result<(), PolicyStoreError> {
+        let mut templates = self.templates.write().unwrap();
+        let len = templates.len();
+        templates.retain(|(existing, _)| existing != id);
+        if templates.len() == len {
+            return Err(PolicyStoreError::NotFound(id.to_string()));
+        }
+
+        self.links
+            .write()
+            .unwrap()
+            .retain(|link| link.template_id != id);
+        Ok(())
+    }
+
+    async fn link_template(
+        &self,
+        template_id: &str,
+        principal: Option<LinkedEntity>,
+        resource: Option<LinkedEntity>,
+    ) -> Result<String, PolicyStoreError> {
+        let content = self
+            .templates
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(existing, _)| existing == template_id)
+            .map(|(_, content)| content.clone())
+            .ok_or_else(|| PolicyStoreError::NotFound(template_id.to_string()))?;
+
+        let link = TemplateLink {
+            link_id: uuid::Uuid::new_v4().to_string(),
+            template_id: template_id.to_string(),
+            principal,
+            resource,
+        };
+        check_link(&parse_template(template_id, &content)?, &link)?;
+
+        let link_id = link.link_id.clone();
+        self.links.write().unwrap().push(link);
+        Ok(link_id)
+    }
+
+    async fn list_template_links(
+        &self,
+        template_id: Option<&str>,
+    ) -> Result<Vec<TemplateLink>, PolicyStoreError> {
+        Ok(self
+            .links
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|link| template_id.is_none_or(|id| link.template_id == id))
+            .cloned()
+            .collect())
+    }
+
+    async fn unlink_template(&self, link_id: &str) -> Result<(), PolicyStoreError> {
+        let mut links = self.links.write().unwrap();
+        let len = links.len();
+        links.retain(|link| link.link_id != link_id);
+        if links.len() == len {
+            return Err(PolicyStoreError::NotFound(link_id.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn create_tenant_policy(
+        &self,
+        tenant_id: &str,
+        content: String,
+    ) -> Result<String, PolicyStoreError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.insert_for_tenant(tenant_id, id.clone(), content)?;
+        Ok(id)
+    }
+
+    async fn list_tenant_policies(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        Ok(self.scoped_policies(Some(tenant_id)))
+    }
+
+    async fn load_tenant_policies(&self, tenant_id: &str) -> Result<PolicySet, PolicyStoreError> {
+        policy_set(self.scoped_policies(Some(tenant_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERMIT_ALL: &str = "permit(principal, action, resource);";
+
+    #[tokio::test]
+    async fn test_create_and_load() {
+        let store = InMemoryPolicyStore::new();
+        let id = store.create_policy(PERMIT_ALL.to_string()).await.unwrap();
+
+        assert_eq!(store.get_policy(&id).await.unwrap(), Some(PERMIT_ALL.to_string()));
+
+        let set = store.load_all_policies().await.unwrap();
+        assert!(set.policy(&PolicyId::new(&id)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_list_policies_contract() {
+        crate::testing::check_list_policies(&InMemoryPolicyStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_content() {
+        let store = InMemoryPolicyStore::new();
+
+        let result = store.create_policy("permit(principal, action".to_string()).await;
+        assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+
+        let two = format!("{}\n{}", PERMIT_ALL, PERMIT_ALL);
+        let result = store.create_policy(two).await;
+        assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+
+        let id = store.create_policy(PERMIT_ALL.to_string()).await.unwrap();
+        let result = store.update_policy(&id, "forbid(".to_string()).await;
+        assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+        assert_eq!(store.get_policy(&id).await.unwrap(), Some(PERMIT_ALL.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_with_schema_rejects_invalid_policy() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity User; action Read appliesTo { principal: User, resource: User };",
+        )
+        .unwrap();
+        let store = InMemoryPolicyStore::new().with_schema(schema);
+
+        let result = store
+            .create_policy(r#"permit(principal is Group, action, resource);"#.to_string())
+            .await;
+        assert!(matches!(result, Err(PolicyStoreError::Validation(_))));
+
+        let valid = r#"permit(principal is User, action == Action::"Read", resource);"#;
+        assert!(store.create_policy(valid.to_string()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_missing() {
+        let store = InMemoryPolicyStore::new();
+        let result = store.update_policy("missing", PERMIT_ALL.to_string()).await;
+        assert!(matches!(result, Err(PolicyStoreError::NotFound(_))));
+        let result = store.delete_policy("missing").await;
+        assert!(matches!(result, Err(PolicyStoreError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_templates_and_links() {
+        let store = InMemoryPolicyStore::new();
+        let template_id = store
+            .create_template(
+                "permit(principal == ?principal, action, resource == ?resource);".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let hrn = |resource: &str| {
+            hodei_hrn::Hrn::builder()
+                .service("docs")
+                .tenant_id("tenant-1")
+                .resource(resource)
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+        let link_id = store
+            .link_template(
+                &template_id,
+                Some(LinkedEntity::new("User", hrn("user/alice"))),
+                Some(LinkedEntity::new("Document", hrn("document/1"))),
+            )
+            .await
+            .unwrap();
+
+        let set = store.load_all_policies().await.unwrap();
+        assert!(set.policy(&PolicyId::new(&link_id)).is_some());
+
+        // Falta el slot ?resource
+        let result = store
+            .link_template(&template_id, Some(LinkedEntity::new("User", hrn("user/bob"))), None)
+            .await;
+        assert!(matches!(result, Err(PolicyStoreError::Parse(_))));
+
+        store.delete_template(&template_id).await.unwrap();
+        assert!(store.list_template_links(None).await.unwrap().is_empty());
+        let set = store.load_all_policies().await.unwrap();
+        assert_eq!(set.policies().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_policies_are_scoped() {
+        let store = InMemoryPolicyStore::new();
+        let global = store.create_policy(PERMIT_ALL.to_string()).await.unwrap();
+        let acme = store
+            .create_tenant_policy("acme", "forbid(principal, action, resource);".to_string())
+            .await
+            .unwrap();
+
+        let global_ids: Vec<String> = store
+            .list_policies()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(global_ids, vec![global.clone()]);
+
+        let set = store.load_tenant_policies("acme").await.unwrap();
+        assert!(set.policy(&PolicyId::new(&acme)).is_some());
+        assert!(set.policy(&PolicyId::new(&global)).is_none());
+        assert!(store.list_tenant_policies("globex").await.unwrap().is_empty());
+
+        // Las operaciones por id no dependen del tenant
+        assert!(store.get_policy(&acme).await.unwrap().is_some());
+        store.delete_policy(&acme).await.unwrap();
+        assert!(store.list_tenant_policies("acme").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_policies_from_source_ids() {
+        let source = r#"
+            @id("admins")
+            permit(principal, action, resource) when { principal.role == "admin" };
+            forbid(principal, action, resource) when { resource.locked };
+        "#;
+        let policies = policies_from_source("documents", source).unwrap();
+        let ids: Vec<&str> = policies.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["admins", "documents-1"]);
+
+        let single = policies_from_source("public", PERMIT_ALL).unwrap();
+        assert_eq!(single[0].0, "public");
+    }
+
+    #[test]
+    fn test_policies_from_source_keeps_order() {
+        let source = r#"
+            // first; the comment has a semicolon
+            permit(principal, action, resource) when { resource.name == "a;b" };
+            forbid(principal, action, resource) when { resource.locked };
+            permit(principal, action, resource) when { resource.public };
+        "#;
+        let policies = policies_from_source("documents", source).unwrap();
+        assert_eq!(policies.len(), 3);
+        assert!(policies[0].1.contains("a;b"));
+        assert!(policies[1].1.starts_with("forbid"));
+        assert!(policies[2].1.contains("resource.public"));
+        assert_eq!(policies[2].0, "documents-2");
+    }
+
+    #[tokio::test]
+    async fn test_from_dir() {
+        let dir = std::env::temp_dir().join(format!("hodei-policies-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.cedar"), PERMIT_ALL).unwrap();
+        std::fs::write(dir.join("a.cedar"), "forbid(principal, action, resource);").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let store = InMemoryPolicyStore::from_dir(&dir).unwrap();
+        let ids: Vec<String> = store
+            .list_policies()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, vec!["a".to_string(), "b".to_string()]);
+
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity User; action Read appliesTo { principal: User, resource: User };",
+        )
+        .unwrap();
+        let invalid = "permit(principal is Group, action, resource);";
+        std::fs::write(dir.join("c.cedar"), invalid).unwrap();
+        let result = InMemoryPolicyStore::from_dir_with_schema(&dir, schema);
+        assert!(matches!(result, Err(PolicyStoreError::Validation(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
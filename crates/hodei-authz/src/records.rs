@@ -0,0 +1,205 @@
+//! Policy metadata for administration
+//!
+//! `PolicyStore::list_policies` only returns `(id, content)` pairs, which is
+//! what evaluation needs. [`PolicyRecord`] adds the descriptive fields admins
+//! use to find, label and temporarily disable policies.
+
+use cedar_policy::Effect;
+use std::time::SystemTime;
+
+/// Descriptive fields attached to a policy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyMetadata {
+    /// Short human readable name
+    pub name: Option<String>,
+    /// Longer explanation of what the policy is for
+    pub description: Option<String>,
+    /// Free-form labels, e.g. `["billing", "temporary"]`
+    pub tags: Vec<String>,
+    /// Team or user responsible for the policy
+    pub owner: Option<String>,
+}
+
+/// A stored policy with its metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyRecord {
+    /// ID of the policy
+    pub id: String,
+    /// Cedar source of the policy
+    pub content: String,
+    /// Tenant the policy is scoped to, `None` for global policies
+    pub tenant_id: Option<String>,
+    /// Descriptive fields
+    pub metadata: PolicyMetadata,
+    /// Disabled policies are kept but left out of `load_all_policies`
+    pub enabled: bool,
+    /// Whether the policy permits or forbids, when it could be determined
+    pub effect: Option<Effect>,
+    /// Current version (see `PolicyStore::policy_history`)
+    pub version: i64,
+    /// When the policy was created
+    pub created_at: SystemTime,
+    /// When the policy was last changed, if ever
+    pub updated_at: Option<SystemTime>,
+}
+
+/// Criteria for `PolicyStore::list_policy_records`
+///
+/// Every criterion left as `None` (or empty) matches all policies.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let page = store
+///     .list_policy_records(
+///         &PolicyFilter::default()
+///             .with_tag("billing")
+///             .with_enabled(false)
+///             .page(0, 20),
+///     )
+///     .await?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyFilter {
+    /// Only policies scoped to this tenant
+    pub tenant_id: Option<String>,
+    /// Case-insensitive substring of the name
+    pub name_contains: Option<String>,
+    /// Policies must carry every one of these tags
+    pub tags: Vec<String>,
+    /// Exact owner
+    pub owner: Option<String>,
+    /// Only enabled (`true`) or disabled (`false`) policies
+    pub enabled: Option<bool>,
+    /// Only permit or forbid policies
+    pub effect: Option<Effect>,
+    /// Number of matching records to skip
+    pub offset: usize,
+    /// Maximum number of records to return
+    pub limit: Option<usize>,
+}
+
+impl PolicyFilter {
+    /// Match policies scoped to `tenant_id`
+    pub fn with_tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    /// Match names containing `name`, ignoring case
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name_contains = Some(name.into());
+        self
+    }
+
+    /// Require `tag`; may be called several times
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Match policies owned by `owner`
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Match only enabled or only disabled policies
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    /// Match only policies with `effect`
+    pub fn with_effect(mut self, effect: Effect) -> Self {
+        self.effect = Some(effect);
+        self
+    }
+
+    /// Return at most `limit` records after skipping `offset`
+    pub fn page(mut self, offset: usize, limit: usize) -> Self {
+        self.offset = offset;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns true if `record` meets every criterion (pagination aside)
+    ///
+    /// Stores that cannot filter natively can use this on each record.
+    pub fn matches(&self, record: &PolicyRecord) -> bool {
+        let name_matches = self.name_contains.as_ref().is_none_or(|needle| {
+            record
+                .metadata
+                .name
+                .as_ref()
+                .is_some_and(|name| name.to_lowercase().contains(&needle.to_lowercase()))
+        });
+
+        name_matches
+            && self
+                .tenant_id
+                .as_ref()
+                .is_none_or(|tenant_id| record.tenant_id.as_ref() == Some(tenant_id))
+            && self.tags.iter().all(|tag| record.metadata.tags.contains(tag))
+            && self
+                .owner
+                .as_ref()
+                .is_none_or(|owner| record.metadata.owner.as_ref() == Some(owner))
+            && self.enabled.is_none_or(|enabled| record.enabled == enabled)
+            && self.effect.is_none_or(|effect| record.effect == Some(effect))
+    }
+}
+
+/// A page of `PolicyRecord`s
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyPage {
+    /// Records in this page
+    pub records: Vec<PolicyRecord>,
+    /// Number of records matching the filter across all pages
+    pub total: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, tags: &[&str], enabled: bool, effect: Effect) -> PolicyRecord {
+        PolicyRecord {
+            id: name.to_string(),
+            content: String::new(),
+            tenant_id: None,
+            metadata: PolicyMetadata {
+                name: Some(name.to_string()),
+                description: None,
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                owner: Some("security".to_string()),
+            },
+            enabled,
+            effect: Some(effect),
+            version: 1,
+            created_at: SystemTime::now(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let admins = record("Admins full access", &["core"], true, Effect::Permit);
+        let freeze = record("Billing freeze", &["billing", "temporary"], false, Effect::Forbid);
+
+        assert!(PolicyFilter::default().matches(&admins));
+        assert!(PolicyFilter::default().with_name("ADMINS").matches(&admins));
+        assert!(!PolicyFilter::default().with_name("billing").matches(&admins));
+
+        let filter = PolicyFilter::default().with_tag("billing").with_tag("temporary");
+        assert!(filter.matches(&freeze));
+        assert!(!filter.matches(&admins));
+
+        assert!(PolicyFilter::default().with_enabled(false).matches(&freeze));
+        assert!(!PolicyFilter::default().with_enabled(false).matches(&admins));
+        assert!(PolicyFilter::default().with_effect(Effect::Forbid).matches(&freeze));
+        assert!(PolicyFilter::default().with_owner("security").matches(&freeze));
+        assert!(!PolicyFilter::default().with_owner("platform").matches(&freeze));
+        assert!(!PolicyFilter::default().with_tenant("tenant-1").matches(&freeze));
+    }
+}
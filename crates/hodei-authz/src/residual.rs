@@ -0,0 +1,452 @@
+//! Residual conditions from partial evaluation
+//!
+//! Evaluating a request with the resource left unknown yields, for each policy
+//! that may still apply, the condition the resource has to meet. List
+//! endpoints can turn those conditions into a database filter instead of
+//! authorizing every row.
+//!
+//! Only the shapes a database can express are modelled ([`ResidualExpr`]);
+//! anything else is kept as [`ResidualExpr::Unsupported`] so translators can
+//! fall back to checking those rows one by one.
+
+use cedar_policy::{Decision, Effect, PartialResponse, Policy};
+use serde_json::Value;
+
+/// A literal in a residual condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResidualValue {
+    Bool(bool),
+    Long(i64),
+    String(String),
+    /// Entity reference; `id` is the HRN for entities mapped by the derives
+    Entity { type_name: String, id: String },
+}
+
+/// Comparison operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+}
+
+/// Condition over the unknown resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResidualExpr {
+    /// A known value, e.g. the principal after substitution
+    Value(ResidualValue),
+    /// The resource itself
+    Resource,
+    /// `expr.attr`
+    Attr(Box<ResidualExpr>, String),
+    /// `expr has attr`
+    Has(Box<ResidualExpr>, String),
+    /// The resource belongs to this tenant (the tenant part of its HRN)
+    ///
+    /// Not produced by Cedar; added by [`ResidualFilter::within_tenant`].
+    InTenant(String),
+    Compare(CompareOp, Box<ResidualExpr>, Box<ResidualExpr>),
+    And(Box<ResidualExpr>, Box<ResidualExpr>),
+    Or(Box<ResidualExpr>, Box<ResidualExpr>),
+    Not(Box<ResidualExpr>),
+    /// Any other expression, as Cedar JSON (EST)
+    Unsupported(String),
+}
+
+impl ResidualExpr {
+    /// Convert a Cedar JSON (EST) expression
+    pub fn from_json(expr: &Value) -> Self {
+        let Some((key, body)) = expr.as_object().and_then(|obj| {
+            let mut entries = obj.iter();
+            match (entries.next(), entries.next()) {
+                (Some(entry), None) => Some(entry),
+                _ => None,
+            }
+        }) else {
+            return Self::Unsupported(expr.to_string());
+        };
+
+        let parsed = match key.as_str() {
+            "Value" => value_from_json(body).map(Self::Value),
+            "unknown" if is_unknown_resource(body) => Some(Self::Resource),
+            "." => attr_from_json(body).map(|(left, attr)| Self::Attr(Box::new(left), attr)),
+            "has" => attr_from_json(body).map(|(left, attr)| Self::Has(Box::new(left), attr)),
+            "==" => binary_from_json(body, |l, r| Self::Compare(CompareOp::Eq, l, r)),
+            "!=" => binary_from_json(body, |l, r| Self::Compare(CompareOp::NotEq, l, r)),
+            "<" => binary_from_json(body, |l, r| Self::Compare(CompareOp::Less, l, r)),
+            "<=" => binary_from_json(body, |l, r| Self::Compare(CompareOp::LessEq, l, r)),
+            ">" => binary_from_json(body, |l, r| Self::Compare(CompareOp::Greater, l, r)),
+            ">=" => binary_from_json(body, |l, r| Self::Compare(CompareOp::GreaterEq, l, r)),
+            "&&" => binary_from_json(body, |l, r| and(*l, *r)),
+            "||" => binary_from_json(body, Self::Or),
+            "!" => body.get("arg").map(|arg| Self::Not(Box::new(Self::from_json(arg)))),
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| Self::Unsupported(expr.to_string()))
+    }
+
+    /// Returns true if the whole expression is modelled
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::Value(_) | Self::Resource | Self::InTenant(_) => true,
+            Self::Attr(expr, _) | Self::Has(expr, _) | Self::Not(expr) => expr.is_supported(),
+            Self::Compare(_, left, right) | Self::And(left, right) | Self::Or(left, right) => {
+                left.is_supported() && right.is_supported()
+            }
+            Self::Unsupported(_) => false,
+        }
+    }
+}
+
+fn value_from_json(value: &Value) -> Option<ResidualValue> {
+    match value {
+        Value::Bool(b) => Some(ResidualValue::Bool(*b)),
+        Value::Number(n) => n.as_i64().map(ResidualValue::Long),
+        Value::String(s) => Some(ResidualValue::String(s.clone())),
+        Value::Object(obj) => {
+            let entity = obj.get("__entity")?;
+            Some(ResidualValue::Entity {
+                type_name: entity.get("type")?.as_str()?.to_string(),
+                id: entity.get("id")?.as_str()?.to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Partial evaluation represents the unknown resource as `unknown("resource")`
+fn is_unknown_resource(args: &Value) -> bool {
+    args.as_array()
+        .and_then(|args| args.first())
+        .and_then(|arg| arg.get("Value"))
+        .and_then(Value::as_str)
+        == Some("resource")
+}
+
+fn attr_from_json(body: &Value) -> Option<(ResidualExpr, String)> {
+    let left = ResidualExpr::from_json(body.get("left")?);
+    let attr = body.get("attr")?.as_str()?.to_string();
+    Some((left, attr))
+}
+
+fn binary_from_json(
+    body: &Value,
+    build: impl FnOnce(Box<ResidualExpr>, Box<ResidualExpr>) -> ResidualExpr,
+) -> Option<ResidualExpr> {
+    let left = ResidualExpr::from_json(body.get("left")?);
+    let right = ResidualExpr::from_json(body.get("right")?);
+    Some(build(Box::new(left), Box::new(right)))
+}
+
+/// `left && right`, dropping literal `true`s and short-circuiting on `false`
+///
+/// Partial evaluation leaves the satisfied scope checks as `true && ...`.
+fn and(left: ResidualExpr, right: ResidualExpr) -> ResidualExpr {
+    match (left, right) {
+        (ResidualExpr::Value(ResidualValue::Bool(true)), expr)
+        | (expr, ResidualExpr::Value(ResidualValue::Bool(true))) => expr,
+        (ResidualExpr::Value(ResidualValue::Bool(false)), _)
+        | (_, ResidualExpr::Value(ResidualValue::Bool(false))) => {
+            ResidualExpr::Value(ResidualValue::Bool(false))
+        }
+        (left, right) => ResidualExpr::And(Box::new(left), Box::new(right)),
+    }
+}
+
+/// A policy that may still apply, reduced to its condition on the resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidualPolicy {
+    /// ID of the original policy
+    pub id: String,
+    pub effect: Effect,
+    /// Condition the resource must meet for the policy to apply
+    pub condition: ResidualExpr,
+}
+
+impl ResidualPolicy {
+    /// Reduce a residual policy returned by partial evaluation
+    pub fn from_policy(policy: &Policy) -> Self {
+        let condition = match policy.to_json() {
+            Ok(json) => condition_from_json(&json),
+            Err(e) => ResidualExpr::Unsupported(e.to_string()),
+        };
+
+        Self {
+            id: policy.id().to_string(),
+            effect: policy.effect(),
+            condition,
+        }
+    }
+}
+
+/// Combine the scope and the `when`/`unless` clauses of a policy
+fn condition_from_json(policy: &Value) -> ResidualExpr {
+    let mut parts = Vec::new();
+
+    for var in ["principal", "action"] {
+        match policy.get(var) {
+            Some(scope) if is_unconstrained(scope) => {}
+            scope => parts.push(ResidualExpr::Unsupported(
+                scope.map(Value::to_string).unwrap_or_default(),
+            )),
+        }
+    }
+    match policy.get("resource") {
+        Some(scope) if is_unconstrained(scope) => {}
+        Some(scope) if scope.get("op").and_then(Value::as_str) == Some("==") => {
+            let entity = scope
+                .get("entity")
+                .and_then(|entity| value_from_json(&serde_json::json!({ "__entity": entity })));
+            parts.push(match entity {
+                Some(entity) => ResidualExpr::Compare(
+                    CompareOp::Eq,
+                    Box::new(ResidualExpr::Resource),
+                    Box::new(ResidualExpr::Value(entity)),
+                ),
+                None => ResidualExpr::Unsupported(scope.to_string()),
+            });
+        }
+        scope => parts.push(ResidualExpr::Unsupported(
+            scope.map(Value::to_string).unwrap_or_default(),
+        )),
+    }
+
+    for clause in policy
+        .get("conditions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let body = clause
+            .get("body")
+            .map(ResidualExpr::from_json)
+            .unwrap_or_else(|| ResidualExpr::Unsupported(clause.to_string()));
+        match clause.get("kind").and_then(Value::as_str) {
+            Some("when") => parts.push(body),
+            Some("unless") => parts.push(ResidualExpr::Not(Box::new(body))),
+            _ => parts.push(ResidualExpr::Unsupported(clause.to_string())),
+        }
+    }
+
+    parts
+        .into_iter()
+        .fold(ResidualExpr::Value(ResidualValue::Bool(true)), and)
+}
+
+fn is_unconstrained(scope: &Value) -> bool {
+    scope.get("op").and_then(Value::as_str) == Some("All")
+}
+
+/// What partial evaluation says about the resources of one type
+///
+/// A resource is allowed when it meets the condition of at least one permit
+/// and of no forbid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResidualFilter {
+    /// Every resource is allowed
+    AllowAll,
+    /// No resource is allowed
+    DenyAll,
+    /// It depends on the resource
+    Conditional {
+        permits: Vec<ResidualPolicy>,
+        forbids: Vec<ResidualPolicy>,
+    },
+}
+
+impl ResidualFilter {
+    /// Returns true if every residual condition is modelled
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::AllowAll | Self::DenyAll => true,
+            Self::Conditional { permits, forbids } => permits
+                .iter()
+                .chain(forbids)
+                .all(|policy| policy.condition.is_supported()),
+        }
+    }
+}
+
+impl ResidualFilter {
+    /// Like `From<&PartialResponse>`, but only keeps resources of `tenant_id`
+    ///
+    /// Every permit except those for which `cross_tenant` returns true (given
+    /// the policy id) also requires [`ResidualExpr::InTenant`]. A request that
+    /// is allowed outright thus becomes conditional unless a cross-tenant
+    /// permit allows it.
+    pub fn within_tenant(
+        response: &PartialResponse,
+        tenant_id: &str,
+        cross_tenant: impl Fn(&str) -> bool,
+    ) -> Self {
+        if response.decision() == Some(Decision::Deny) {
+            return Self::DenyAll;
+        }
+
+        let (mut permits, forbids) = residual_policies(response);
+        for permit in permits.iter_mut().filter(|permit| !cross_tenant(&permit.id)) {
+            let tenant = ResidualExpr::InTenant(tenant_id.to_string());
+            let condition = std::mem::replace(&mut permit.condition, tenant.clone());
+            permit.condition = and(tenant, condition);
+        }
+
+        let allowed = permits
+            .iter()
+            .any(|permit| permit.condition == ResidualExpr::Value(ResidualValue::Bool(true)));
+        if allowed && forbids.is_empty() {
+            Self::AllowAll
+        } else if permits.is_empty() {
+            Self::DenyAll
+        } else {
+            Self::Conditional { permits, forbids }
+        }
+    }
+}
+
+/// Residual permits and forbids, including the ones already satisfied
+///
+/// Policies whose condition reduced to `false` can never apply and are left out.
+fn residual_policies(response: &PartialResponse) -> (Vec<ResidualPolicy>, Vec<ResidualPolicy>) {
+    response
+        .all_residuals()
+        .map(|policy| ResidualPolicy::from_policy(&policy))
+        .filter(|policy| policy.condition != ResidualExpr::Value(ResidualValue::Bool(false)))
+        .partition(|policy| policy.effect == Effect::Permit)
+}
+
+impl From<&PartialResponse> for ResidualFilter {
+    fn from(response: &PartialResponse) -> Self {
+        match response.decision() {
+            Some(Decision::Allow) => return Self::AllowAll,
+            Some(Decision::Deny) => return Self::DenyAll,
+            None => {}
+        }
+
+        let (permits, forbids) = residual_policies(response);
+        if permits.is_empty() {
+            Self::DenyAll
+        } else {
+            Self::Conditional { permits, forbids }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resource_attr(attr: &str) -> ResidualExpr {
+        ResidualExpr::Attr(Box::new(ResidualExpr::Resource), attr.to_string())
+    }
+
+    #[test]
+    fn test_condition_from_json() {
+        let policy = json!({
+            "effect": "permit",
+            "principal": { "op": "All" },
+            "action": { "op": "All" },
+            "resource": { "op": "All" },
+            "conditions": [{
+                "kind": "when",
+                "body": { "||": {
+                    "left": { "==": {
+                        "left": { ".": { "left": { "unknown": [{ "Value": "resource" }] }, "attr": "owner_id" } },
+                        "right": { "Value": { "__entity": { "type": "MyApp::User", "id": "alice" } } }
+                    } },
+                    "right": { "==": {
+                        "left": { ".": { "left": { "unknown": [{ "Value": "resource" }] }, "attr": "is_public" } },
+                        "right": { "Value": true }
+                    } }
+                } }
+            }, {
+                "kind": "unless",
+                "body": { "like": { "left": { "Value": "a" }, "pattern": ["Wildcard"] } }
+            }]
+        });
+
+        let ResidualExpr::And(when, unless) = condition_from_json(&policy) else {
+            panic!("Expected a conjunction");
+        };
+        assert_eq!(
+            *when,
+            ResidualExpr::Or(
+                Box::new(ResidualExpr::Compare(
+                    CompareOp::Eq,
+                    Box::new(resource_attr("owner_id")),
+                    Box::new(ResidualExpr::Value(ResidualValue::Entity {
+                        type_name: "MyApp::User".to_string(),
+                        id: "alice".to_string(),
+                    })),
+                )),
+                Box::new(ResidualExpr::Compare(
+                    CompareOp::Eq,
+                    Box::new(resource_attr("is_public")),
+                    Box::new(ResidualExpr::Value(ResidualValue::Bool(true))),
+                )),
+            )
+        );
+        assert!(when.is_supported());
+        assert!(matches!(*unless, ResidualExpr::Not(ref inner) if !inner.is_supported()));
+    }
+
+    #[test]
+    fn test_literal_conjuncts_are_folded() {
+        let owner = json!({ "==": {
+            "left": { ".": { "left": { "unknown": [{ "Value": "resource" }] }, "attr": "owner" } },
+            "right": { "Value": "alice" }
+        } });
+        let policy = |body: Value| json!({
+            "effect": "permit",
+            "principal": { "op": "All" },
+            "action": { "op": "All" },
+            "resource": { "op": "All" },
+            "conditions": [{ "kind": "when", "body": body }]
+        });
+
+        let scoped = json!({ "&&": {
+            "left": { "&&": { "left": { "Value": true }, "right": { "Value": true } } },
+            "right": owner.clone()
+        } });
+        assert_eq!(
+            condition_from_json(&policy(scoped)),
+            ResidualExpr::Compare(
+                CompareOp::Eq,
+                Box::new(resource_attr("owner")),
+                Box::new(ResidualExpr::Value(ResidualValue::String("alice".to_string()))),
+            )
+        );
+
+        let never = json!({ "&&": { "left": owner, "right": { "Value": false } } });
+        assert_eq!(
+            condition_from_json(&policy(never)),
+            ResidualExpr::Value(ResidualValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn test_resource_scope_becomes_condition() {
+        let policy = json!({
+            "effect": "forbid",
+            "principal": { "op": "All" },
+            "action": { "op": "All" },
+            "resource": { "op": "==", "entity": { "type": "MyApp::Document", "id": "d1" } },
+            "conditions": []
+        });
+
+        assert_eq!(
+            condition_from_json(&policy),
+            ResidualExpr::Compare(
+                CompareOp::Eq,
+                Box::new(ResidualExpr::Resource),
+                Box::new(ResidualExpr::Value(ResidualValue::Entity {
+                    type_name: "MyApp::Document".to_string(),
+                    id: "d1".to_string(),
+                })),
+            )
+        );
+    }
+}
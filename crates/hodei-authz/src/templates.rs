@@ -0,0 +1,209 @@
+//! Policy templates and template-linked policies
+//!
+//! A template is a policy with `?principal` and/or `?resource` slots. Linking
+//! it to concrete entities produces a policy without writing new Cedar text,
+//! which keeps per-resource sharing ("share document X with user Y") down to a
+//! single row per share.
+
+use crate::api::RuntimeHodeiEntityMapper;
+use crate::traits::{PolicyLoadReport, PolicyStoreError, RejectedPolicy};
+use cedar_policy::{EntityUid, PolicyId, PolicySet, SlotId, Template};
+use hodei_hrn::Hrn;
+use std::collections::HashMap;
+
+/// Entity bound to a template slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkedEntity {
+    /// Cedar entity type, e.g. `MyApp::User`
+    pub entity_type: String,
+    /// HRN of the entity, used as its Cedar id
+    pub hrn: Hrn,
+}
+
+impl LinkedEntity {
+    /// Entity of type `entity_type` identified by `hrn`
+    pub fn new(entity_type: impl Into<String>, hrn: Hrn) -> Self {
+        Self {
+            entity_type: entity_type.into(),
+            hrn,
+        }
+    }
+
+    /// Entity mapped by `#[derive(HodeiEntity)]`
+    pub fn of(entity: &(impl RuntimeHodeiEntityMapper + ?Sized)) -> Self {
+        Self::new(entity.hodei_type_name(), entity.hodei_hrn().clone())
+    }
+
+    /// Cedar uid of the entity, built the same way as the derive does
+    pub fn to_euid(&self) -> Result<EntityUid, PolicyStoreError> {
+        let type_name = self.entity_type.parse().map_err(|e| {
+            PolicyStoreError::Parse(format!("Invalid entity type {}: {}", self.entity_type, e))
+        })?;
+        Ok(EntityUid::from_type_name_and_id(
+            type_name,
+            self.hrn.to_string().parse().unwrap(),
+        ))
+    }
+}
+
+/// A template linked to concrete entities
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateLink {
+    /// ID of the resulting policy
+    pub link_id: String,
+    /// ID of the linked template
+    pub template_id: String,
+    /// Entity bound to `?principal`, if the template has that slot
+    pub principal: Option<LinkedEntity>,
+    /// Entity bound to `?resource`, if the template has that slot
+    pub resource: Option<LinkedEntity>,
+}
+
+impl TemplateLink {
+    /// Slot values for `PolicySet::link`
+    pub fn slot_values(&self) -> Result<HashMap<SlotId, EntityUid>, PolicyStoreError> {
+        let mut values = HashMap::new();
+        if let Some(principal) = &self.principal {
+            values.insert(SlotId::principal(), principal.to_euid()?);
+        }
+        if let Some(resource) = &self.resource {
+            values.insert(SlotId::resource(), resource.to_euid()?);
+        }
+        Ok(values)
+    }
+}
+
+/// Parse `content` as a template with the given id
+pub fn parse_template(id: &str, content: &str) -> Result<Template, PolicyStoreError> {
+    Template::parse(Some(PolicyId::new(id)), content)
+        .map_err(|e| PolicyStoreError::Parse(e.to_string()))
+}
+
+/// Add templates and their links to `policy_set`
+///
+/// Fails on the first template that does not parse or link that does not
+/// match its template's slots.
+pub fn add_templates(
+    policy_set: &mut PolicySet,
+    templates: Vec<(String, String)>,
+    links: Vec<TemplateLink>,
+) -> Result<(), PolicyStoreError> {
+    for (id, content) in templates {
+        policy_set
+            .add_template(parse_template(&id, &content)?)
+            .map_err(|e| PolicyStoreError::Internal(e.to_string()))?;
+    }
+    for link in links {
+        link_into(policy_set, &link)
+            .map_err(|reason| PolicyStoreError::Parse(format!("{}: {}", link.link_id, reason)))?;
+    }
+    Ok(())
+}
+
+/// Add templates and their links to a lenient load report
+///
+/// Broken templates are rejected together with every link that uses them.
+pub fn add_templates_lenient(
+    report: &mut PolicyLoadReport,
+    templates: Vec<(String, String)>,
+    links: Vec<TemplateLink>,
+) {
+    for (id, content) in templates {
+        let added = parse_template(&id, &content)
+            .map_err(|e| e.to_string())
+            .and_then(|template| report.policy_set.add_template(template).map_err(|e| e.to_string()));
+        if let Err(reason) = added {
+            report.rejected.push(RejectedPolicy { policy_id: id, reason });
+        }
+    }
+    for link in links {
+        if let Err(reason) = link_into(&mut report.policy_set, &link) {
+            report.rejected.push(RejectedPolicy {
+                policy_id: link.link_id,
+                reason,
+            });
+        }
+    }
+}
+
+/// Check that `link` can be applied to `template`
+pub fn check_link(template: &Template, link: &TemplateLink) -> Result<(), PolicyStoreError> {
+    let mut set = PolicySet::new();
+    set.add_template(template.clone())
+        .map_err(|e| PolicyStoreError::Internal(e.to_string()))?;
+    link_into(&mut set, link).map_err(PolicyStoreError::Parse)
+}
+
+fn link_into(policy_set: &mut PolicySet, link: &TemplateLink) -> Result<(), String> {
+    let values = link.slot_values().map_err(|e| e.to_string())?;
+    policy_set
+        .link(
+            PolicyId::new(&link.template_id),
+            PolicyId::new(&link.link_id),
+            values,
+        )
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARE: &str = r#"permit(principal == ?principal, action, resource == ?resource);"#;
+
+    fn hrn(resource: &str) -> Hrn {
+        Hrn::builder()
+            .service("docs")
+            .tenant_id("tenant-1")
+            .resource(resource)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn share(link_id: &str) -> TemplateLink {
+        TemplateLink {
+            link_id: link_id.to_string(),
+            template_id: "share".to_string(),
+            principal: Some(LinkedEntity::new("App::User", hrn("user/alice"))),
+            resource: Some(LinkedEntity::new("App::Document", hrn("document/1"))),
+        }
+    }
+
+    #[test]
+    fn test_add_templates_and_links() {
+        let mut set = PolicySet::new();
+        add_templates(
+            &mut set,
+            vec![("share".to_string(), SHARE.to_string())],
+            vec![share("share-1")],
+        )
+        .unwrap();
+
+        assert_eq!(set.templates().count(), 1);
+        let linked = set.policy(&PolicyId::new("share-1")).unwrap();
+        assert_eq!(linked.template_id(), Some(&PolicyId::new("share")));
+    }
+
+    #[test]
+    fn test_link_missing_slot_fails() {
+        let template = parse_template("share", SHARE).unwrap();
+        let mut link = share("share-1");
+        link.resource = None;
+
+        assert!(matches!(check_link(&template, &link), Err(PolicyStoreError::Parse(_))));
+    }
+
+    #[test]
+    fn test_lenient_rejects_links_of_broken_template() {
+        let mut report = PolicyLoadReport::default();
+        add_templates_lenient(
+            &mut report,
+            vec![("share".to_string(), "permit(principal ==".to_string())],
+            vec![share("share-1")],
+        );
+
+        let rejected: Vec<&str> = report.rejected.iter().map(|r| r.policy_id.as_str()).collect();
+        assert_eq!(rejected, vec!["share", "share-1"]);
+    }
+}
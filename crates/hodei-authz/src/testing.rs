@@ -0,0 +1,65 @@
+//! Checks shared by the tests of every `PolicyStore` implementation
+//!
+//! Enabled with the `testing` feature. Each check creates its own policies
+//! and removes them afterwards, so it can run against a shared database.
+
+use crate::traits::{PolicyStore, PolicyStoreError};
+use cedar_policy::PolicyId;
+
+/// Tenant used by the checks for tenant-scoped policies
+const TENANT: &str = "store-contract-tenant";
+
+/// `list_policies` returns every global policy, enabled or not, and leaves
+/// out tenant policies; `load_all_policies` skips the disabled ones
+///
+/// Steps the store does not support (disabling, tenant policies) are skipped.
+pub async fn check_list_policies<S: PolicyStore + ?Sized>(store: &S) {
+    let enabled = store
+        .create_policy(r#"permit(principal, action == Action::"Read", resource);"#.to_string())
+        .await
+        .expect("Failed to create policy");
+    let disabled = store
+        .create_policy(r#"permit(principal, action == Action::"Write", resource);"#.to_string())
+        .await
+        .expect("Failed to create policy");
+    let can_disable = supported(store.set_policy_enabled(&disabled, false).await);
+    let tenant_policy = supported_value(
+        store
+            .create_tenant_policy(TENANT, "permit(principal, action, resource);".to_string())
+            .await,
+    );
+
+    let listed: Vec<String> = store
+        .list_policies()
+        .await
+        .expect("Failed to list policies")
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert!(listed.contains(&enabled));
+    assert!(listed.contains(&disabled), "list_policies left out a disabled policy");
+    if let Some(tenant_policy) = &tenant_policy {
+        assert!(!listed.contains(tenant_policy), "list_policies returned a tenant policy");
+    }
+
+    let loaded = store.load_all_policies().await.expect("Failed to load policies");
+    assert!(loaded.policy(&PolicyId::new(&enabled)).is_some());
+    assert_eq!(loaded.policy(&PolicyId::new(&disabled)).is_some(), !can_disable);
+
+    for id in [Some(enabled), Some(disabled), tenant_policy].into_iter().flatten() {
+        store.delete_policy(&id).await.ok();
+    }
+}
+
+/// Returns false when the store does not support the operation
+fn supported(result: Result<(), PolicyStoreError>) -> bool {
+    supported_value(result).is_some()
+}
+
+fn supported_value<T>(result: Result<T, PolicyStoreError>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(PolicyStoreError::Unsupported(_)) => None,
+        Err(e) => panic!("Unexpected store error: {}", e),
+    }
+}
@@ -1,7 +1,14 @@
 //! Core traits for Hodei authorization framework
 
+use crate::api::{RuntimeHodeiActionMapper, RuntimeHodeiEntityMapper};
+use crate::decision::{AuthorizationDecision, AuthorizationError};
+use crate::records::{PolicyFilter, PolicyMetadata, PolicyPage, PolicyRecord};
+use crate::templates::{add_templates_lenient, LinkedEntity, TemplateLink};
+use crate::validation::{format_diagnostics, PolicyDiagnostic};
 use async_trait::async_trait;
-use cedar_policy::PolicySet;
+use cedar_policy::{Context, Policy, PolicyId, PolicySet};
+use std::sync::Arc;
+use std::time::SystemTime;
 use thiserror::Error;
 
 /// Errors that can occur in policy storage operations
@@ -11,8 +18,20 @@ pub enum PolicyStoreError {
     Database(String),
     #[error("Policy not found: {0}")]
     NotFound(String),
+    #[error("Policy already exists: {0}")]
+    AlreadyExists(String),
     #[error("Policy parse error: {0}")]
     Parse(String),
+    #[error("Policy validation failed: {}", format_diagnostics(.0))]
+    Validation(Vec<PolicyDiagnostic>),
+    #[error("Version conflict on policy {policy_id}: expected version {expected}, found {actual}")]
+    Conflict {
+        policy_id: String,
+        expected: i64,
+        actual: i64,
+    },
+    #[error("Operation not supported by this store: {0}")]
+    Unsupported(String),
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -28,7 +47,95 @@ pub enum CacheError {
     Subscribe(String),
 }
 
+/// A policy skipped by a lenient load
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedPolicy {
+    /// ID of the skipped policy (or of the source it came from)
+    pub policy_id: String,
+    /// Why the policy could not be loaded
+    pub reason: String,
+}
+
+/// Result of a lenient load: the policies that loaded and the ones skipped
+#[derive(Debug, Clone, Default)]
+pub struct PolicyLoadReport {
+    /// Every policy that parsed successfully
+    pub policy_set: PolicySet,
+    /// Policies left out of `policy_set`
+    pub rejected: Vec<RejectedPolicy>,
+}
+
+impl PolicyLoadReport {
+    /// Parse `(id, content)` pairs, rejecting the ones that fail
+    pub fn from_policies(policies: Vec<(String, String)>) -> Self {
+        let mut report = Self::default();
+        for (id, content) in policies {
+            let added = Policy::parse(Some(PolicyId::new(&id)), &content)
+                .map_err(|e| e.to_string())
+                .and_then(|policy| report.policy_set.add(policy).map_err(|e| e.to_string()));
+            if let Err(reason) = added {
+                report.rejected.push(RejectedPolicy { policy_id: id, reason });
+            }
+        }
+        report
+    }
+
+    /// Returns true if every policy was loaded
+    pub fn is_complete(&self) -> bool {
+        self.rejected.is_empty()
+    }
+}
+
+/// Who made a policy change and why
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyChange {
+    /// Identifier of the user or system making the change
+    pub author: Option<String>,
+    /// Free-form explanation recorded with the change
+    pub reason: Option<String>,
+}
+
+impl PolicyChange {
+    /// Change attributed to `author`
+    pub fn by(author: impl Into<String>) -> Self {
+        Self {
+            author: Some(author.into()),
+            reason: None,
+        }
+    }
+
+    /// Attach a reason to the change
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+/// A recorded version of a policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyVersion {
+    /// ID of the policy
+    pub policy_id: String,
+    /// Version number, starting at 1 and increasing with every change
+    pub version: i64,
+    /// Policy content at this version
+    pub content: String,
+    /// Who made the change, if known
+    pub author: Option<String>,
+    /// Why the change was made, if given
+    pub reason: Option<String>,
+    /// When the version was recorded
+    pub created_at: SystemTime,
+    /// The policy was deleted in this version; `content` is its last content
+    pub deleted: bool,
+}
+
 /// Trait for policy storage backends
+///
+/// Policies are global unless created with
+/// [`create_tenant_policy`](Self::create_tenant_policy). The tenant-less
+/// listing and loading methods only see global policies; `get_policy`,
+/// `update_policy` and `delete_policy` work on any policy by ID.
 #[async_trait]
 pub trait PolicyStore: Send + Sync {
     /// Create a new policy and return its ID
@@ -37,7 +144,9 @@ pub trait PolicyStore: Send + Sync {
     /// Get a policy by ID
     async fn get_policy(&self, id: &str) -> Result<Option<String>, PolicyStoreError>;
     
-    /// List all policies as (id, content) tuples
+    /// List every global policy as (id, content) tuples, enabled or not
+    ///
+    /// Tenant policies are left out; see `list_tenant_policies`.
     async fn list_policies(&self) -> Result<Vec<(String, String)>, PolicyStoreError>;
     
     /// Update an existing policy
@@ -48,6 +157,179 @@ pub trait PolicyStore: Send + Sync {
     
     /// Load all policies as a PolicySet
     async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError>;
+    
+    /// Load all policies, skipping the ones that cannot be parsed
+    ///
+    /// Unlike `load_all_policies`, a single broken policy does not fail the
+    /// whole load; it is reported in [`PolicyLoadReport::rejected`] instead.
+    /// Errors reaching the backend itself are still returned.
+    async fn load_policies_lenient(&self) -> Result<PolicyLoadReport, PolicyStoreError> {
+        let mut report = PolicyLoadReport::from_policies(self.list_policies().await?);
+        
+        match self.list_templates().await {
+            Ok(templates) => {
+                let links = self.list_template_links(None).await?;
+                add_templates_lenient(&mut report, templates, links);
+            }
+            Err(PolicyStoreError::Unsupported(_)) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(report)
+    }
+    
+    /// List every recorded version of a policy, oldest first
+    ///
+    /// History outlives the policy: a deletion is recorded as a last version
+    /// with `deleted` set. Stores without history return `PolicyStoreError::Unsupported`.
+    async fn policy_history(&self, _id: &str) -> Result<Vec<PolicyVersion>, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("policy_history".to_string()))
+    }
+    
+    /// Get a specific version of a policy
+    async fn get_policy_version(
+        &self,
+        _id: &str,
+        _version: i64,
+    ) -> Result<Option<PolicyVersion>, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("get_policy_version".to_string()))
+    }
+    
+    /// Update a policy only if its current version is `expected_version`
+    ///
+    /// Returns the new version, or `PolicyStoreError::Conflict` if the policy
+    /// was changed concurrently.
+    async fn update_policy_versioned(
+        &self,
+        _id: &str,
+        _content: String,
+        _expected_version: i64,
+        _change: PolicyChange,
+    ) -> Result<i64, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("update_policy_versioned".to_string()))
+    }
+    
+    /// Restore the content of `version` as a new version and return its number
+    async fn rollback_policy(
+        &self,
+        _id: &str,
+        _version: i64,
+        _change: PolicyChange,
+    ) -> Result<i64, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("rollback_policy".to_string()))
+    }
+    
+    /// Create a policy template (with `?principal` and/or `?resource` slots)
+    /// and return its ID
+    ///
+    /// Stores without template support return `PolicyStoreError::Unsupported`.
+    async fn create_template(&self, _content: String) -> Result<String, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("create_template".to_string()))
+    }
+    
+    /// List all templates as (id, content) tuples
+    async fn list_templates(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("list_templates".to_string()))
+    }
+    
+    /// Delete a template together with all of its links
+    async fn delete_template(&self, _id: &str) -> Result<(), PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("delete_template".to_string()))
+    }
+    
+    /// Link a template to concrete entities and return the link ID
+    ///
+    /// Each slot of the template must receive exactly one entity.
+    async fn link_template(
+        &self,
+        _template_id: &str,
+        _principal: Option<LinkedEntity>,
+        _resource: Option<LinkedEntity>,
+    ) -> Result<String, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("link_template".to_string()))
+    }
+    
+    /// List template links, optionally only those of one template
+    async fn list_template_links(
+        &self,
+        _template_id: Option<&str>,
+    ) -> Result<Vec<TemplateLink>, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("list_template_links".to_string()))
+    }
+    
+    /// Remove a template link by ID
+    async fn unlink_template(&self, _link_id: &str) -> Result<(), PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("unlink_template".to_string()))
+    }
+    
+    /// List policies with their metadata, filtered and paginated
+    ///
+    /// As with `list_policies`, disabled policies are included unless the
+    /// filter says otherwise. Stores without metadata return
+    /// `PolicyStoreError::Unsupported`.
+    async fn list_policy_records(
+        &self,
+        _filter: &PolicyFilter,
+    ) -> Result<PolicyPage, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("list_policy_records".to_string()))
+    }
+    
+    /// Get a policy with its metadata
+    async fn get_policy_record(&self, _id: &str) -> Result<Option<PolicyRecord>, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("get_policy_record".to_string()))
+    }
+    
+    /// Replace the name, description, tags and owner of a policy
+    async fn update_policy_metadata(
+        &self,
+        _id: &str,
+        _metadata: PolicyMetadata,
+    ) -> Result<(), PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("update_policy_metadata".to_string()))
+    }
+    
+    /// Enable or disable a policy
+    ///
+    /// Disabled policies stay in the store but are left out of
+    /// `load_all_policies` and `load_policies_lenient`. Callers still need to
+    /// invalidate caches for the change to reach running services.
+    async fn set_policy_enabled(&self, _id: &str, _enabled: bool) -> Result<(), PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("set_policy_enabled".to_string()))
+    }
+    
+    /// Create a policy that only applies within `tenant_id` and return its ID
+    ///
+    /// Stores without tenant support return `PolicyStoreError::Unsupported`.
+    async fn create_tenant_policy(
+        &self,
+        _tenant_id: &str,
+        _content: String,
+    ) -> Result<String, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("create_tenant_policy".to_string()))
+    }
+    
+    /// List the policies of `tenant_id` as (id, content) tuples, without the
+    /// global ones
+    async fn list_tenant_policies(
+        &self,
+        _tenant_id: &str,
+    ) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("list_tenant_policies".to_string()))
+    }
+    
+    /// Load the policies of `tenant_id`, without the global ones
+    async fn load_tenant_policies(&self, _tenant_id: &str) -> Result<PolicySet, PolicyStoreError> {
+        Err(PolicyStoreError::Unsupported("load_tenant_policies".to_string()))
+    }
+    
+    /// Load the policies of `tenant_id`, skipping the ones that cannot be parsed
+    async fn load_tenant_policies_lenient(
+        &self,
+        tenant_id: &str,
+    ) -> Result<PolicyLoadReport, PolicyStoreError> {
+        Ok(PolicyLoadReport::from_policies(
+            self.list_tenant_policies(tenant_id).await?,
+        ))
+    }
 }
 
 /// Trait for cache invalidation mechanisms
@@ -62,6 +344,203 @@ pub trait CacheInvalidation: Send + Sync {
         F: Fn() + Send + Sync + 'static;
 }
 
+/// Cache invalidation that does nothing
+///
+/// Suitable for single-instance deployments where the service reloads its own
+/// policies and there are no other instances to notify.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopCacheInvalidation;
+
+#[async_trait]
+impl CacheInvalidation for NoopCacheInvalidation {
+    async fn invalidate_policies(&self) -> Result<(), CacheError> {
+        Ok(())
+    }
+
+    async fn subscribe_to_invalidations<F>(&self, _callback: F) -> Result<(), CacheError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Ok(())
+    }
+}
+
+// Lets the application keep a handle to the same instance it hands to the service
+#[async_trait]
+impl<T: PolicyStore + ?Sized> PolicyStore for Arc<T> {
+    async fn create_policy(&self, content: String) -> Result<String, PolicyStoreError> {
+        (**self).create_policy(content).await
+    }
+
+    async fn get_policy(&self, id: &str) -> Result<Option<String>, PolicyStoreError> {
+        (**self).get_policy(id).await
+    }
+
+    async fn list_policies(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        (**self).list_policies().await
+    }
+
+    async fn update_policy(&self, id: &str, content: String) -> Result<(), PolicyStoreError> {
+        (**self).update_policy(id, content).await
+    }
+
+    async fn delete_policy(&self, id: &str) -> Result<(), PolicyStoreError> {
+        (**self).delete_policy(id).await
+    }
+
+    async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
+        (**self).load_all_policies().await
+    }
+
+    async fn load_policies_lenient(&self) -> Result<PolicyLoadReport, PolicyStoreError> {
+        (**self).load_policies_lenient().await
+    }
+
+    async fn policy_history(&self, id: &str) -> Result<Vec<PolicyVersion>, PolicyStoreError> {
+        (**self).policy_history(id).await
+    }
+
+    async fn get_policy_version(
+        &self,
+        id: &str,
+        version: i64,
+    ) -> Result<Option<PolicyVersion>, PolicyStoreError> {
+        (**self).get_policy_version(id, version).await
+    }
+
+    async fn update_policy_versioned(
+        &self,
+        id: &str,
+        content: String,
+        expected_version: i64,
+        change: PolicyChange,
+    ) -> Result<i64, PolicyStoreError> {
+        (**self)
+            .update_policy_versioned(id, content, expected_version, change)
+            .await
+    }
+
+    async fn rollback_policy(
+        &self,
+        id: &str,
+        version: i64,
+        change: PolicyChange,
+    ) -> Result<i64, PolicyStoreError> {
+        (**self).rollback_policy(id, version, change).await
+    }
+
+    async fn create_template(&self, content: String) -> Result<String, PolicyStoreError> {
+        (**self).create_template(content).await
+    }
+
+    async fn list_templates(&self) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        (**self).list_templates().await
+    }
+
+    async fn delete_template(&self, id: &str) -> Result<(), PolicyStoreError> {
+        (**self).delete_template(id).await
+    }
+
+    async fn link_template(
+        &self,
+        template_id: &str,
+        principal: Option<LinkedEntity>,
+        resource: Option<LinkedEntity>,
+    ) -> Result<String, PolicyStoreError> {
+        (**self).link_template(template_id, principal, resource).await
+    }
+
+    async fn list_template_links(
+        &self,
+        template_id: Option<&str>,
+    ) -> Result<Vec<TemplateLink>, PolicyStoreError> {
+        (**self).list_template_links(template_id).await
+    }
+
+    async fn unlink_template(&self, link_id: &str) -> Result<(), PolicyStoreError> {
+        (**self).unlink_template(link_id).await
+    }
+
+    async fn list_policy_records(
+        &self,
+        filter: &PolicyFilter,
+    ) -> Result<PolicyPage, PolicyStoreError> {
+        (**self).list_policy_records(filter).await
+    }
+
+    async fn get_policy_record(&self, id: &str) -> Result<Option<PolicyRecord>, PolicyStoreError> {
+        (**self).get_policy_record(id).await
+    }
+
+    async fn update_policy_metadata(
+        &self,
+        id: &str,
+        metadata: PolicyMetadata,
+    ) -> Result<(), PolicyStoreError> {
+        (**self).update_policy_metadata(id, metadata).await
+    }
+
+    async fn set_policy_enabled(&self, id: &str, enabled: bool) -> Result<(), PolicyStoreError> {
+        (**self).set_policy_enabled(id, enabled).await
+    }
+
+    async fn create_tenant_policy(
+        &self,
+        tenant_id: &str,
+        content: String,
+    ) -> Result<String, PolicyStoreError> {
+        (**self).create_tenant_policy(tenant_id, content).await
+    }
+
+    async fn list_tenant_policies(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<(String, String)>, PolicyStoreError> {
+        (**self).list_tenant_policies(tenant_id).await
+    }
+
+    async fn load_tenant_policies(&self, tenant_id: &str) -> Result<PolicySet, PolicyStoreError> {
+        (**self).load_tenant_policies(tenant_id).await
+    }
+
+    async fn load_tenant_policies_lenient(
+        &self,
+        tenant_id: &str,
+    ) -> Result<PolicyLoadReport, PolicyStoreError> {
+        (**self).load_tenant_policies_lenient(tenant_id).await
+    }
+}
+
+#[async_trait]
+impl<T: CacheInvalidation> CacheInvalidation for Arc<T> {
+    async fn invalidate_policies(&self) -> Result<(), CacheError> {
+        (**self).invalidate_policies().await
+    }
+
+    async fn subscribe_to_invalidations<F>(&self, callback: F) -> Result<(), CacheError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        (**self).subscribe_to_invalidations(callback).await
+    }
+}
+
+/// Trait for services able to evaluate authorization requests
+///
+/// Integrations such as the Axum middleware depend on this trait instead of a
+/// concrete service so they can work with any evaluation backend.
+#[async_trait]
+pub trait AuthorizationEvaluator: Send + Sync {
+    /// Evaluate whether `principal` can perform `action` on `resource`
+    async fn evaluate(
+        &self,
+        principal: &(dyn RuntimeHodeiEntityMapper + Sync),
+        action: &(dyn RuntimeHodeiActionMapper + Sync),
+        resource: &(dyn RuntimeHodeiEntityMapper + Sync),
+        context: Context,
+    ) -> Result<AuthorizationDecision, AuthorizationError>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
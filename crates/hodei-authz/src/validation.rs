@@ -0,0 +1,179 @@
+//! Schema validation for policies written to a `PolicyStore`
+//!
+//! Stores call [`validate_policy`] on every write so that a policy referring to
+//! unknown entity types, actions or attributes is rejected before it can break
+//! `load_all_policies`.
+
+use crate::traits::PolicyStoreError;
+use cedar_policy::{Policy, PolicySet, Template, ValidationMode, Validator};
+use miette::Diagnostic;
+use std::fmt;
+
+/// Byte range within the policy source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// Offset of the first byte
+    pub offset: usize,
+    /// Length in bytes
+    pub len: usize,
+}
+
+/// A single problem found while validating a policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDiagnostic {
+    /// ID of the offending policy
+    pub policy_id: String,
+    /// Location of the problem in the policy source, when known
+    pub span: Option<SourceSpan>,
+    /// Human readable description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for PolicyDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(
+                f,
+                "{} [{}..{}]: {}",
+                self.policy_id,
+                span.offset,
+                span.offset + span.len,
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.policy_id, self.message),
+        }
+    }
+}
+
+/// Join diagnostics for display in `PolicyStoreError::Validation`
+pub(crate) fn format_diagnostics(diagnostics: &[PolicyDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Validate a parsed policy against the validator's schema
+///
+/// Uses strict mode, the same mode Cedar recommends for policies evaluated
+/// with request validation. Warnings are not reported.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let validator = Validator::new(schema);
+/// let policy = Policy::parse(Some(PolicyId::new("p1")), content)?;
+/// validate_policy(&validator, &policy)?;
+/// ```
+pub fn validate_policy(validator: &Validator, policy: &Policy) -> Result<(), PolicyStoreError> {
+    let mut set = PolicySet::new();
+    set.add(policy.clone())
+        .map_err(|e| PolicyStoreError::Internal(e.to_string()))?;
+    validate_set(validator, &set, policy.id().as_ref())
+}
+
+/// Validate a template against the validator's schema
+///
+/// Slots are checked against every entity type they may be linked to, so a
+/// valid template stays valid for any link.
+pub fn validate_template(
+    validator: &Validator,
+    template: &Template,
+) -> Result<(), PolicyStoreError> {
+    let mut set = PolicySet::new();
+    set.add_template(template.clone())
+        .map_err(|e| PolicyStoreError::Internal(e.to_string()))?;
+    validate_set(validator, &set, template.id().as_ref())
+}
+
+/// Validate a single-policy set and report its errors under `policy_id`
+fn validate_set(
+    validator: &Validator,
+    set: &PolicySet,
+    policy_id: &str,
+) -> Result<(), PolicyStoreError> {
+    let result = validator.validate(set, ValidationMode::Strict);
+    if result.validation_passed() {
+        return Ok(());
+    }
+
+    let diagnostics = result
+        .validation_errors()
+        .map(|error| {
+            let span = error
+                .labels()
+                .and_then(|mut labels| labels.next())
+                .map(|label| SourceSpan {
+                    offset: label.offset(),
+                    len: label.len(),
+                });
+            let message = match error.help() {
+                Some(help) => format!("{} ({})", error, help),
+                None => error.to_string(),
+            };
+            PolicyDiagnostic {
+                policy_id: policy_id.to_string(),
+                span,
+                message,
+            }
+        })
+        .collect();
+
+    Err(PolicyStoreError::Validation(diagnostics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedar_policy::{PolicyId, Schema};
+
+    const SCHEMA: &str = r#"
+        namespace App {
+            entity User { role: String };
+            entity Document;
+            action Read appliesTo { principal: User, resource: Document };
+        }
+    "#;
+
+    fn validator() -> Validator {
+        let (schema, _) = Schema::from_cedarschema_str(SCHEMA).unwrap();
+        Validator::new(schema)
+    }
+
+    fn policy(content: &str) -> Policy {
+        Policy::parse(Some(PolicyId::new("p1")), content).unwrap()
+    }
+
+    #[test]
+    fn test_valid_policy() {
+        let valid = policy(
+            r#"permit(principal, action == App::Action::"Read", resource) when { principal.role == "admin" };"#,
+        );
+        assert!(validate_policy(&validator(), &valid).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_attribute_reports_span() {
+        let content = r#"permit(principal, action == App::Action::"Read", resource) when { principal.rol == "admin" };"#;
+        let result = validate_policy(&validator(), &policy(content));
+
+        let Err(PolicyStoreError::Validation(diagnostics)) = result else {
+            panic!("expected validation error, got {:?}", result);
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].policy_id, "p1");
+
+        let span = diagnostics[0].span.expect("diagnostic should carry a span");
+        assert!(content[span.offset..span.offset + span.len].contains("principal.rol"));
+    }
+
+    #[test]
+    fn test_unknown_entity_type() {
+        let result = validate_policy(
+            &validator(),
+            &policy(r#"permit(principal is App::Admin, action, resource);"#),
+        );
+        assert!(matches!(result, Err(PolicyStoreError::Validation(_))));
+    }
+}
@@ -0,0 +1,188 @@
+//! Tests for the HodeiEntity, HodeiAction and HodeiContext derives
+
+use cedar_policy::{Authorizer, Context, Decision, Entities, PolicySet, Request};
+use hodei_authz::{
+    HodeiAction, HodeiContext, HodeiEntity, RuntimeHodeiActionMapper, RuntimeHodeiEntityMapper,
+};
+use hodei_hrn::Hrn;
+use std::net::IpAddr;
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "App::User")]
+struct User {
+    id: Hrn,
+    email: String,
+    #[hodei(parent, entity_type = "App::Group")]
+    groups: Vec<Hrn>,
+}
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "App::Document")]
+struct Document {
+    id: Hrn,
+    #[hodei(parent, entity_type = "App::Folder")]
+    folder: Option<Hrn>,
+    nickname: Option<String>,
+}
+
+#[derive(HodeiAction)]
+#[hodei(namespace = "App", member_of = "DocumentActions")]
+enum DocumentAction {
+    #[hodei(principal = "User", resource = "Document")]
+    Read,
+    #[hodei(principal = "User", resource = "Document", name = "document:write")]
+    Update {
+        #[allow(dead_code)]
+        id: Hrn,
+    },
+}
+
+#[derive(HodeiContext)]
+#[hodei(context_type = "App::RequestContext")]
+struct RequestContext {
+    source_ip: IpAddr,
+    mfa: bool,
+    reason: Option<String>,
+}
+
+fn hrn(resource: &str) -> Hrn {
+    format!("hrn:hodei:docapp:global:tenant-1:{}", resource).parse().unwrap()
+}
+
+fn user() -> User {
+    User {
+        id: hrn("user/alice"),
+        email: "alice@example.com".to_string(),
+        groups: vec![hrn("group/admins"), hrn("group/editors")],
+    }
+}
+
+#[test]
+fn test_parents_populated_from_parent_fields() {
+    let entity = user().to_cedar_entity();
+    let json = entity.to_json_value().unwrap();
+
+    let parents = json["parents"].as_array().unwrap();
+    assert_eq!(parents.len(), 2);
+    assert!(parents.iter().all(|p| p["type"] == "App::Group"));
+    // Los campos parent no se exponen como atributos
+    assert!(json["attrs"].get("groups").is_none());
+    assert_eq!(json["attrs"]["email"], "alice@example.com");
+}
+
+#[test]
+fn test_optional_parent_and_attribute() {
+    let document = Document {
+        id: hrn("document/doc-1"),
+        folder: None,
+        nickname: None,
+    };
+    let json = document.to_cedar_entity().to_json_value().unwrap();
+    assert!(json["parents"].as_array().unwrap().is_empty());
+    assert!(json["attrs"].get("nickname").is_none());
+
+    let document = Document {
+        folder: Some(hrn("folder/reports")),
+        nickname: Some("q3".to_string()),
+        ..document
+    };
+    let json = document.to_cedar_entity().to_json_value().unwrap();
+    assert_eq!(json["parents"][0]["type"], "App::Folder");
+    assert_eq!(json["attrs"]["nickname"], "q3");
+}
+
+#[test]
+fn test_principal_in_group_policy() {
+    let user = user();
+    let document = Document {
+        id: hrn("document/doc-1"),
+        folder: Some(hrn("folder/reports")),
+        nickname: None,
+    };
+
+    let policies: PolicySet = format!(
+        r#"permit(principal in App::Group::"{}", action, resource in App::Folder::"{}");"#,
+        hrn("group/editors"),
+        hrn("folder/reports"),
+    )
+    .parse()
+    .unwrap();
+
+    let entities = Entities::from_entities(
+        [user.to_cedar_entity(), document.to_cedar_entity()],
+        None,
+    )
+    .unwrap();
+    let request = Request::new(
+        user.to_cedar_euid(),
+        DocumentAction::Read.to_cedar_action_euid(),
+        document.to_cedar_euid(),
+        Context::empty(),
+        None,
+    )
+    .unwrap();
+
+    let response = Authorizer::new().is_authorized(&request, &policies, &entities);
+    assert_eq!(response.decision(), Decision::Allow);
+}
+
+#[test]
+fn test_action_euid_uses_namespace() {
+    assert_eq!(
+        DocumentAction::Read.to_cedar_action_euid().to_string(),
+        r#"App::Action::"Document::Read""#
+    );
+    assert_eq!(
+        DocumentAction::Update { id: hrn("document/doc-1") }
+            .to_cedar_action_euid()
+            .to_string(),
+        r#"App::Action::"document:write""#
+    );
+}
+
+#[test]
+fn test_typed_context() {
+    let user = user();
+    let document = Document {
+        id: hrn("document/doc-1"),
+        folder: None,
+        nickname: None,
+    };
+    let policies: PolicySet = r#"permit(principal, action, resource)
+        when { context.source_ip.isInRange(ip("10.0.0.0/8")) && context.mfa };"#
+        .parse()
+        .unwrap();
+    let entities = Entities::from_entities(
+        [user.to_cedar_entity(), document.to_cedar_entity()],
+        None,
+    )
+    .unwrap();
+
+    let decide = |context: RequestContext| {
+        let request = Request::new(
+            user.to_cedar_euid(),
+            DocumentAction::Read.to_cedar_action_euid(),
+            document.to_cedar_euid(),
+            context.to_cedar_context().unwrap(),
+            None,
+        )
+        .unwrap();
+        Authorizer::new()
+            .is_authorized(&request, &policies, &entities)
+            .decision()
+    };
+
+    let internal = RequestContext {
+        source_ip: "10.1.2.3".parse().unwrap(),
+        mfa: true,
+        reason: None,
+    };
+    assert_eq!(decide(internal), Decision::Allow);
+
+    let external = RequestContext {
+        source_ip: "203.0.113.7".parse().unwrap(),
+        mfa: true,
+        reason: Some("travelling".to_string()),
+    };
+    assert_eq!(decide(external), Decision::Deny);
+}
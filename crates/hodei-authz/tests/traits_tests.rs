@@ -2,7 +2,10 @@
 
 use async_trait::async_trait;
 use cedar_policy::PolicySet;
-use hodei_authz::{CacheError, CacheInvalidation, PolicyStore, PolicyStoreError};
+use hodei_authz::{
+    CacheError, CacheInvalidation, NoopCacheInvalidation, PolicyChange, PolicyFilter, PolicyStore,
+    PolicyStoreError,
+};
 
 // Mock implementations for testing
 
@@ -59,7 +62,7 @@ impl PolicyStore for MockPolicyStore {
     }
     
     async fn load_all_policies(&self) -> Result<PolicySet, PolicyStoreError> {
-        PolicySet::new().map_err(|e| PolicyStoreError::Internal(e.to_string()))
+        Ok(PolicySet::new())
     }
 }
 
@@ -192,3 +195,80 @@ async fn test_mock_cache_invalidation() {
     
     assert_eq!(cache.get_count(), 2);
 }
+
+#[tokio::test]
+async fn test_noop_cache_invalidation() {
+    let cache = NoopCacheInvalidation;
+    
+    cache.subscribe_to_invalidations(|| panic!("Noop must never notify"))
+        .await
+        .expect("Failed to subscribe");
+    
+    cache.invalidate_policies()
+        .await
+        .expect("Failed to invalidate");
+}
+
+#[tokio::test]
+async fn test_arc_forwards_to_inner() {
+    let store = std::sync::Arc::new(MockPolicyStore::new());
+    let shared = store.clone();
+    
+    let id = shared.create_policy("policy".to_string())
+        .await
+        .expect("Failed to create policy");
+    
+    assert_eq!(store.get_policy(&id).await.unwrap(), Some("policy".to_string()));
+    
+    let cache = std::sync::Arc::new(MockCacheInvalidation::new());
+    cache.clone().invalidate_policies()
+        .await
+        .expect("Failed to invalidate");
+    
+    assert_eq!(cache.get_count(), 1);
+}
+
+#[tokio::test]
+async fn test_lenient_load_reports_broken_policies() {
+    let store = MockPolicyStore::new();
+    
+    store.create_policy("permit(principal, action, resource);".to_string())
+        .await
+        .expect("Failed to create policy");
+    let broken = store.create_policy("permit(principal, action".to_string())
+        .await
+        .expect("Failed to create policy");
+    
+    let report = store.load_policies_lenient()
+        .await
+        .expect("Lenient load must not fail on broken policies");
+    
+    assert_eq!(report.policy_set.policies().count(), 1);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].policy_id, broken);
+    assert!(!report.is_complete());
+}
+
+#[tokio::test]
+async fn test_versioning_unsupported_by_default() {
+    let store = MockPolicyStore::new();
+    
+    let result = store.policy_history("any").await;
+    assert!(matches!(result, Err(PolicyStoreError::Unsupported(_))));
+    
+    let result = store
+        .update_policy_versioned("any", "content".to_string(), 1, PolicyChange::by("alice"))
+        .await;
+    assert!(matches!(result, Err(PolicyStoreError::Unsupported(_))));
+}
+
+#[tokio::test]
+async fn test_metadata_unsupported_by_default() {
+    let store = MockPolicyStore::new();
+    
+    let result = store.list_policy_records(&PolicyFilter::default()).await;
+    assert!(matches!(result, Err(PolicyStoreError::Unsupported(_))));
+    
+    let result = store.set_policy_enabled("any", false).await;
+    assert!(matches!(result, Err(PolicyStoreError::Unsupported(_))));
+}
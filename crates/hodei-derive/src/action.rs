@@ -0,0 +1,233 @@
+//! Generación de código para `HodeiAction`
+
+use crate::attrs::{for_each_hodei_meta, namespace_path, parse_str, type_reference, unknown_key};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields};
+
+/// Claves admitidas en `#[hodei(...)]` a nivel de enum
+const ENUM_KEYS: &[&str] = &["namespace", "context", "request_context", "member_of"];
+
+/// Claves admitidas en `#[hodei(...)]` a nivel de variante
+const VARIANT_KEYS: &[&str] = &["principal", "resource", "creates_resource", "name"];
+
+/// Valida el nombre de una acción o grupo de acciones
+fn action_name(lit: &syn::LitStr) -> syn::Result<String> {
+    let value = lit.value();
+    if value.trim().is_empty() {
+        return Err(syn::Error::new(lit.span(), "el nombre de la acción no puede estar vacío"));
+    }
+    Ok(value)
+}
+
+pub(crate) fn expand_action(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let enum_name = &ast.ident;
+
+    let data_enum = match &ast.data {
+        Data::Enum(de) => de,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                enum_name,
+                "HodeiAction solo se puede derivar en enums",
+            ));
+        }
+    };
+
+    let mut namespace: Option<String> = None;
+    let mut context_type: Option<syn::Path> = None;
+    let mut request_context_type: Option<syn::Path> = None;
+    let mut groups: Vec<String> = Vec::new();
+    for_each_hodei_meta(&ast.attrs, |meta| {
+        if meta.path.is_ident("namespace") {
+            namespace = Some(namespace_path(&parse_str(&meta)?)?);
+        } else if meta.path.is_ident("context") {
+            context_type = Some(parse_str(&meta)?.parse()?);
+        } else if meta.path.is_ident("request_context") {
+            request_context_type = Some(parse_str(&meta)?.parse()?);
+        } else if meta.path.is_ident("member_of") {
+            groups.push(action_name(&parse_str(&meta)?)?);
+        } else {
+            return Err(unknown_key(&meta, ENUM_KEYS));
+        }
+        Ok(())
+    })?;
+    let namespace = namespace.ok_or_else(|| {
+        syn::Error::new_spanned(
+            enum_name,
+            "#[derive(HodeiAction)] requiere #[hodei(namespace = \"...\")]",
+        )
+    })?;
+
+    let action_type_str = format!("{}::Action", namespace);
+    // El contexto del schema es independiente del contexto de las entidades virtuales
+    let context_type_expr = match &request_context_type {
+        Some(context_type) => {
+            quote! { Some(<#context_type as hodei_authz::HodeiContext>::CONTEXT_TYPE) }
+        }
+        None => quote! { None },
+    };
+    let member_of: Vec<serde_json::Value> = groups
+        .iter()
+        .map(|group| serde_json::json!({ "id": group }))
+        .collect();
+
+    // Los grupos se registran como acciones sin `appliesTo`
+    let mut inventory_submissions: Vec<TokenStream> = groups
+        .iter()
+        .map(|group| {
+            quote! {
+                #[cfg(feature = "schema-discovery")]
+                hodei_authz::inventory::submit! {
+                    hodei_authz::ActionSchemaFragment {
+                        namespace: #namespace,
+                        name: #group,
+                        fragment_json: "{}",
+                        context_type: None
+                    }
+                }
+            }
+        })
+        .collect();
+    let mut action_names: Vec<String> = Vec::new();
+    let mut euid_match_arms: Vec<TokenStream> = Vec::new();
+    let mut creates_resource_match_arms: Vec<TokenStream> = Vec::new();
+    let mut virtual_entity_match_arms: Vec<TokenStream> = Vec::new();
+
+    for variant in &data_enum.variants {
+        let variant_name = &variant.ident;
+        let action_name_str = variant_name.to_string();
+        let mut principal_types: Vec<String> = Vec::new();
+        let mut resource_types: Vec<String> = Vec::new();
+        let mut is_create_action = false;
+        let mut name_override: Option<String> = None;
+
+        for_each_hodei_meta(&variant.attrs, |meta| {
+            if meta.path.is_ident("name") {
+                name_override = Some(action_name(&parse_str(&meta)?)?);
+            } else if meta.path.is_ident("principal") {
+                principal_types.push(type_reference(&parse_str(&meta)?)?);
+            } else if meta.path.is_ident("resource") {
+                resource_types.push(type_reference(&parse_str(&meta)?)?);
+            } else if meta.path.is_ident("creates_resource") {
+                is_create_action = true;
+            } else {
+                return Err(unknown_key(&meta, VARIANT_KEYS));
+            }
+            Ok(())
+        })?;
+
+        // Generar nombre de acción con formato ResourceType::ActionName dentro del
+        // namespace. Ej: HodeiMVP::Action::"Document::Create"
+        let resource_type = resource_types.first().ok_or_else(|| {
+            syn::Error::new_spanned(
+                variant_name,
+                format!(
+                    "la acción `{}` requiere al menos un #[hodei(resource = \"...\")]",
+                    action_name_str
+                ),
+            )
+        })?;
+        // #[hodei(name = "...")] reemplaza el nombre generado
+        let full_action_name =
+            name_override.unwrap_or_else(|| format!("{}::{}", resource_type, action_name_str));
+        if action_names.contains(&full_action_name) {
+            return Err(syn::Error::new_spanned(
+                variant_name,
+                format!("la acción `{}` está duplicada", full_action_name),
+            ));
+        }
+        action_names.push(full_action_name.clone());
+
+        let mut action_schema_json = serde_json::json!({
+            "appliesTo": {
+                "principalTypes": principal_types,
+                "resourceTypes": resource_types
+            }
+        });
+        if !member_of.is_empty() {
+            action_schema_json["memberOf"] = serde_json::json!(member_of);
+        }
+        let action_schema_str = serde_json::to_string(&action_schema_json).unwrap();
+
+        inventory_submissions.push(quote! {
+            #[cfg(feature = "schema-discovery")]
+            hodei_authz::inventory::submit! {
+                hodei_authz::ActionSchemaFragment {
+                    namespace: #namespace,
+                    name: #full_action_name,
+                    fragment_json: #action_schema_str,
+                    context_type: #context_type_expr
+                }
+            }
+        });
+
+        let fields_pattern = match &variant.fields {
+            Fields::Named(_) => quote! { {..} },
+            Fields::Unnamed(_) => quote! { (..) },
+            Fields::Unit => quote! {},
+        };
+
+        euid_match_arms.push(quote! {
+            Self::#variant_name #fields_pattern => cedar_policy::EntityUid::from_type_name_and_id(
+                #action_type_str.parse().unwrap(),
+                cedar_policy::EntityId::new(#full_action_name),
+            )
+        });
+
+        if is_create_action {
+            creates_resource_match_arms
+                .push(quote! { Self::#variant_name #fields_pattern => true });
+            match &variant.fields {
+                Fields::Unnamed(f) if f.unnamed.len() == 1 => {
+                    let context_type = context_type.as_ref().ok_or_else(|| {
+                        syn::Error::new_spanned(
+                            variant_name,
+                            "las acciones `creates_resource` requieren #[hodei(context = \"...\")] en el enum",
+                        )
+                    })?;
+                    virtual_entity_match_arms.push(quote! {
+                        Self::#variant_name(payload) => {
+                            let ctx = context.downcast_ref::<#context_type>().ok_or(
+                                hodei_authz::VirtualEntityError::InvalidContext(
+                                    std::any::type_name::<#context_type>(),
+                                ),
+                            )?;
+                            hodei_authz::HodeiVirtualEntity::<#context_type>::to_virtual_entity(payload, ctx)
+                                .map(Some)
+                        }
+                    });
+                }
+                _ => {
+                    virtual_entity_match_arms
+                        .push(quote! { Self::#variant_name #fields_pattern => Ok(None) });
+                }
+            }
+        } else {
+            creates_resource_match_arms
+                .push(quote! { Self::#variant_name #fields_pattern => false });
+        }
+    }
+
+    if virtual_entity_match_arms.len() < data_enum.variants.len() {
+        virtual_entity_match_arms.push(quote! { _ => Ok(None) });
+    }
+
+    Ok(quote! {
+        #(#inventory_submissions)*
+        impl hodei_authz::RuntimeHodeiActionMapper for #enum_name {
+            fn to_cedar_action_euid(&self) -> cedar_policy::EntityUid {
+                match self { #(#euid_match_arms,)* }
+            }
+            fn creates_resource_from_payload(&self) -> bool {
+                match self { #(#creates_resource_match_arms,)* }
+            }
+            #[allow(unused_variables)]
+            fn get_payload_as_virtual_entity(
+                &self,
+                context: &dyn std::any::Any,
+            ) -> Result<Option<cedar_policy::Entity>, hodei_authz::VirtualEntityError> {
+                match self { #(#virtual_entity_match_arms,)* }
+            }
+        }
+    })
+}
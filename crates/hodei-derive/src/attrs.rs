@@ -0,0 +1,149 @@
+//! Parseo y validación de los atributos `#[hodei(...)]`
+//!
+//! Todos los errores se devuelven como `syn::Error` con el span del atributo o
+//! campo afectado, para que el compilador los muestre en el código del usuario.
+
+use crate::types::FieldAttrs;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr, Lit, LitStr, Meta};
+
+/// Claves admitidas en `#[hodei(...)]` a nivel de campo
+const FIELD_KEYS: &[&str] = &["entity_type", "record_type", "record", "parent"];
+
+/// Recorre las claves de todos los atributos `#[hodei(...)]`
+pub(crate) fn for_each_hodei_meta(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta) -> syn::Result<()>,
+) -> syn::Result<()> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("hodei")) {
+        attr.parse_nested_meta(&mut f)?;
+    }
+    Ok(())
+}
+
+/// Lee el valor `key = "..."` de una clave
+pub(crate) fn parse_str(meta: &ParseNestedMeta) -> syn::Result<LitStr> {
+    meta.value()?.parse()
+}
+
+/// Error para una clave desconocida, con el span de la clave
+pub(crate) fn unknown_key(meta: &ParseNestedMeta, expected: &[&str]) -> syn::Error {
+    let path = &meta.path;
+    let key = quote::quote!(#path).to_string().replace(' ', "");
+    meta.error(format!(
+        "atributo `{}` desconocido, se esperaba uno de: {}",
+        key,
+        expected.join(", ")
+    ))
+}
+
+fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Segmentos de un path Cedar (`A::B::C`) si todos son identificadores
+fn path_segments(value: &str) -> Option<Vec<&str>> {
+    let segments: Vec<&str> = value.split("::").collect();
+    segments
+        .iter()
+        .all(|segment| is_identifier(segment))
+        .then_some(segments)
+}
+
+/// Valida un nombre de tipo Cedar completo (`Namespace::Type`), con uno o más
+/// segmentos de namespace (`Org::App::Type`)
+pub(crate) fn type_name(lit: &LitStr) -> syn::Result<String> {
+    let value = lit.value();
+    if path_segments(&value).is_none_or(|segments| segments.len() < 2) {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!(
+                "`{}` no es un nombre de tipo válido, se esperaba `Namespace::Type`",
+                value
+            ),
+        ));
+    }
+    Ok(value)
+}
+
+/// Valida una referencia a un tipo, con o sin namespace (`Type` o `Namespace::Type`)
+pub(crate) fn type_reference(lit: &LitStr) -> syn::Result<String> {
+    let value = lit.value();
+    if path_segments(&value).is_none() {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!(
+                "`{}` no es un nombre de tipo válido, se esperaba `Type` o `Namespace::Type`",
+                value
+            ),
+        ));
+    }
+    Ok(value)
+}
+
+/// Valida un namespace Cedar, simple (`App`) o anidado (`Org::App`)
+pub(crate) fn namespace_path(lit: &LitStr) -> syn::Result<String> {
+    let value = lit.value();
+    if path_segments(&value).is_none() {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("`{}` no es un namespace válido", value),
+        ));
+    }
+    Ok(value)
+}
+
+/// Valida un identificador Cedar (p.ej. un namespace)
+pub(crate) fn identifier(lit: &LitStr) -> syn::Result<String> {
+    let value = lit.value();
+    if !is_identifier(&value) {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("`{}` no es un identificador válido", value),
+        ));
+    }
+    Ok(value)
+}
+
+/// Lee los atributos de un campo: #[entity_type = "..."] y
+/// #[hodei(entity_type = "...", record_type = "...", record, parent)]
+pub(crate) fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+
+    // Forma abreviada: #[entity_type = "Namespace::EntityType"]
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("entity_type")) {
+        let lit = match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr_lit) => match &expr_lit.lit {
+                    Lit::Str(lit) => Some(lit),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        let lit = lit.ok_or_else(|| {
+            syn::Error::new_spanned(attr, "se esperaba #[entity_type = \"Namespace::Type\"]")
+        })?;
+        field_attrs.entity_type = Some(type_name(lit)?);
+    }
+
+    for_each_hodei_meta(attrs, |meta| {
+        if meta.path.is_ident("entity_type") {
+            field_attrs.entity_type = Some(type_name(&parse_str(&meta)?)?);
+        } else if meta.path.is_ident("record_type") {
+            field_attrs.record_type = Some(type_reference(&parse_str(&meta)?)?);
+        } else if meta.path.is_ident("record") {
+            field_attrs.record = true;
+        } else if meta.path.is_ident("parent") {
+            field_attrs.parent = true;
+        } else {
+            return Err(unknown_key(&meta, FIELD_KEYS));
+        }
+        Ok(())
+    })?;
+
+    Ok(field_attrs)
+}
@@ -0,0 +1,375 @@
+//! Generación de código para `HodeiEntity`, `HodeiRecord`, `HodeiContext` y `HodeiVirtualEntity`
+
+use crate::attrs::{
+    for_each_hodei_meta, identifier, parse_field_attrs, parse_str, type_name, unknown_key,
+};
+use crate::types::FieldType;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, FieldsNamed};
+
+/// Código generado a partir de los campos de un struct
+#[derive(Default)]
+struct NamedFields {
+    /// Atributos del schema (`shape.attributes`)
+    attributes: serde_json::Map<String, serde_json::Value>,
+    /// Sentencias que rellenan `attrs` en runtime
+    attr_map_assignments: Vec<TokenStream>,
+    /// Tipos de entidad de los campos `#[hodei(parent)]` (`memberOfTypes`)
+    parent_types: Vec<String>,
+    /// Sentencias que rellenan `parents` en runtime
+    parent_insertions: Vec<TokenStream>,
+}
+
+/// Acumula `error` en `errors`
+fn push_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// Genera el schema, las asignaciones de atributos y los padres para los campos de un struct
+///
+/// Se omite el campo `skip` (el identificador de la entidad). Los campos `#[hodei(parent)]` no
+/// se exponen como atributos sino como padres de la entidad. Los errores de todos
+/// los campos se acumulan para mostrarlos de una vez.
+fn named_field_attributes(fields: &FieldsNamed, skip: Option<&str>) -> syn::Result<NamedFields> {
+    let mut named_fields = NamedFields::default();
+    let mut errors: Option<syn::Error> = None;
+
+    for field in &fields.named {
+        let field_ident = field.ident.as_ref().unwrap();
+        let field_name = field_ident.to_string();
+        if skip == Some(field_name.as_str()) {
+            continue;
+        }
+
+        let result = parse_field_attrs(&field.attrs).and_then(|field_attrs| {
+            let field_type = FieldType::classify(field, &field_attrs)?;
+            Ok((field_attrs, field_type))
+        });
+        let (field_attrs, field_type) = match result {
+            Ok(classified) => classified,
+            Err(error) => {
+                push_error(&mut errors, error);
+                continue;
+            }
+        };
+
+        if field_attrs.parent {
+            let Some(parent_type) = field_type.parent_entity_type() else {
+                let error = syn::Error::new_spanned(
+                    &field.ty,
+                    "#[hodei(parent)] solo se admite en campos Hrn, Option<Hrn> o Vec<Hrn>",
+                );
+                push_error(&mut errors, error);
+                continue;
+            };
+            if !named_fields.parent_types.iter().any(|t| t == parent_type) {
+                named_fields.parent_types.push(parent_type.to_string());
+            }
+            named_fields
+                .parent_insertions
+                .push(field_type.parent_insertion(field_ident));
+            continue;
+        }
+
+        named_fields
+            .attributes
+            .insert(field_name.clone(), field_type.schema());
+        named_fields
+            .attr_map_assignments
+            .push(field_type.assignment(field_ident, &field_name));
+    }
+
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(named_fields),
+    }
+}
+
+/// Campos nombrados de un struct, o error si el derive se aplica a otra cosa
+fn struct_fields<'a>(ast: &'a DeriveInput, derive: &str) -> syn::Result<&'a FieldsNamed> {
+    match &ast.data {
+        Data::Struct(data_struct) => match &data_struct.fields {
+            Fields::Named(fields) => Ok(fields),
+            _ => Err(syn::Error::new_spanned(
+                &ast.ident,
+                format!("{} solo se puede derivar en structs con campos nombrados", derive),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &ast.ident,
+            format!("{} solo se puede derivar en structs con campos nombrados", derive),
+        )),
+    }
+}
+
+/// Comprueba que el struct tiene el campo `name`
+fn require_field(
+    ast: &DeriveInput,
+    fields: &FieldsNamed,
+    name: &str,
+    message: &str,
+) -> syn::Result<()> {
+    if fields
+        .named
+        .iter()
+        .any(|field| field.ident.as_ref().is_some_and(|ident| ident == name))
+    {
+        Ok(())
+    } else {
+        Err(syn::Error::new_spanned(&ast.ident, message))
+    }
+}
+
+/// Rechaza los campos `#[hodei(parent)]` en structs que no son entidades
+fn reject_parents(fields: &FieldsNamed) -> syn::Result<()> {
+    match fields.named.iter().find(|field| {
+        parse_field_attrs(&field.attrs).is_ok_and(|field_attrs| field_attrs.parent)
+    }) {
+        Some(field) => Err(syn::Error::new_spanned(
+            &field.ident,
+            "#[hodei(parent)] solo se admite en entidades (HodeiEntity)",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Lee el atributo obligatorio `#[hodei(<key> = "Namespace::Type")]` del struct
+fn required_type_attr(ast: &DeriveInput, key: &str, derive: &str) -> syn::Result<String> {
+    let mut value: Option<String> = None;
+    for_each_hodei_meta(&ast.attrs, |meta| {
+        if meta.path.is_ident(key) {
+            value = Some(type_name(&parse_str(&meta)?)?);
+            Ok(())
+        } else {
+            Err(unknown_key(&meta, &[key]))
+        }
+    })?;
+    value.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &ast.ident,
+            format!(
+                "#[derive({})] requiere #[hodei({} = \"Namespace::Type\")]",
+                derive, key
+            ),
+        )
+    })
+}
+
+pub(crate) fn expand_entity(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &ast.ident;
+    let entity_type_str = required_type_attr(ast, "entity_type", "HodeiEntity")?;
+
+    let fields = struct_fields(ast, "HodeiEntity")?;
+    require_field(ast, fields, "id", "HodeiEntity requiere un campo `id: Hrn`")?;
+
+    let NamedFields {
+        mut attributes,
+        attr_map_assignments,
+        parent_types,
+        parent_insertions,
+    } = named_field_attributes(fields, Some("id"))?;
+
+    attributes.insert(
+        "tenant_id".to_string(),
+        serde_json::json!({ "type": "String" }),
+    );
+    attributes.insert(
+        "service".to_string(),
+        serde_json::json!({ "type": "String" }),
+    );
+
+    // Cedar 4.7 usa "shape" con "type": "Record" y "attributes" dentro
+    let schema_fragment_json = serde_json::json!({
+        "memberOfTypes": parent_types,
+        "shape": {
+            "type": "Record",
+            "attributes": attributes
+        }
+    });
+    let schema_fragment_str = serde_json::to_string(&schema_fragment_json).unwrap();
+
+    Ok(quote! {
+        impl hodei_authz::RuntimeHodeiEntityMapper for #struct_name {
+            fn hodei_type_name(&self) -> &'static str { #entity_type_str }
+            fn hodei_id(&self) -> String { self.id.resource_id.clone() }
+            fn hodei_hrn(&self) -> &hodei_hrn::api::Hrn { &self.id }
+            fn to_cedar_entity(&self) -> cedar_policy::Entity {
+                let euid = self.to_cedar_euid();
+                let mut attrs = std::collections::HashMap::new();
+                #(#attr_map_assignments)*
+                attrs.insert("tenant_id".into(), cedar_policy::RestrictedExpression::new_string(self.id.tenant_id.clone()));
+                attrs.insert("service".into(), cedar_policy::RestrictedExpression::new_string(self.id.service.clone()));
+                #[allow(unused_mut)]
+                let mut parents = std::collections::HashSet::new();
+                #(#parent_insertions)*
+                cedar_policy::Entity::new(euid, attrs, parents).unwrap()
+            }
+        }
+        #[cfg(feature = "schema-discovery")]
+        hodei_authz::inventory::submit! {
+            hodei_authz::EntitySchemaFragment { entity_type: #entity_type_str, fragment_json: #schema_fragment_str, }
+        }
+    })
+}
+
+pub(crate) fn expand_record(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &ast.ident;
+    let record_type_str = required_type_attr(ast, "record_type", "HodeiRecord")?;
+
+    let fields = struct_fields(ast, "HodeiRecord")?;
+    reject_parents(fields)?;
+
+    let NamedFields {
+        attributes,
+        attr_map_assignments,
+        ..
+    } = named_field_attributes(fields, None)?;
+
+    let schema_fragment_json = serde_json::json!({
+        "type": "Record",
+        "attributes": attributes
+    });
+    let schema_fragment_str = serde_json::to_string(&schema_fragment_json).unwrap();
+
+    Ok(quote! {
+        impl hodei_authz::HodeiRecord for #struct_name {
+            fn to_cedar_record(&self) -> cedar_policy::RestrictedExpression {
+                let mut attrs = std::collections::HashMap::<String, cedar_policy::RestrictedExpression>::new();
+                #(#attr_map_assignments)*
+                cedar_policy::RestrictedExpression::new_record(attrs).unwrap()
+            }
+        }
+        #[cfg(feature = "schema-discovery")]
+        hodei_authz::inventory::submit! {
+            hodei_authz::RecordSchemaFragment { type_name: #record_type_str, fragment_json: #schema_fragment_str, }
+        }
+    })
+}
+
+pub(crate) fn expand_context(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &ast.ident;
+    let context_type_str = required_type_attr(ast, "context_type", "HodeiContext")?;
+
+    let fields = struct_fields(ast, "HodeiContext")?;
+    reject_parents(fields)?;
+
+    let NamedFields {
+        attributes,
+        attr_map_assignments,
+        ..
+    } = named_field_attributes(fields, None)?;
+
+    let schema_fragment_json = serde_json::json!({
+        "type": "Record",
+        "attributes": attributes
+    });
+    let schema_fragment_str = serde_json::to_string(&schema_fragment_json).unwrap();
+
+    Ok(quote! {
+        impl hodei_authz::HodeiContext for #struct_name {
+            const CONTEXT_TYPE: &'static str = #context_type_str;
+
+            fn to_cedar_context(&self) -> Result<cedar_policy::Context, hodei_authz::AuthorizationError> {
+                #[allow(unused_mut)]
+                let mut attrs = std::collections::HashMap::<String, cedar_policy::RestrictedExpression>::new();
+                #(#attr_map_assignments)*
+                cedar_policy::Context::from_pairs(attrs)
+                    .map_err(|e| hodei_authz::AuthorizationError::Context(e.to_string()))
+            }
+        }
+        #[cfg(feature = "schema-discovery")]
+        hodei_authz::inventory::submit! {
+            hodei_authz::RecordSchemaFragment { type_name: #context_type_str, fragment_json: #schema_fragment_str, }
+        }
+    })
+}
+
+pub(crate) fn expand_virtual_entity(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &ast.ident;
+
+    let mut entity_type: Option<String> = None;
+    let mut service: Option<String> = None;
+    let mut resource_type: Option<String> = None;
+    for_each_hodei_meta(&ast.attrs, |meta| {
+        if meta.path.is_ident("entity_type") {
+            entity_type = Some(type_name(&parse_str(&meta)?)?);
+        } else if meta.path.is_ident("service") {
+            service = Some(parse_str(&meta)?.value());
+        } else if meta.path.is_ident("resource_type") {
+            resource_type = Some(identifier(&parse_str(&meta)?)?);
+        } else {
+            return Err(unknown_key(&meta, &["entity_type", "service", "resource_type"]));
+        }
+        Ok(())
+    })?;
+    let entity_type_str = entity_type.ok_or_else(|| {
+        syn::Error::new_spanned(
+            struct_name,
+            "#[derive(HodeiVirtualEntity)] requiere #[hodei(entity_type = \"Namespace::Type\")]",
+        )
+    })?;
+    let service_str = service.ok_or_else(|| {
+        syn::Error::new_spanned(
+            struct_name,
+            "#[derive(HodeiVirtualEntity)] requiere #[hodei(service = \"...\")]",
+        )
+    })?;
+    // Por defecto el tipo de recurso del HRN es el nombre de la entidad en minúsculas
+    let resource_type_str = resource_type.unwrap_or_else(|| {
+        entity_type_str
+            .rsplit("::")
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    });
+
+    let fields = struct_fields(ast, "HodeiVirtualEntity")?;
+    require_field(
+        ast,
+        fields,
+        "resource_id",
+        "HodeiVirtualEntity requiere un campo `resource_id`",
+    )?;
+
+    let NamedFields {
+        attr_map_assignments,
+        parent_insertions,
+        ..
+    } = named_field_attributes(fields, Some("resource_id"))?;
+
+    Ok(quote! {
+        impl<Ctx> hodei_authz::HodeiVirtualEntity<Ctx> for #struct_name
+        where
+            Ctx: hodei_authz::VirtualEntityContext + ?Sized,
+        {
+            fn to_virtual_entity(
+                &self,
+                context: &Ctx,
+            ) -> Result<cedar_policy::Entity, hodei_authz::VirtualEntityError> {
+                let hrn = hodei_hrn::api::Hrn::builder()
+                    .service(#service_str)
+                    .tenant_id(context.tenant_id())
+                    .resource(&format!("{}/{}", #resource_type_str, self.resource_id))
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| hodei_authz::VirtualEntityError::Hrn(e.to_string()))?;
+                let euid = cedar_policy::EntityUid::from_type_name_and_id(
+                    #entity_type_str.parse().unwrap(),
+                    hrn.to_string().parse().unwrap(),
+                );
+                let mut attrs = std::collections::HashMap::new();
+                #(#attr_map_assignments)*
+                attrs.insert("tenant_id".into(), cedar_policy::RestrictedExpression::new_string(hrn.tenant_id.clone()));
+                attrs.insert("service".into(), cedar_policy::RestrictedExpression::new_string(hrn.service.clone()));
+                #[allow(unused_mut)]
+                let mut parents = std::collections::HashSet::new();
+                #(#parent_insertions)*
+                cedar_policy::Entity::new(euid, attrs, parents)
+                    .map_err(|e| hodei_authz::VirtualEntityError::Entity(e.to_string()))
+            }
+        }
+    })
+}
@@ -1,309 +1,70 @@
-use proc_macro::TokenStream;
-use quote::quote;
-use serde_json;
-use syn::{Data, DeriveInput, Fields, Lit, Meta, parse_macro_input};
-
-/// Extrae el tipo de entidad desde los atributos del campo
-/// Busca atributos como: #[entity_type = "MyNamespace::MyEntity"]
-fn extract_entity_type_from_attrs(attrs: &[syn::Attribute]) -> Option<String> {
-    for attr in attrs {
-        if attr.path().is_ident("entity_type") {
-            if let Meta::NameValue(meta) = &attr.meta {
-                if let syn::Expr::Lit(expr_lit) = &meta.value {
-                    if let Lit::Str(lit_str) = &expr_lit.lit {
-                        return Some(lit_str.value());
-                    }
-                }
-            }
-        }
-    }
-    None
-}
+mod action;
+mod attrs;
+mod entity;
+mod types;
 
-// Función de inferencia eliminada - ahora el atributo #[entity_type] es OBLIGATORIO
-// para todos los campos de tipo Hrn. Esto hace el sistema completamente escalable.
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
 
 #[proc_macro_derive(HodeiEntity, attributes(hodei, entity_type))]
 pub fn hodei_entity_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    let struct_name = &ast.ident;
-
-    let mut entity_type_str: Option<String> = None;
-    for attr in &ast.attrs {
-        if attr.path().is_ident("hodei") {
-            let _ = attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("entity_type") {
-                    if let Ok(Lit::Str(s)) = meta.value()?.parse() {
-                        entity_type_str = Some(s.value());
-                    }
-                }
-                Ok(())
-            });
-        }
-    }
-    let entity_type_str =
-        entity_type_str.expect("#[derive(HodeiEntity)] requiere #[hodei(entity_type = \"...\")]");
-
-    let mut attributes = serde_json::Map::new();
-    let mut attr_map_assignments: Vec<proc_macro2::TokenStream> = Vec::new();
-
-    if let Data::Struct(data_struct) = &ast.data {
-        if let Fields::Named(fields) = &data_struct.fields {
-            for field in &fields.named {
-                let field_ident = field.ident.as_ref().unwrap();
-                let field_name = field_ident.to_string();
-                if field_name == "id" {
-                    continue;
-                }
-
-                let type_ident = match &field.ty {
-                    syn::Type::Path(tp) => tp
-                        .path
-                        .segments
-                        .last()
-                        .map(|s| s.ident.to_string())
-                        .unwrap_or_else(|| "Complex".to_string()),
-                    _ => "Complex".to_string(),
-                };
-
-                // Determinar el tipo Cedar basado en el tipo Rust
-                let schema_type = if type_ident == "Hrn" {
-                    // Hrn se convierte a Entity en Cedar
-                    // El atributo #[entity_type = "Namespace::EntityType"] es OBLIGATORIO
-                    let entity_type = extract_entity_type_from_attrs(&field.attrs)
-                        .unwrap_or_else(|| {
-                            panic!(
-                                "Campo '{}' de tipo Hrn requiere el atributo #[entity_type = \"Namespace::EntityType\"]\n\
-                                Ejemplo: #[entity_type = \"MyApp::User\"]",
-                                field_name
-                            )
-                        });
-                    serde_json::json!({
-                        "type": "Entity",
-                        "name": entity_type,
-                        "required": true
-                    })
-                } else {
-                    let cedar_type = match type_ident.as_str() {
-                        "String" => "String",
-                        "i64" | "u64" | "i32" | "u32" | "usize" => "Long",
-                        "bool" => "Boolean",
-                        _ => "String",
-                    };
-                    serde_json::json!({ "type": cedar_type, "required": true })
-                };
-
-                attributes.insert(field_name.clone(), schema_type);
-
-                let value_expr = if type_ident == "Hrn" {
-                    // Convertir Hrn a EntityUid en Cedar
-                    // El atributo #[entity_type = "Namespace::EntityType"] es OBLIGATORIO
-                    let entity_type = extract_entity_type_from_attrs(&field.attrs)
-                        .unwrap_or_else(|| {
-                            panic!(
-                                "Campo '{}' de tipo Hrn requiere el atributo #[entity_type = \"Namespace::EntityType\"]\n\
-                                Ejemplo: #[entity_type = \"MyApp::User\"]",
-                                field_name
-                            )
-                        });
-                    let entity_type_lit =
-                        syn::LitStr::new(&entity_type, proc_macro2::Span::call_site());
-                    quote! {
-                        {
-                            let euid = cedar_policy::EntityUid::from_type_name_and_id(
-                                #entity_type_lit.parse().unwrap(),
-                                self.#field_ident.to_string().parse().unwrap(),
-                            );
-                            cedar_policy::RestrictedExpression::new_entity_uid(euid)
-                        }
-                    }
-                } else if type_ident == "String" {
-                    quote! { cedar_policy::RestrictedExpression::new_string(self.#field_ident.clone()) }
-                } else if type_ident == "bool" {
-                    quote! { cedar_policy::RestrictedExpression::new_bool(self.#field_ident) }
-                } else {
-                    quote! { cedar_policy::RestrictedExpression::new_long(self.#field_ident as i64) }
-                };
-
-                attr_map_assignments.push(quote! {
-                    attrs.insert(#field_name.into(), #value_expr);
-                });
-            }
-        }
-    }
+    entity::expand_entity(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    attributes.insert(
-        "tenant_id".to_string(),
-        serde_json::json!({ "type": "String" }),
-    );
-    attributes.insert(
-        "service".to_string(),
-        serde_json::json!({ "type": "String" }),
-    );
+/// Deriva `HodeiRecord` para structs anidados dentro de entidades
+///
+/// El struct se registra como common type de Cedar con el nombre indicado en
+/// `#[hodei(record_type = "Namespace::Name")]`. Las entidades lo referencian por
+/// el nombre del tipo Rust (o por `#[hodei(record_type = "...")]` en el campo).
+#[proc_macro_derive(HodeiRecord, attributes(hodei, entity_type))]
+pub fn hodei_record_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    entity::expand_record(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    // Cedar 4.7 usa "shape" con "type": "Record" y "attributes" dentro
-    let schema_fragment_json = serde_json::json!({
-        "memberOfTypes": [],
-        "shape": {
-            "type": "Record",
-            "attributes": attributes
-        }
-    });
-    let schema_fragment_str = serde_json::to_string(&schema_fragment_json).unwrap();
+/// Deriva `HodeiContext` para el contexto tipado de las peticiones
+///
+/// El struct se registra como common type con el nombre indicado en
+/// `#[hodei(context_type = "Namespace::Name")]` y las acciones que lo declaran con
+/// `#[hodei(request_context = "...")]` lo usan como `context` en el schema.
+#[proc_macro_derive(HodeiContext, attributes(hodei, entity_type))]
+pub fn hodei_context_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    entity::expand_context(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let expanded = quote! {
-        impl hodei_authz::RuntimeHodeiEntityMapper for #struct_name {
-            fn hodei_type_name(&self) -> &'static str { #entity_type_str }
-            fn hodei_id(&self) -> String { self.id.resource_id.clone() }
-            fn hodei_hrn(&self) -> &hodei_hrn::api::Hrn { &self.id }
-            fn to_cedar_entity(&self) -> cedar_policy::Entity {
-                let euid = self.to_cedar_euid();
-                let mut attrs = std::collections::HashMap::new();
-                #(#attr_map_assignments)*
-                attrs.insert("tenant_id".into(), cedar_policy::RestrictedExpression::new_string(self.id.tenant_id.clone()));
-                attrs.insert("service".into(), cedar_policy::RestrictedExpression::new_string(self.id.service.clone()));
-                cedar_policy::Entity::new(euid, attrs, std::collections::HashSet::new()).unwrap()
-            }
-        }
-        #[cfg(feature = "schema-discovery")]
-        hodei_authz::inventory::submit! {
-            hodei_authz::EntitySchemaFragment { entity_type: #entity_type_str, fragment_json: #schema_fragment_str, }
-        }
-    };
-    expanded.into()
+/// Deriva `HodeiVirtualEntity` para payloads de acciones `creates_resource`
+///
+/// El HRN del recurso se construye con `#[hodei(service = "...")]`, el tenant del
+/// contexto (`VirtualEntityContext`) y el campo `resource_id`. El resto de campos
+/// se convierten en atributos igual que en `HodeiEntity`.
+#[proc_macro_derive(HodeiVirtualEntity, attributes(hodei, entity_type))]
+pub fn hodei_virtual_entity_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    entity::expand_virtual_entity(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
+/// Deriva `RuntimeHodeiActionMapper` para enums de acciones
+///
+/// `#[hodei(context = "path::Type")]` declara el contexto con el que se construyen
+/// las entidades virtuales: las variantes `creates_resource` con un único payload
+/// lo requieren y el payload debe implementar `HodeiVirtualEntity<Type>`.
+///
+/// `#[hodei(request_context = "path::Type")]` declara el `context` de las acciones
+/// en el schema; el tipo debe implementar `HodeiContext`.
 #[proc_macro_derive(HodeiAction, attributes(hodei))]
 pub fn hodei_action_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    let enum_name = &ast.ident;
-
-    let mut namespace: Option<String> = None;
-    for attr in &ast.attrs {
-        if attr.path().is_ident("hodei") {
-            let _ = attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("namespace") {
-                    if let Ok(Lit::Str(s)) = meta.value()?.parse() {
-                        namespace = Some(s.value());
-                    }
-                }
-                Ok(())
-            });
-        }
-    }
-    let _namespace =
-        namespace.expect("#[derive(HodeiAction)] requiere #[hodei(namespace = \"...\")]");
-
-    let data_enum = match &ast.data {
-        Data::Enum(de) => de,
-        _ => panic!("HodeiAction solo se puede derivar en enums"),
-    };
-
-    let mut inventory_submissions: Vec<proc_macro2::TokenStream> = Vec::new();
-    let mut euid_match_arms: Vec<proc_macro2::TokenStream> = Vec::new();
-    let mut creates_resource_match_arms: Vec<proc_macro2::TokenStream> = Vec::new();
-    let mut virtual_entity_match_arms: Vec<proc_macro2::TokenStream> = Vec::new();
-
-    for variant in &data_enum.variants {
-        let variant_name = &variant.ident;
-        let action_name_str = variant_name.to_string();
-        let mut principal_types: Vec<String> = Vec::new();
-        let mut resource_types: Vec<String> = Vec::new();
-        let mut is_create_action = false;
-
-        for attr in &variant.attrs {
-            if attr.path().is_ident("hodei") {
-                let _ = attr.parse_nested_meta(|meta| {
-                    if meta.path.is_ident("principal") {
-                        if let Ok(Lit::Str(s)) = meta.value()?.parse() {
-                            principal_types.push(s.value());
-                        }
-                    } else if meta.path.is_ident("resource") {
-                        if let Ok(Lit::Str(s)) = meta.value()?.parse() {
-                            resource_types.push(s.value());
-                        }
-                    } else if meta.path.is_ident("creates_resource") {
-                        is_create_action = true;
-                    }
-                    Ok(())
-                });
-            }
-        }
-
-        // Generar nombre de acción con formato ResourceType::ActionName
-        // Ej: "Document::Create", "Artifact::Read"
-        let resource_type = resource_types
-            .first()
-            .expect("Action must have at least one resource type");
-        let full_action_name = format!("{}::{}", resource_type, action_name_str);
-        let action_euid_str = format!("Action::\"{}\"", full_action_name);
-
-        let action_schema_json = serde_json::json!({
-            "appliesTo": {
-                "principalTypes": principal_types,
-                "resourceTypes": resource_types
-            }
-        });
-        let action_schema_str = serde_json::to_string(&action_schema_json).unwrap();
-
-        inventory_submissions.push(quote! {
-            #[cfg(feature = "schema-discovery")]
-            hodei_authz::inventory::submit! {
-                hodei_authz::ActionSchemaFragment {
-                    name: #full_action_name,
-                    fragment_json: #action_schema_str
-                }
-            }
-        });
-
-        let fields_pattern = match &variant.fields {
-            Fields::Named(_) => quote! { {..} },
-            Fields::Unnamed(_) => quote! { (..) },
-            Fields::Unit => quote! {},
-        };
-
-        euid_match_arms.push(
-            quote! { Self::#variant_name #fields_pattern => #action_euid_str.parse().unwrap() },
-        );
-
-        if is_create_action {
-            creates_resource_match_arms
-                .push(quote! { Self::#variant_name #fields_pattern => true });
-            match &variant.fields {
-                Fields::Unnamed(f) if f.unnamed.len() == 1 => {
-                    virtual_entity_match_arms.push(quote! {
-                        Self::#variant_name(payload) => {
-                            let ctx = context.downcast_ref::<crate::RequestContext>().expect("Invalid context type");
-                            Some(payload.to_virtual_entity(ctx))
-                        }
-                    });
-                }
-                _ => {
-                    virtual_entity_match_arms
-                        .push(quote! { Self::#variant_name #fields_pattern => None });
-                }
-            }
-        } else {
-            creates_resource_match_arms
-                .push(quote! { Self::#variant_name #fields_pattern => false });
-        }
-    }
-
-    virtual_entity_match_arms.push(quote! { _ => None });
-
-    let expanded = quote! {
-        #(#inventory_submissions)*
-        impl hodei_authz::RuntimeHodeiActionMapper for #enum_name {
-            fn to_cedar_action_euid(&self) -> cedar_policy::EntityUid {
-                match self { #(#euid_match_arms,)* }
-            }
-            fn creates_resource_from_payload(&self) -> bool {
-                match self { #(#creates_resource_match_arms,)* }
-            }
-            fn get_payload_as_virtual_entity(&self, context: &dyn std::any::Any) -> Option<cedar_policy::Entity> {
-                match self { #(#virtual_entity_match_arms,)* }
-            }
-        }
-    };
-    expanded.into()
+    action::expand_action(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -0,0 +1,249 @@
+//! Mapeo de tipos Rust a tipos Cedar
+//!
+//! Cada campo se clasifica en un [`CedarType`] que sabe generar tanto su
+//! fragmento de schema como la expresión que construye su valor en runtime.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Field, GenericArgument, PathArguments, Type};
+
+/// Atributos `#[hodei(...)]` / `#[entity_type = "..."]` de un campo
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// Tipo Cedar de la entidad referenciada por un campo `Hrn`
+    pub entity_type: Option<String>,
+    /// Nombre del common type para records anidados (por defecto el nombre del tipo Rust)
+    pub record_type: Option<String>,
+    /// `#[hodei(record)]`: el campo es un record anidado (`#[derive(HodeiRecord)]`)
+    pub record: bool,
+    /// `#[hodei(parent)]`: el campo define la jerarquía (`memberOf`) en lugar de un atributo
+    pub parent: bool,
+}
+
+/// Tipo Cedar de un atributo
+pub(crate) enum CedarType {
+    String,
+    Long,
+    Bool,
+    /// Extensión `ipaddr` (campos `IpAddr`, `Ipv4Addr`, `Ipv6Addr`)
+    IpAddr,
+    /// Referencia a otra entidad (campos `Hrn`)
+    Entity(String),
+    Set(Box<CedarType>),
+    /// Record anidado que implementa `HodeiRecord`, referenciado como common type
+    Record(String),
+}
+
+/// Tipo Cedar de un campo y si es opcional (`Option<T>`)
+pub(crate) struct FieldType {
+    pub cedar_type: CedarType,
+    pub optional: bool,
+}
+
+/// Devuelve el último segmento del path de un tipo y su primer argumento genérico
+fn split_type(ty: &Type) -> Option<(String, Option<&Type>)> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    let inner = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        }),
+        _ => None,
+    };
+    Some((segment.ident.to_string(), inner))
+}
+
+impl FieldType {
+    /// Clasifica el tipo de un campo
+    pub(crate) fn classify(field: &Field, attrs: &FieldAttrs) -> syn::Result<Self> {
+        match split_type(&field.ty) {
+            Some((ident, Some(inner))) if ident == "Option" => Ok(Self {
+                cedar_type: CedarType::classify(inner, attrs, field)?,
+                optional: true,
+            }),
+            _ => Ok(Self {
+                cedar_type: CedarType::classify(&field.ty, attrs, field)?,
+                optional: false,
+            }),
+        }
+    }
+
+    /// Fragmento de schema del atributo
+    pub(crate) fn schema(&self) -> serde_json::Value {
+        let mut schema = self.cedar_type.schema();
+        schema["required"] = serde_json::json!(!self.optional);
+        schema
+    }
+
+    /// Tipo de entidad padre de un campo `Hrn`, `Option<Hrn>` o colección de `Hrn`
+    pub(crate) fn parent_entity_type(&self) -> Option<&str> {
+        match &self.cedar_type {
+            CedarType::Entity(entity_type) => Some(entity_type),
+            CedarType::Set(element) if !self.optional => match element.as_ref() {
+                CedarType::Entity(entity_type) => Some(entity_type),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Inserta los UIDs de `self.<field>` en el conjunto `parents`
+    pub(crate) fn parent_insertion(&self, field_ident: &syn::Ident) -> TokenStream {
+        let entity_type = self
+            .parent_entity_type()
+            .expect("parent_insertion solo es válido para campos Hrn");
+        match (&self.cedar_type, self.optional) {
+            (CedarType::Entity(_), false) => {
+                let euid = euid_expr(entity_type, &quote! { &self.#field_ident });
+                quote! { parents.insert(#euid); }
+            }
+            (CedarType::Entity(_), true) => {
+                let euid = euid_expr(entity_type, &quote! { value });
+                quote! {
+                    if let Some(value) = &self.#field_ident {
+                        parents.insert(#euid);
+                    }
+                }
+            }
+            _ => {
+                let euid = euid_expr(entity_type, &quote! { item });
+                quote! {
+                    for item in &self.#field_ident {
+                        parents.insert(#euid);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Inserta el valor de `self.<field>` en el mapa `attrs`, omitiéndolo si es `None`
+    pub(crate) fn assignment(&self, field_ident: &syn::Ident, attr_name: &str) -> TokenStream {
+        if self.optional {
+            let value_expr = self.cedar_type.value_expr(&quote! { value });
+            quote! {
+                if let Some(value) = &self.#field_ident {
+                    attrs.insert(#attr_name.into(), #value_expr);
+                }
+            }
+        } else {
+            let value_expr = self.cedar_type.value_expr(&quote! { &self.#field_ident });
+            quote! {
+                attrs.insert(#attr_name.into(), #value_expr);
+            }
+        }
+    }
+}
+
+/// Expresión `EntityUid` de un `Hrn` con el tipo de entidad dado
+fn euid_expr(entity_type: &str, value: &TokenStream) -> TokenStream {
+    quote! {
+        cedar_policy::EntityUid::from_type_name_and_id(
+            #entity_type.parse().unwrap(),
+            (#value).to_string().parse().unwrap(),
+        )
+    }
+}
+
+impl CedarType {
+    fn classify(ty: &Type, attrs: &FieldAttrs, field: &Field) -> syn::Result<Self> {
+        let (ident, inner) = split_type(ty)
+            .ok_or_else(|| syn::Error::new_spanned(ty, "tipo no soportado por HodeiEntity"))?;
+
+        let cedar_type = match ident.as_str() {
+            "String" => CedarType::String,
+            "bool" => CedarType::Bool,
+            "IpAddr" | "Ipv4Addr" | "Ipv6Addr" => CedarType::IpAddr,
+            "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" => CedarType::Long,
+            "u64" | "usize" | "u128" | "i128" => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    format!(
+                        "`{}` no cabe en un Long de Cedar (i64), use i64 o un entero más pequeño",
+                        ident
+                    ),
+                ));
+            }
+            "Hrn" => {
+                // El tipo de la entidad referenciada es OBLIGATORIO
+                let entity_type = attrs.entity_type.clone().ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        &field.ident,
+                        "los campos de tipo Hrn requieren #[hodei(entity_type = \"Namespace::Type\")]",
+                    )
+                })?;
+                CedarType::Entity(entity_type)
+            }
+            "Vec" | "HashSet" | "BTreeSet" => {
+                let element = inner.ok_or_else(|| {
+                    syn::Error::new_spanned(ty, "no se pudo determinar el tipo del elemento")
+                })?;
+                CedarType::Set(Box::new(CedarType::classify(element, attrs, field)?))
+            }
+            // Records anidados (#[derive(HodeiRecord)]), solo si se declaran como tales
+            _ if attrs.record || attrs.record_type.is_some() => {
+                CedarType::Record(attrs.record_type.clone().unwrap_or(ident))
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    format!(
+                        "tipo `{}` no soportado; si es un record anidado (#[derive(HodeiRecord)]) \
+                         márquelo con #[hodei(record)] o #[hodei(record_type = \"...\")]",
+                        ident
+                    ),
+                ));
+            }
+        };
+        Ok(cedar_type)
+    }
+
+    /// Fragmento de schema Cedar (formato JSON)
+    pub(crate) fn schema(&self) -> serde_json::Value {
+        match self {
+            CedarType::String => serde_json::json!({ "type": "String" }),
+            CedarType::Long => serde_json::json!({ "type": "Long" }),
+            CedarType::Bool => serde_json::json!({ "type": "Boolean" }),
+            CedarType::IpAddr => serde_json::json!({ "type": "Extension", "name": "ipaddr" }),
+            CedarType::Entity(name) => serde_json::json!({ "type": "Entity", "name": name }),
+            CedarType::Set(element) => serde_json::json!({ "type": "Set", "element": element.schema() }),
+            CedarType::Record(name) => serde_json::json!({ "type": name }),
+        }
+    }
+
+    /// Expresión que convierte `value` (una referencia al valor Rust) en `RestrictedExpression`
+    pub(crate) fn value_expr(&self, value: &TokenStream) -> TokenStream {
+        match self {
+            CedarType::String => {
+                quote! { cedar_policy::RestrictedExpression::new_string((#value).clone()) }
+            }
+            // Solo se clasifican como Long los enteros que caben en i64
+            CedarType::Long => {
+                quote! { cedar_policy::RestrictedExpression::new_long(*(#value) as i64) }
+            }
+            CedarType::Bool => {
+                quote! { cedar_policy::RestrictedExpression::new_bool(*(#value)) }
+            }
+            CedarType::IpAddr => {
+                quote! { cedar_policy::RestrictedExpression::new_ip((#value).to_string()) }
+            }
+            CedarType::Entity(entity_type) => {
+                let euid = euid_expr(entity_type, value);
+                quote! { cedar_policy::RestrictedExpression::new_entity_uid(#euid) }
+            }
+            CedarType::Set(element) => {
+                let element_expr = element.value_expr(&quote! { item });
+                quote! {
+                    cedar_policy::RestrictedExpression::new_set(
+                        (#value).iter().map(|item| #element_expr)
+                    )
+                }
+            }
+            CedarType::Record(_) => {
+                quote! { hodei_authz::HodeiRecord::to_cedar_record(#value) }
+            }
+        }
+    }
+}
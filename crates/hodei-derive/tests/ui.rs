@@ -0,0 +1,8 @@
+//! Tests de compilación de los derives (trybuild)
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    t.pass("tests/ui/pass/*.rs");
+}
@@ -0,0 +1,7 @@
+use hodei_derive::HodeiAction;
+
+#[derive(HodeiAction)]
+#[hodei(namespace = "App")]
+struct ReadDocument;
+
+fn main() {}
@@ -0,0 +1,10 @@
+use hodei_derive::HodeiAction;
+
+#[derive(HodeiAction)]
+#[hodei(namespace = "App")]
+enum DocumentAction {
+    #[hodei(principal = "User")]
+    Read,
+}
+
+fn main() {}
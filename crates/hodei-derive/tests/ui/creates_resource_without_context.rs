@@ -0,0 +1,10 @@
+use hodei_derive::HodeiAction;
+
+#[derive(HodeiAction)]
+#[hodei(namespace = "App")]
+enum DocumentAction {
+    #[hodei(principal = "User", resource = "Document", creates_resource)]
+    Create(String),
+}
+
+fn main() {}
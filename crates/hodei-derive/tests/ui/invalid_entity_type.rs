@@ -0,0 +1,10 @@
+use hodei_derive::HodeiEntity;
+use hodei_hrn::Hrn;
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "User")]
+struct User {
+    id: Hrn,
+}
+
+fn main() {}
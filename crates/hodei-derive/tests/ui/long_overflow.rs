@@ -0,0 +1,11 @@
+use hodei_derive::HodeiEntity;
+use hodei_hrn::Hrn;
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "App::Document")]
+struct Document {
+    id: Hrn,
+    size: u64,
+}
+
+fn main() {}
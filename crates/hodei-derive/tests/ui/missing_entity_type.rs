@@ -0,0 +1,9 @@
+use hodei_derive::HodeiEntity;
+use hodei_hrn::Hrn;
+
+#[derive(HodeiEntity)]
+struct User {
+    id: Hrn,
+}
+
+fn main() {}
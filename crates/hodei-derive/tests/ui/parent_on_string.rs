@@ -0,0 +1,12 @@
+use hodei_derive::HodeiEntity;
+use hodei_hrn::Hrn;
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "App::User")]
+struct User {
+    id: Hrn,
+    #[hodei(parent)]
+    group: String,
+}
+
+fn main() {}
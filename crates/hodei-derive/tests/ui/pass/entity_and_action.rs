@@ -0,0 +1,80 @@
+use hodei_authz::{
+    HodeiAction, HodeiContext, HodeiEntity, HodeiRecord, HodeiVirtualEntity, VirtualEntityContext,
+};
+use std::net::IpAddr;
+use hodei_hrn::Hrn;
+
+#[derive(HodeiRecord)]
+#[hodei(record_type = "App::Address")]
+struct Address {
+    city: String,
+}
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "App::User")]
+struct User {
+    id: Hrn,
+    #[entity_type = "App::User"]
+    manager: Option<Hrn>,
+    #[hodei(parent, entity_type = "App::Group")]
+    groups: Vec<Hrn>,
+    #[hodei(record)]
+    address: Address,
+}
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "Org::App::Team")]
+struct Team {
+    id: Hrn,
+}
+
+#[derive(HodeiContext)]
+#[hodei(context_type = "App::RequestContext")]
+struct RequestContext {
+    tenant_id: String,
+    source_ip: IpAddr,
+    mfa: bool,
+}
+
+impl VirtualEntityContext for RequestContext {
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+}
+
+#[derive(HodeiVirtualEntity)]
+#[hodei(entity_type = "App::Document", service = "documents-api")]
+struct DocumentCreatePayload {
+    resource_id: String,
+    #[hodei(entity_type = "App::User")]
+    owner_id: Option<Hrn>,
+}
+
+#[derive(HodeiAction)]
+#[hodei(namespace = "App", context = "RequestContext", request_context = "RequestContext")]
+enum DocumentAction {
+    #[hodei(principal = "User", resource = "Document", creates_resource)]
+    Create(DocumentCreatePayload),
+    #[hodei(principal = "User", resource = "Document")]
+    Read,
+}
+
+// A virtual-entity context does not have to be a request context
+struct TenantContext {
+    tenant_id: String,
+}
+
+impl VirtualEntityContext for TenantContext {
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+}
+
+#[derive(HodeiAction)]
+#[hodei(namespace = "App", context = "TenantContext")]
+enum ImportAction {
+    #[hodei(principal = "User", resource = "Document", creates_resource)]
+    Import(DocumentCreatePayload),
+}
+
+fn main() {}
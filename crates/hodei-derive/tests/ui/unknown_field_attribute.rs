@@ -0,0 +1,12 @@
+use hodei_derive::HodeiEntity;
+use hodei_hrn::Hrn;
+
+#[derive(HodeiEntity)]
+#[hodei(entity_type = "App::Document")]
+struct Document {
+    id: Hrn,
+    #[hodei(entty_type = "App::User")]
+    owner: Hrn,
+}
+
+fn main() {}
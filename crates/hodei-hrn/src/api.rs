@@ -12,6 +12,8 @@ pub enum HrnError {
     InvalidResourcePart,
     #[error("Parte requerida del HRN no especificada: {0}")]
     MissingPart(String),
+    #[error("Patrón de HRN inválido: {0}")]
+    InvalidPattern(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -97,15 +99,15 @@ impl FromStr for Hrn {
         if !s.starts_with("hrn:") { return Err(HrnError::InvalidPrefix); }
         let parts: Vec<&str> = s[4..].split(':').collect();
         if parts.len() != 5 { return Err(HrnError::InvalidFormat); }
-        let resource_parts: Vec<&str> = parts[4].split('/').collect();
-        if resource_parts.len() != 2 { return Err(HrnError::InvalidResourcePart); }
+        // Como en el builder, el id puede contener '/' (p. ej. rutas de carpetas)
+        let (resource_type, resource_id) = parts[4].split_once('/').ok_or(HrnError::InvalidResourcePart)?;
         Ok(Hrn {
             partition: parts[0].to_string(),
             service: parts[1].to_string(),
             region: parts[2].to_string(),
             tenant_id: parts[3].to_string(),
-            resource_type: resource_parts[0].to_string(),
-            resource_id: resource_parts[1].to_string(),
+            resource_type: resource_type.to_string(),
+            resource_id: resource_id.to_string(),
         })
     }
 }
@@ -119,7 +121,10 @@ impl sqlx::Type<sqlx::Postgres> for Hrn {
 
 #[cfg(feature = "sqlx")]
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Hrn {
-    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
         let hrn_string = self.to_string();
         <String as sqlx::Encode<sqlx::Postgres>>::encode_by_ref(&hrn_string, buf)
     }
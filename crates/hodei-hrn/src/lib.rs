@@ -1,3 +1,5 @@
 pub mod api;
+pub mod pattern;
 
 pub use api::{HrnError, Hrn, HrnBuilder};
+pub use pattern::HrnPattern;
@@ -0,0 +1,271 @@
+//! HRN patterns, e.g. for grants over "every document of tenant-1"
+
+use crate::api::{Hrn, HrnError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    Any,
+    Literal(String),
+}
+
+impl Segment {
+    fn parse(s: &str) -> Result<Self, HrnError> {
+        match s {
+            "*" => Ok(Segment::Any),
+            _ if s.contains('*') => Err(HrnError::InvalidPattern(format!(
+                "'{}': '*' debe ocupar una parte completa y '**' solo puede ir al final",
+                s
+            ))),
+            _ => Ok(Segment::Literal(s.to_string())),
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Segment::Any => true,
+            Segment::Literal(literal) => literal == value,
+        }
+    }
+
+    fn push_like(&self, like: &mut String) {
+        match self {
+            Segment::Any => like.push('%'),
+            Segment::Literal(literal) => {
+                for c in literal.chars() {
+                    if matches!(c, '\\' | '%' | '_') {
+                        like.push('\\');
+                    }
+                    like.push(c);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Any => f.write_str("*"),
+            Segment::Literal(literal) => f.write_str(literal),
+        }
+    }
+}
+
+/// Pattern matching a set of HRNs
+///
+/// It has the HRN format, where any part can be `*`. The resource part is a
+/// path (`type/id`, and ids may contain `/`): `*` matches one segment of it
+/// and a final `**` one or more segments.
+///
+/// ```
+/// use hodei_hrn::{Hrn, HrnPattern};
+///
+/// let documents: HrnPattern = "hrn:hodei:docapp:*:tenant-1:document/*".parse().unwrap();
+/// let reports: HrnPattern = "hrn:hodei:docapp:global:tenant-1:folder/reports/**".parse().unwrap();
+///
+/// let hrn: Hrn = "hrn:hodei:docapp:global:tenant-1:document/doc-1".parse().unwrap();
+/// assert!(documents.matches(&hrn));
+///
+/// let hrn: Hrn = "hrn:hodei:docapp:global:tenant-1:folder/reports/2024/q1".parse().unwrap();
+/// assert!(reports.matches(&hrn));
+/// ```
+///
+/// It is (de)serialized as its string form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HrnPattern {
+    /// Partition, service, region and tenant
+    parts: [Segment; 4],
+    /// Resource type followed by the segments of the id
+    path: Vec<Segment>,
+    /// The path ends in `**`
+    descendants: bool,
+}
+
+impl HrnPattern {
+    /// Whether `hrn` is one of the HRNs described by the pattern
+    pub fn matches(&self, hrn: &Hrn) -> bool {
+        let parts = [&hrn.partition, &hrn.service, &hrn.region, &hrn.tenant_id];
+        if !self.parts.iter().zip(parts).all(|(segment, part)| segment.matches(part)) {
+            return false;
+        }
+
+        let path: Vec<&str> = iter::once(hrn.resource_type.as_str())
+            .chain(hrn.resource_id.split('/'))
+            .collect();
+        let length_matches = if self.descendants {
+            path.len() > self.path.len()
+        } else {
+            path.len() == self.path.len()
+        };
+        length_matches
+            && self.path.iter().zip(path).all(|(segment, part)| segment.matches(part))
+    }
+
+    /// SQL `LIKE` pattern, with `\` as escape character, matching the text of
+    /// every HRN the pattern matches
+    ///
+    /// `*` becomes `%`, which also matches `/`, so the `LIKE` alone may keep
+    /// HRNs with extra resource segments.
+    pub fn like_pattern(&self) -> String {
+        let mut like = String::from("hrn:");
+        for segment in &self.parts {
+            segment.push_like(&mut like);
+            like.push(':');
+        }
+        for (i, segment) in self.path.iter().enumerate() {
+            if i > 0 {
+                like.push('/');
+            }
+            segment.push_like(&mut like);
+        }
+        if self.descendants {
+            like.push_str("/%");
+        }
+        like
+    }
+
+    /// Returns false if the condition of `push_like` (with the `sqlx`
+    /// feature) may keep HRNs the pattern does not match, which must then be
+    /// checked with [`matches`](Self::matches)
+    ///
+    /// That happens with a `*` followed by a literal segment before a `**`,
+    /// as in `folder/*/reports/**`.
+    pub fn is_like_exact(&self) -> bool {
+        !self.descendants
+            || self
+                .path
+                .iter()
+                .skip_while(|segment| **segment != Segment::Any)
+                .all(|segment| *segment == Segment::Any)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl HrnPattern {
+    /// Append a condition selecting the HRNs stored as text in `column`
+    ///
+    /// Besides the `LIKE`, patterns without `**` bound the number of resource
+    /// segments. See [`is_like_exact`](Self::is_like_exact).
+    ///
+    /// ```rust,ignore
+    /// let mut query = QueryBuilder::new("SELECT * FROM documents WHERE ");
+    /// pattern.push_like("hrn", &mut query);
+    /// ```
+    pub fn push_like(&self, column: &str, query: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>) {
+        query.push(format!("({} LIKE ", column));
+        query.push_bind(self.like_pattern());
+        query.push(" ESCAPE '\\'");
+        if !self.descendants {
+            // One more '/' than the pattern
+            let deeper = vec!["%"; self.path.len() + 1].join("/");
+            query.push(format!(" AND {} NOT LIKE ", column));
+            query.push_bind(format!("hrn:%:%:%:%:{}", deeper));
+        }
+        query.push(")");
+    }
+}
+
+impl From<Hrn> for HrnPattern {
+    /// Pattern matching only `hrn`
+    fn from(hrn: Hrn) -> Self {
+        Self {
+            parts: [hrn.partition, hrn.service, hrn.region, hrn.tenant_id].map(Segment::Literal),
+            path: iter::once(hrn.resource_type.as_str())
+                .chain(hrn.resource_id.split('/'))
+                .map(|part| Segment::Literal(part.to_string()))
+                .collect(),
+            descendants: false,
+        }
+    }
+}
+
+impl fmt::Display for HrnPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("hrn")?;
+        for segment in &self.parts {
+            write!(f, ":{}", segment)?;
+        }
+        for (i, segment) in self.path.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { ":" } else { "/" }, segment)?;
+        }
+        if self.descendants {
+            f.write_str("/**")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HrnPattern {
+    type Err = HrnError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s.strip_prefix("hrn:").ok_or(HrnError::InvalidPrefix)?;
+        let parts: Vec<&str> = rest.split(':').collect();
+        if parts.len() != 5 {
+            return Err(HrnError::InvalidFormat);
+        }
+        if !parts[4].contains('/') {
+            return Err(HrnError::InvalidResourcePart);
+        }
+
+        let mut path: Vec<&str> = parts[4].split('/').collect();
+        let descendants = path.last() == Some(&"**");
+        if descendants {
+            path.pop();
+        }
+
+        Ok(HrnPattern {
+            parts: [
+                Segment::parse(parts[0])?,
+                Segment::parse(parts[1])?,
+                Segment::parse(parts[2])?,
+                Segment::parse(parts[3])?,
+            ],
+            path: path.into_iter().map(Segment::parse).collect::<Result<_, _>>()?,
+            descendants,
+        })
+    }
+}
+
+impl TryFrom<String> for HrnPattern {
+    type Error = HrnError;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<HrnPattern> for String {
+    fn from(pattern: HrnPattern) -> Self {
+        pattern.to_string()
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl sqlx::Type<sqlx::Postgres> for HrnPattern {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("TEXT")
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'q> sqlx::Encode<'q, sqlx::Postgres> for HrnPattern {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        let pattern_string = self.to_string();
+        <String as sqlx::Encode<sqlx::Postgres>>::encode_by_ref(&pattern_string, buf)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for HrnPattern {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let str_value = <String as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        Ok(str_value.parse()?)
+    }
+}
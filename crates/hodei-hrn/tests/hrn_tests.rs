@@ -123,3 +123,13 @@ fn test_hrn_clone() {
     let cloned = hrn.clone();
     assert_eq!(hrn, cloned);
 }
+
+#[test]
+fn test_hrn_complex_resource_id_roundtrip() {
+    let hrn_str = "hrn:hodei:docs:global:tenant-1:folder/reports/2024/q1.pdf";
+    let hrn: Hrn = hrn_str.parse().unwrap();
+    
+    assert_eq!(hrn.resource_type, "folder");
+    assert_eq!(hrn.resource_id, "reports/2024/q1.pdf");
+    assert_eq!(hrn.to_string(), hrn_str);
+}
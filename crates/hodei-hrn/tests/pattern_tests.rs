@@ -0,0 +1,113 @@
+//! Tests for HRN patterns
+
+use hodei_hrn::{Hrn, HrnError, HrnPattern};
+
+fn hrn(s: &str) -> Hrn {
+    s.parse().unwrap()
+}
+
+fn pattern(s: &str) -> HrnPattern {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_pattern_wildcard_parts() {
+    let pattern = pattern("hrn:hodei:docapp:*:tenant-1:document/*");
+    
+    assert!(pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:document/doc-1")));
+    assert!(pattern.matches(&hrn("hrn:hodei:docapp:eu-west:tenant-1:document/doc-2")));
+    assert!(!pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-2:document/doc-1")));
+    assert!(!pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:folder/doc-1")));
+    // '*' is a single segment
+    assert!(!pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:document/a/b")));
+}
+
+#[test]
+fn test_pattern_descendants() {
+    let pattern = pattern("hrn:hodei:docapp:global:tenant-1:folder/reports/**");
+    
+    assert!(pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:folder/reports/q1")));
+    assert!(pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:folder/reports/2024/q1")));
+    assert!(!pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:folder/reports")));
+    assert!(!pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:folder/reports-old/q1")));
+}
+
+#[test]
+fn test_pattern_from_hrn_matches_only_itself() {
+    let original = hrn("hrn:hodei:docapp:global:tenant-1:folder/reports/q1");
+    let pattern = HrnPattern::from(original.clone());
+    
+    assert!(pattern.matches(&original));
+    assert!(!pattern.matches(&hrn("hrn:hodei:docapp:global:tenant-1:folder/reports/q2")));
+    assert_eq!(pattern.to_string(), original.to_string());
+}
+
+#[test]
+fn test_pattern_invalid() {
+    assert!(matches!("urn:a:b:c:d:e/f".parse::<HrnPattern>(), Err(HrnError::InvalidPrefix)));
+    assert!(matches!("hrn:a:b:c:e/f".parse::<HrnPattern>(), Err(HrnError::InvalidFormat)));
+    assert!(matches!("hrn:a:b:c:d:document".parse::<HrnPattern>(), Err(HrnError::InvalidResourcePart)));
+    assert!(matches!("hrn:a:b:c:tenant-*:e/f".parse::<HrnPattern>(), Err(HrnError::InvalidPattern(_))));
+    assert!(matches!("hrn:a:b:c:d:folder/**/f".parse::<HrnPattern>(), Err(HrnError::InvalidPattern(_))));
+}
+
+#[test]
+fn test_pattern_serialization() {
+    let text = "hrn:hodei:*:global:tenant-1:folder/*/**";
+    let pattern = pattern(text);
+    
+    assert_eq!(pattern.to_string(), text);
+    
+    let json = serde_json::to_string(&pattern).unwrap();
+    assert_eq!(json, format!("\"{}\"", text));
+    let deserialized: HrnPattern = serde_json::from_str(&json).unwrap();
+    assert_eq!(pattern, deserialized);
+    
+    assert!(serde_json::from_str::<HrnPattern>("\"hrn:bad\"").is_err());
+}
+
+#[test]
+fn test_pattern_like() {
+    assert_eq!(
+        pattern("hrn:hodei:docapp:*:tenant-1:document/*").like_pattern(),
+        "hrn:hodei:docapp:%:tenant-1:document/%"
+    );
+    assert_eq!(
+        pattern("hrn:hodei:docapp:global:tenant_1:folder/100%/**").like_pattern(),
+        r"hrn:hodei:docapp:global:tenant\_1:folder/100\%/%"
+    );
+}
+
+#[test]
+fn test_pattern_like_exactness() {
+    assert!(pattern("hrn:hodei:docapp:*:tenant-1:document/*").is_like_exact());
+    assert!(pattern("hrn:hodei:docapp:*:tenant-1:folder/*/**").is_like_exact());
+    assert!(!pattern("hrn:hodei:docapp:*:tenant-1:folder/*/reports/**").is_like_exact());
+}
+
+#[cfg(feature = "sqlx")]
+#[test]
+fn test_pattern_push_like() {
+    let mut query = sqlx::QueryBuilder::new("SELECT * FROM documents WHERE ");
+    pattern("hrn:hodei:docapp:*:tenant-1:document/*").push_like("hrn", &mut query);
+    assert_eq!(
+        query.sql(),
+        r"SELECT * FROM documents WHERE (hrn LIKE $1 ESCAPE '\' AND hrn NOT LIKE $2)"
+    );
+    
+    let mut query = sqlx::QueryBuilder::new("");
+    pattern("hrn:hodei:docapp:*:tenant-1:folder/reports/**").push_like("d.hrn", &mut query);
+    assert_eq!(query.sql(), r"(d.hrn LIKE $1 ESCAPE '\')");
+}
+
+#[cfg(feature = "sqlx")]
+#[test]
+fn test_pattern_encodes_as_text() {
+    use sqlx::Encode;
+    
+    let pattern = pattern("hrn:hodei:docapp:*:tenant-1:document/*");
+    let mut buf = sqlx::postgres::PgArgumentBuffer::default();
+    let is_null = Encode::<sqlx::Postgres>::encode_by_ref(&pattern, &mut buf).unwrap();
+    assert!(matches!(is_null, sqlx::encode::IsNull::No));
+    assert_eq!(&buf[..], pattern.to_string().as_bytes());
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use hodei_authz::HodeiAction;
+use hodei_authz::{HodeiAction, HodeiVirtualEntity, VirtualEntityError};
 use cedar_policy::{Entity, EntityUid};
 use hodei_hrn::api::Hrn;
 
@@ -8,7 +8,7 @@ use hodei_hrn::api::Hrn;
 // ============================================================================
 
 #[derive(Debug, Clone, HodeiAction)]
-#[hodei(namespace = "HodeiMVP")]
+#[hodei(namespace = "HodeiMVP", context = "crate::RequestContext", request_context = "crate::RequestContext")]
 pub enum ArtifactCommand {
     #[hodei(principal = "User", resource = "Artifact", creates_resource)]
     Create(ArtifactCreatePayload),
@@ -30,13 +30,14 @@ pub struct ArtifactCreatePayload {
     pub version: String,
 }
 
-impl ArtifactCreatePayload {
-    pub fn to_virtual_entity(&self, context: &crate::internal::domain::RequestContext) -> Entity {
+impl HodeiVirtualEntity<crate::RequestContext> for ArtifactCreatePayload {
+    fn to_virtual_entity(&self, context: &crate::RequestContext) -> Result<Entity, VirtualEntityError> {
         let hrn = Hrn::builder()
             .service("artifacts-api")
             .tenant_id(&context.tenant_id)
-            .resource(&format!("artifact/{}", self.resource_id)).unwrap()
-            .build().unwrap();
+            .resource(&format!("artifact/{}", self.resource_id))
+            .and_then(|builder| builder.build())
+            .map_err(|e| VirtualEntityError::Hrn(e.to_string()))?;
 
         let euid = EntityUid::from_type_name_and_id(
             "HodeiMVP::Artifact".parse().unwrap(),
@@ -50,8 +51,9 @@ impl ArtifactCreatePayload {
         let placeholder_user_hrn = Hrn::builder()
             .service("users-api")
             .tenant_id(&context.tenant_id)
-            .resource("user/placeholder").unwrap()
-            .build().unwrap();
+            .resource("user/placeholder")
+            .and_then(|builder| builder.build())
+            .map_err(|e| VirtualEntityError::Hrn(e.to_string()))?;
 
         let creator_euid = EntityUid::from_type_name_and_id(
                 "HodeiMVP::User".parse().unwrap(),
@@ -76,7 +78,8 @@ impl ArtifactCreatePayload {
         attrs.insert("tenant_id".into(), cedar_policy::RestrictedExpression::new_string(context.tenant_id.clone()));
         attrs.insert("service".into(), cedar_policy::RestrictedExpression::new_string("artifacts-api".to_string()));
 
-        Entity::new(euid, attrs, std::collections::HashSet::new()).unwrap()
+        Entity::new(euid, attrs, std::collections::HashSet::new())
+            .map_err(|e| VirtualEntityError::Entity(e.to_string()))
     }
 }
 
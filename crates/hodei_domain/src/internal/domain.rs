@@ -1,14 +1,21 @@
 use serde::{Deserialize, Serialize};
-use hodei_authz::HodeiEntity;
+use hodei_authz::{HodeiContext, HodeiEntity, VirtualEntityContext};
 use cedar_policy::{Entity, EntityUid};
 use hodei_hrn::api::Hrn;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, HodeiContext)]
+#[hodei(context_type = "HodeiMVP::RequestContext")]
 pub struct RequestContext {
     pub ip_address: String,
     pub tenant_id: String,
 }
 
+impl VirtualEntityContext for RequestContext {
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, HodeiEntity, sqlx::FromRow)]
 #[hodei(entity_type = "HodeiMVP::User")]
 pub struct User {
@@ -89,9 +96,9 @@ pub struct Artifact {
     #[entity_type = "HodeiMVP::User"]
     pub updated_by: Hrn,
 
-    /// Documento asociado - TODO: hacer opcional cuando el macro soporte Option<Hrn>
+    /// Documento asociado (opcional)
     #[entity_type = "HodeiMVP::Document"]
-    pub document_id: Hrn,
+    pub document_id: Option<Hrn>,
 
     pub name: String,
     pub artifact_type: String,